@@ -0,0 +1,99 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct WindowActivity {
+    pub app: String,
+    pub title: String,
+    pub seconds: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Bucket {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Event {
+    duration: f64,
+    data: EventData,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventData {
+    #[serde(default)]
+    app: String,
+    #[serde(default)]
+    title: String,
+}
+
+// top_windows on its own thread, so a slow or stopped server doesn't hold up the UI
+pub fn top_windows_in_background(
+    base_url: &str,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    limit: usize,
+) -> Receiver<std::result::Result<Vec<WindowActivity>, String>> {
+    let (tx, rx) = mpsc::channel();
+    let base_url = base_url.to_string();
+    std::thread::spawn(move || {
+        let result = top_windows(&base_url, start, end, limit).map_err(|e| e.to_string());
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+// Ask the local ActivityWatch server which windows were focused between `start` and `end`,
+// summed per app/title and sorted by time spent.
+pub fn top_windows(
+    base_url: &str,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    limit: usize,
+) -> Result<Vec<WindowActivity>> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_millis(800))
+        .build();
+    let base_url = base_url.trim_end_matches('/');
+
+    let buckets: HashMap<String, Bucket> = agent
+        .get(&format!("{}/api/0/buckets/", base_url))
+        .call()?
+        .into_json()?;
+    let bucket_id = buckets
+        .iter()
+        .find(|(_, b)| b.kind == "currentwindow")
+        .map(|(id, _)| id.clone())
+        .ok_or_else(|| anyhow!("no window watcher bucket found"))?;
+
+    let events: Vec<Event> = agent
+        .get(&format!("{}/api/0/buckets/{}/events", base_url, bucket_id))
+        .query("start", &start.to_rfc3339())
+        .query("end", &end.to_rfc3339())
+        .call()?
+        .into_json()?;
+
+    let mut totals: HashMap<(String, String), f64> = HashMap::new();
+    for event in events {
+        *totals
+            .entry((event.data.app, event.data.title))
+            .or_insert(0.0) += event.duration;
+    }
+
+    let mut windows: Vec<WindowActivity> = totals
+        .into_iter()
+        .map(|((app, title), seconds)| WindowActivity {
+            app,
+            title,
+            seconds,
+        })
+        .collect();
+    windows.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+    windows.truncate(limit);
+    Ok(windows)
+}
@@ -1,15 +1,56 @@
 use anyhow::Result;
 use arboard::Clipboard;
-use crossterm::event::{self, KeyEventKind};
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use crossterm::event::{self, DisableBracketedPaste, EnableBracketedPaste, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{self as term, EnterAlternateScreen, LeaveAlternateScreen};
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
+use std::collections::{HashMap, HashSet};
 use std::io;
 
-use crate::config::Config;
-use crate::time_entry::TimeEntry;
+use crate::activitywatch::{self, WindowActivity};
+use crate::archive::{self, Day};
+use crate::backup;
+use crate::budget::{self, BudgetUsage};
+use crate::changelog::{self, Release};
+use crate::checks;
+use crate::clock::{ClockJump, ClockWatch};
+use crate::coalesce::{self, Merge};
+use crate::columns::{FetchedAllowlists, InputRules};
+use crate::config::{self, Config, Keymap, NewRow, PaneLayout, QuitBinding};
+use crate::day_summary;
+use crate::dedupe::{self, Duplicate};
+use crate::demo::{self, DemoDir};
+use crate::editor::FieldEditor;
+use crate::email;
+use crate::export::{self, ExportJob, ExportScope};
+use crate::external_editor;
+use crate::fill::{self, Gap};
+use crate::follow::FileWatch;
+use crate::history::{HistorySearch, TaskDescriptions};
+use crate::hooks::{self, ExportSummary};
+use crate::links;
+use crate::logging;
+use crate::mapping;
+use crate::paths;
+use crate::repair::{self, LoadProblem};
+use crate::replace::{Change, Replace};
+use crate::schema;
+use crate::sheet::Saved;
+use crate::snippets;
+use crate::stats::{self, Timings};
+use crate::status_file::{self, TimerStatus};
+use crate::text;
+use crate::theme::Theme;
+use crate::time_entry::{self, TimeEntry};
+use crate::totals::{self, TaskTotal};
+use crate::trash::{self, TrashedEntry};
 use crate::ui;
-use serde_json;
+use crate::upload;
+use crate::validate::{self, Issue};
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub enum InputMode {
@@ -20,8 +61,89 @@ pub enum InputMode {
     Help,
     ConfirmDeleteEntry,
     ConfirmClearEntries,
+    Report,
+    Trash,
+    Log,
+    ExportMenu,
+    Prompt,
+    ConfirmQuit,
+    ConfirmExport,
+    TimePicker,
+    ConfirmCoalesce,
+    ConfirmDedupe,
+    ConfirmFill,
+    ConfirmReplace,
+    History,
+    Repair,
+    Rollover,
+    ClockJump,
+    PickDescription,
+    WhatsNew,
+    Validation,
+    Debug,
 }
 
+// Earlier descriptions of a task number, offered when it's typed into a row without one
+#[derive(Debug, Clone)]
+pub struct DescriptionPick {
+    pub row: usize,
+    pub task: String,
+    pub descriptions: Vec<String>,
+    pub selected: usize,
+    resume: InputMode, // Where the keys go once one is picked or skipped
+}
+
+// Descriptions offered for a task number
+const DESCRIPTION_CHOICES: usize = 3;
+
+// What a line of text typed into the prompt is for
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromptKind {
+    SaveAs,
+    Command,
+    ZipPassphrase,
+    ReportFrom,
+    ReportTo,
+    Filter,
+    DayNote,
+}
+
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+impl PromptKind {
+    // Input that shouldn't be shown on screen
+    pub fn is_secret(&self) -> bool {
+        matches!(self, PromptKind::ZipPassphrase)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PromptKind::SaveAs => "Save entries to",
+            PromptKind::Command => "Command",
+            PromptKind::ZipPassphrase => "Passphrase for the export zip",
+            PromptKind::ReportFrom => "Report from (YYYY-MM-DD)",
+            PromptKind::ReportTo => "Report to (YYYY-MM-DD)",
+            PromptKind::Filter => "Show rows containing (empty shows all)",
+            PromptKind::DayNote => "Note for today (empty removes it)",
+        }
+    }
+}
+
+// Lines of the log the log screen loads
+const LOG_VIEW_LINES: usize = 500;
+// Latest errors and warnings from the log on the debug screen
+const DEBUG_PROBLEMS: usize = 5;
+
+// Consecutive failed saves before the app switches to degraded mode
+const DEGRADED_AFTER_FAILURES: usize = 3;
+
+// Days shown in the report view's hours chart
+const RECENT_DAYS: i64 = 14;
+
 #[derive(Debug, Clone)]
 pub struct Cursor {
     pub row: usize,
@@ -34,6 +156,43 @@ impl Cursor {
     }
 }
 
+// Start/End time being picked with the arrow keys instead of typed
+#[derive(Debug, Clone, Copy)]
+pub struct TimePicker {
+    pub time: NaiveTime,
+    pub minutes_selected: bool, // Up/Down change the minutes, otherwise the hours
+}
+
+impl TimePicker {
+    // Hours move by one, minutes by `step` and snap to its multiples, e.g. 09:07 up to 09:10
+    fn adjust(&mut self, up: bool, step: u32) {
+        let delta = if self.minutes_selected {
+            let step = i64::from(step.max(1));
+            let offset = i64::from(self.time.minute()) % step;
+            match (up, offset) {
+                (true, _) => step - offset,
+                (false, 0) => -step,
+                (false, _) => -offset,
+            }
+        } else if up {
+            60
+        } else {
+            -60
+        };
+        self.time = self
+            .time
+            .overflowing_add_signed(chrono::Duration::minutes(delta))
+            .0;
+    }
+}
+
+// Window activity reported by ActivityWatch for one row's time range
+pub struct ActivityHints {
+    pub row: usize,
+    pub range: (String, String),
+    pub windows: Option<std::result::Result<Vec<WindowActivity>, String>>, // None while loading
+}
+
 pub struct App {
     pub entries: Vec<TimeEntry>,
     pub cursor: Cursor,
@@ -41,16 +200,242 @@ pub struct App {
     pub config: Config,
     pub should_quit: bool,
     pub popup_scroll: usize,
-    pub text_cursor: usize,   // Position within the current text field
+    pub editor: FieldEditor, // Text, cursor and undo history of the current field
     pub pending_delete: bool, // Track if first 'd' was pressed for 'dd' command
     pub status_message: Option<String>, // Temporary status message
     pub message_timer: Option<std::time::Instant>, // Timer for status message
+    pub activity_hints: Option<ActivityHints>,
+    activity_job:
+        Option<std::sync::mpsc::Receiver<std::result::Result<Vec<WindowActivity>, String>>>,
+    allowlist_job: Option<std::sync::mpsc::Receiver<FetchedAllowlists>>, // URL allowlists being fetched
+    pub data_dir: PathBuf,
+    pub archived_days: Vec<Day>, // History needed for budget periods
+    pub trash: Vec<TrashedEntry>,
+    pub trash_selected: usize,
+    pub project: Option<String>,
+    pub dirty: bool, // Entries changed since the last save
+    pub last_saved: Option<std::time::Instant>,
+    saved_hashes: HashMap<String, String>, // Content by entry ID as last read or saved, for stamping changes
+    pub save_failures: Vec<(chrono::DateTime<chrono::Local>, String)>, // Journal of failed saves
+    pub degraded: bool,                    // Saving keeps failing, entries only live in memory
+    pub prompt: Option<Prompt>,
+    pub report_range: (NaiveDate, NaiveDate),
+    pub report_days: Vec<Day>, // Days inside report_range, today from the live entries
+    pub report_by_hour: bool, // The report chart shows the range's hours of the day instead of recent days
+    pub recent_days: Vec<(NaiveDate, i64)>, // Minutes logged on each of the last RECENT_DAYS days
+    pub row_jump: String,     // Row number typed in Navigation, applied on Enter
+    pub pending_quit: bool,   // First 'q' pressed when quitting takes 'qq'
+    pub pending_go: bool,     // 'g' pressed, the next key picks the column to jump to
+    cleared_cell: Option<(usize, usize, String, String)>, // Row, column, old value and the row's hash after D
+    next_link: Option<(usize, usize)>, // Row and index of the link `b` opens next
+    ripple: bool, // Move the touching neighbour's time along with this edit, Ctrl+L flips it
+    pub legend: bool, // Explain the row marks under the table, :legend flips it
+    pub relative_times: bool, // The When column, :relative flips it
+    pub webhook_result: Option<std::sync::mpsc::Receiver<std::result::Result<(), String>>>,
+    pub email_result: Option<std::sync::mpsc::Receiver<std::result::Result<(), String>>>,
+    pub upload_result: Option<std::sync::mpsc::Receiver<std::result::Result<String, String>>>,
+    pub export_job: Option<ExportJob>, // Export still being written
+    pub log_view: std::result::Result<(PathBuf, Vec<String>), String>, // Shown by the log screen
+    pub log_scroll: usize,             // Lines scrolled up from the end of the log
+    // How long saving, backing up and drawing take, for the debug screen (:debug)
+    save_timings: Timings,
+    backup_timings: Timings,
+    draw_timings: Timings,
+    pub debug_view: Vec<(String, String)>,
+    pub whats_new: String, // Changelog sections shown after an upgrade or by :changelog
+    pub whats_new_scroll: u16,
+    update_check: Option<std::sync::mpsc::Receiver<std::result::Result<Option<Release>, String>>>,
+    latest_backup: Option<String>, // Hash of the newest backup, so saves needn't read it back
+    backup_mirror: Option<std::sync::mpsc::Receiver<std::result::Result<usize, String>>>,
+    mirror_again: bool, // A backup was written while the mirror was being copied to
+    mirror_status: Option<String>, // How the last copy to backup.mirror_path went, for :debug
+    mirror_failing: bool,
+    pub snippet_placeholders: Vec<String>, // Placeholders of the expanded snippet Tab still moves to
+    pub pending_export: Option<ExportScope>, // Export waiting on confirmation of the check warnings
+    export_passphrase: Option<String>,     // Set when the next export goes into an encrypted zip
+    email_export: bool,                    // Set when the next export is emailed as well
+    pub filter: Option<String>,            // Only rows containing this are listed
+    // Rows on the same task number folded into their first, :group toggles it. Holds the
+    // task numbers opened up with Enter.
+    pub grouped: Option<HashSet<String>>,
+    pub input_rules: InputRules,
+    timer_status: Option<TimerStatus>, // Last state written to the status file
+    pub time_picker: Option<TimePicker>,
+    pub day_note: String, // Kept with the day when it is archived and written to exports
+    pub locked: bool,     // The day was submitted, entries can't change until :unlock
+    pub day: NaiveDate,   // The date the sheet is for, the day Slothtime was opened
+    kept_day_until: Option<NaiveDate>, // Carried on with the old sheet after midnight until this date
+    clock: ClockWatch,
+    pub clock_jump: Option<ClockJump>, // Shown while asking what to do with the running entry
+    pub coalesce_plan: Vec<Merge>,     // Merges shown for confirmation by :coalesce
+    pub duplicates: Vec<Duplicate>,    // Duplicate rows found by :dedupe
+    pub fill_gaps: Vec<Gap>,           // Uncovered parts of the workday found by :fill
+    pub replace_plan: Vec<Change>,     // Cells :%s would change, shown for confirmation
+    pub issues: Vec<Issue>,            // Problems found by :validate
+    pub issue_selected: usize,
+    external_edit: bool, // Open the description in $EDITOR once the key is handled
+    pub history: Option<HistorySearch>,
+    pub task_descriptions: TaskDescriptions, // Every archived description by task number
+    task_descriptions_job: Option<std::sync::mpsc::Receiver<Result<TaskDescriptions, String>>>, // Reading them from the archive
+    pub description_pick: Option<DescriptionPick>,
+    offer_descriptions: Option<usize>, // Row whose task number was just entered
+    pub load_problem: Option<LoadProblem>, // entries.json was damaged, waiting for a repair choice
+    pub demo: Option<DemoDir>,         // Running on throwaway sample data from --demo
+    pub follow: Option<FileWatch>, // A read-only mirror from --follow, reloading what another Slothtime saves
+    pub past_day: bool,            // An archived day opened with --date, saved back to the archive
+    pub tutorial: Option<usize>,   // Step of the demo tutorial being shown
+    pub theme: Theme,
 }
 
 impl App {
-    pub fn new() -> Result<Self> {
-        let config = Config::load()?;
-        let entries = Self::load_entries().unwrap_or_else(|_| vec![TimeEntry::new()]);
+    pub fn new(project: Option<String>) -> Result<Self> {
+        let mut app = Self::open_project(project)?;
+        // A damaged entries file comes first, the notes wait for the next start then
+        if matches!(app.mode, InputMode::Navigation) {
+            if let Some(notes) = changelog::unseen() {
+                app.open_whats_new(notes);
+            }
+            changelog::mark_seen();
+        }
+        if app.config.updates.check {
+            app.update_check = Some(changelog::check_latest(&app.config.updates.url));
+        }
+        Ok(app)
+    }
+
+    // A read-only mirror of the entries another Slothtime is editing, for a second
+    // monitor or pane. It never writes, so a damaged file is left for the editor to repair.
+    pub fn follower(project: Option<String>) -> Result<Self> {
+        let mut app = Self::open_project(project)?;
+        app.follow = Some(FileWatch::new(&app.data_dir));
+        // Simple's always-editing keys and typing over a time cell have nothing to edit here
+        app.config.ui.keymap = Keymap::Vim;
+        app.config.ui.type_to_edit = false;
+        app.mode = InputMode::Navigation;
+        app.update_mode_for_column();
+        if let Some(problem) = app.load_problem.take() {
+            app.show_message(&format!("Could not read the entries: {}", problem.error));
+        }
+        Ok(app)
+    }
+
+    // An archived day opened with --date for corrections. Saving writes it back to the
+    // archive, and today's entries, note and lock are left as they are.
+    pub fn past_day(project: Option<String>, date: NaiveDate) -> Result<Self> {
+        if date >= chrono::Local::now().date_naive() {
+            anyhow::bail!(
+                "{} isn't a past day, open Slothtime without --date for today's entries",
+                date
+            );
+        }
+        let mut app = Self::open_project(project)?;
+        let Some(day) = archive::load_day(&app.data_dir, date)? else {
+            anyhow::bail!(
+                "{} isn't in the archive of {}",
+                date,
+                app.data_dir.display()
+            );
+        };
+        // A day archived by exporting it is archived again from entries.json when the
+        // next day starts, which would undo a correction made here
+        if app.sheet_is_day(&day) {
+            anyhow::bail!(
+                "{} is still the sheet in entries.json, open Slothtime without --date to correct it",
+                date
+            );
+        }
+        app.entries = day.entries;
+        if app.entries.is_empty() {
+            app.entries.push(TimeEntry::new());
+        }
+        app.saved_hashes = time_entry::saved_hashes(&app.entries);
+        app.day = date;
+        app.day_note = day.note;
+        app.past_day = true;
+        app.locked = false;
+        // Today's entries.json isn't being edited, so its problems can wait
+        app.load_problem = None;
+        app.load_history();
+        app.mode = if app.config.ui.keymap == Keymap::Simple {
+            InputMode::Editing
+        } else {
+            InputMode::Navigation
+        };
+        app.update_mode_for_column();
+        Ok(app)
+    }
+
+    // Whether entries.json still holds the archived day: it was last saved that day, or
+    // shares entries with it
+    fn sheet_is_day(&self, day: &Day) -> bool {
+        if self.entries.iter().all(TimeEntry::is_entirely_empty) {
+            return false;
+        }
+        let saved_on = fs::metadata(self.data_dir.join("entries.json"))
+            .and_then(|metadata| metadata.modified())
+            .map(|modified| chrono::DateTime::<chrono::Local>::from(modified).date_naive());
+        saved_on.is_ok_and(|saved_on| saved_on == day.date)
+            || self
+                .entries
+                .iter()
+                .any(|entry| day.entries.iter().any(|archived| archived.id == entry.id))
+    }
+
+    fn open_project(project: Option<String>) -> Result<Self> {
+        let config = Config::load(project.as_deref())?;
+        let data_dir = match &project {
+            Some(name) => config::project_dir(name)?,
+            None => config::data_dir(),
+        };
+        Self::open(config, data_dir, project)
+    }
+
+    // Sample data in a temporary folder with the tutorial hints shown
+    pub fn demo() -> Result<Self> {
+        let (config, demo_dir) = demo::setup(chrono::Local::now().date_naive())?;
+        let mut app = Self::open(config, demo_dir.path().to_path_buf(), None)?;
+        app.demo = Some(demo_dir);
+        app.tutorial = Some(0);
+        Ok(app)
+    }
+
+    fn open(config: Config, data_dir: PathBuf, project: Option<String>) -> Result<Self> {
+        let file = data_dir.join("entries.json");
+        let (entries, load_problem) = match fs::read_to_string(&file) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (vec![TimeEntry::new()], None),
+            Err(e) => (
+                vec![TimeEntry::new()],
+                Some(LoadProblem::new(&data_dir, "", e.to_string())),
+            ),
+            Ok(content) => match Self::read_entries(&content) {
+                Ok((entries, assigned)) => {
+                    // Entries saved before IDs existed keep the IDs they're given from now on
+                    if assigned {
+                        let saved = schema::write_entries(&entries).and_then(|content| {
+                            paths::write_atomic(&file, content).map_err(anyhow::Error::from)
+                        });
+                        if let Err(e) = saved {
+                            tracing::warn!("saving the entries' new IDs failed: {:#}", e);
+                        }
+                    }
+                    (entries, None)
+                }
+                Err(e) => (
+                    vec![TimeEntry::new()],
+                    Some(LoadProblem::new(&data_dir, &content, e.to_string())),
+                ),
+            },
+        };
+        let trash = trash::load(&data_dir).unwrap_or_default();
+        let input_rules = InputRules::new(&config.columns, &data_dir)?;
+        let day_note = archive::load_note(&data_dir);
+        let locked = archive::is_locked(&data_dir);
+        let today = chrono::Local::now().date_naive();
+        let theme = Theme::new(config.ui.palette, config.ui.ascii_only);
+        let ripple = config.ui.ripple_times;
+        let legend = config.ui.legend;
+        let relative_times = config.ui.relative_times;
+        let saved_hashes = time_entry::saved_hashes(&entries);
         let mut app = Self {
             entries,
             cursor: Cursor::new(),
@@ -58,227 +443,1580 @@ impl App {
             config,
             should_quit: false,
             popup_scroll: 0,
-            text_cursor: 0,
+            editor: FieldEditor::default(),
             pending_delete: false,
             status_message: None,
             message_timer: None,
+            activity_hints: None,
+            activity_job: None,
+            allowlist_job: None,
+            data_dir,
+            archived_days: Vec::new(),
+            trash,
+            trash_selected: 0,
+            project,
+            dirty: false,
+            last_saved: None,
+            saved_hashes,
+            save_failures: Vec::new(),
+            degraded: false,
+            prompt: None,
+            report_range: (budget::month_start(today), today),
+            report_days: Vec::new(),
+            report_by_hour: false,
+            recent_days: Vec::new(),
+            row_jump: String::new(),
+            pending_quit: false,
+            pending_go: false,
+            cleared_cell: None,
+            next_link: None,
+            ripple,
+            legend,
+            relative_times,
+            webhook_result: None,
+            email_result: None,
+            upload_result: None,
+            export_job: None,
+            log_view: Ok((PathBuf::new(), Vec::new())),
+            whats_new: String::new(),
+            whats_new_scroll: 0,
+            update_check: None,
+            latest_backup: None,
+            backup_mirror: None,
+            mirror_again: false,
+            mirror_status: None,
+            mirror_failing: false,
+            log_scroll: 0,
+            save_timings: Timings::default(),
+            backup_timings: Timings::default(),
+            draw_timings: Timings::default(),
+            debug_view: Vec::new(),
+            snippet_placeholders: Vec::new(),
+            pending_export: None,
+            export_passphrase: None,
+            email_export: false,
+            filter: None,
+            grouped: None,
+            input_rules,
+            timer_status: None,
+            time_picker: None,
+            day_note,
+            locked,
+            day: today,
+            kept_day_until: None,
+            clock: ClockWatch::new(),
+            clock_jump: None,
+            coalesce_plan: Vec::new(),
+            duplicates: Vec::new(),
+            fill_gaps: Vec::new(),
+            replace_plan: Vec::new(),
+            issues: Vec::new(),
+            issue_selected: 0,
+            external_edit: false,
+            history: None,
+            task_descriptions: TaskDescriptions::default(),
+            task_descriptions_job: None,
+            description_pick: None,
+            offer_descriptions: None,
+            load_problem,
+            demo: None,
+            follow: None,
+            past_day: false,
+            tutorial: None,
+            theme,
         };
+        app.load_history();
+        app.allowlist_job = app.input_rules.fetch_in_background();
+        if let Some(error) = app.input_rules.load_errors.first().cloned() {
+            tracing::warn!("could not load the {}", error);
+            app.show_message(&format!("Could not load the {}", error));
+        }
+        if app.config.ui.keymap == Keymap::Simple {
+            app.mode = InputMode::Editing;
+        }
         // Initialize mode based on starting column
         app.update_mode_for_column();
+        if app.load_problem.is_some() {
+            app.mode = InputMode::Repair;
+        }
         Ok(app)
     }
 
-    fn load_entries() -> Result<Vec<TimeEntry>> {
-        // Get home dir/ location for entries file
-        let home_dir = dirs::home_dir().unwrap();
-        let config_dir = home_dir.join(".slothtime");
-        if !config_dir.exists() {
-            fs::create_dir_all(&config_dir).unwrap();
-        }
-        let file = config_dir.join("entries.json");
-        let content = fs::read_to_string(file)?;
-        let entries: Vec<TimeEntry> = serde_json::from_str(&content)?;
-        Ok(entries)
+    // The entries for reading only, e.g. by a report or the metrics server. Entries
+    // without IDs get them in memory, writing those back is left to the app.
+    pub fn load_entries(data_dir: &Path) -> Result<Vec<TimeEntry>> {
+        let content = fs::read_to_string(data_dir.join("entries.json"))?;
+        Self::read_entries(&content).map(|(entries, _)| entries)
+    }
+
+    // The entries, and whether any were given an ID because they were saved without one
+    fn read_entries(content: &str) -> Result<(Vec<TimeEntry>, bool)> {
+        let mut entries = schema::read_entries(content)?;
+        let assigned = time_entry::assign_ids(&mut entries);
+        Ok((entries, assigned))
+    }
+
+    // Written through a temporary file, so a crash while saving can't cut entries.json short
+    fn save_entries(&self) -> Result<String> {
+        let content = schema::write_entries(&self.entries)?;
+        let file = self.data_dir.join("entries.json");
+        paths::write_atomic(&file, &content)?;
+        Ok(content)
     }
 
-    fn save_entries(&self) -> Result<()> {
-        let content = serde_json::to_string(&self.entries)?;
-        // Get home dir/ location for entries file
-        let home_dir = dirs::home_dir().unwrap();
-        let config_dir = home_dir.join(".slothtime");
-        if !config_dir.exists() {
-            fs::create_dir_all(&config_dir).unwrap();
+    // Give copied rows their own ID and note the time of changes, just before writing
+    fn stamp_entries(&mut self) {
+        self.saved_hashes =
+            time_entry::stamp(&mut self.entries, &self.saved_hashes, chrono::Local::now());
+    }
+
+    fn save_past_day(&self) -> Result<()> {
+        let day = Day {
+            date: self.day,
+            entries: self.entries.clone(),
+            note: self.day_note.clone(),
+        };
+        archive::save_day(&self.data_dir, &day)
+    }
+
+    // Show what the followed Slothtime saved, keeping the cursor on its row where it can
+    fn follow_changes(&mut self) {
+        let Some(watch) = self.follow.as_mut() else {
+            return;
+        };
+        if !watch.changed() {
+            return;
         }
-        let file = config_dir.join("entries.json");
-        fs::write(file, content)?;
-        Ok(())
+        // Read as saved, giving old entries IDs is left to the Slothtime being followed
+        let read = fs::read_to_string(self.data_dir.join("entries.json"))
+            .map_err(anyhow::Error::from)
+            .and_then(|content| schema::read_entries(&content));
+        match read {
+            Ok(entries) => self.entries = entries,
+            Err(e)
+                if e.downcast_ref::<io::Error>()
+                    .is_some_and(|e| e.kind() == io::ErrorKind::NotFound) =>
+            {
+                self.entries = vec![TimeEntry::new()];
+            }
+            Err(e) => {
+                watch.retry();
+                self.show_message(&format!(
+                    "Could not read the entries, showing them as they were: {}",
+                    e
+                ));
+                return;
+            }
+        }
+        if self.entries.is_empty() {
+            self.entries.push(TimeEntry::new());
+        }
+        self.day_note = archive::load_note(&self.data_dir);
+        self.locked = archive::is_locked(&self.data_dir);
+        self.load_history();
+        self.cursor.row = self.cursor.row.min(self.entries.len() - 1);
+        self.open_editor();
     }
 
-    pub fn run(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
-        loop {
-            self.update_message_timer();
-            terminal.draw(|f| ui::draw(f, self))?;
-            if self.should_quit {
-                self.save_entries().ok();
-                break;
+    // Persist entries and remember when, so the status bar can show whether it's safe to quit
+    fn save(&mut self) -> Result<()> {
+        // A mirror never writes over the entries of the Slothtime it follows
+        if self.follow.is_some() {
+            return Ok(());
+        }
+        let started = std::time::Instant::now();
+        self.stamp_entries();
+        let saved = if self.past_day {
+            self.save_past_day().map(|_| None)
+        } else {
+            self.save_entries().map(Some)
+        };
+        let content = match saved {
+            Ok(content) => {
+                self.save_timings.record(started.elapsed());
+                content
             }
-            if event::poll(std::time::Duration::from_millis(100))? {
-                if let event::Event::Key(key) = event::read()? {
-                    // Only handle key press events, ignore key release events
-                    // This fixes double input on Windows
-                    if key.kind == KeyEventKind::Press {
-                        self.handle_key(key);
-                    }
+            Err(e) => {
+                tracing::error!("saving {} failed: {:#}", self.data_dir.display(), e);
+                self.save_failures
+                    .push((chrono::Local::now(), e.to_string()));
+                if self.save_failures.len() >= DEGRADED_AFTER_FAILURES && !self.degraded {
+                    self.degraded = true;
+                    self.show_message("Saving keeps failing, entries are only in memory. Press W to save elsewhere");
+                }
+                return Err(e);
+            }
+        };
+        // The entries are safe on disk, so a failed backup is only worth a message.
+        // Backups are of entries.json, an archived day opened with --date has none.
+        if let Some(content) = content {
+            let started = std::time::Instant::now();
+            match backup::snapshot(
+                &self.data_dir,
+                &content,
+                &self.config.backup,
+                &mut self.latest_backup,
+            ) {
+                Ok(true) => self.mirror_backups(),
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!("backup failed: {:#}", e);
+                    self.show_message(&format!("Saved, but the backup failed: {}", e));
                 }
             }
+            self.backup_timings.record(started.elapsed());
         }
+        self.dirty = false;
+        self.last_saved = Some(std::time::Instant::now());
+        self.save_failures.clear();
+        self.degraded = false;
         Ok(())
     }
 
-    fn handle_key(&mut self, key: event::KeyEvent) {
-        match self.mode {
-            InputMode::Navigation => match key.code {
-                event::KeyCode::Char('q') => self.should_quit = true,
-                event::KeyCode::Char('s')
-                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
-                {
-                    let _ = self.export();
-                }
-                event::KeyCode::Char('x')
-                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
-                {
-                    self.mode = InputMode::ConfirmClearEntries;
+    // Copy new backups to backup.mirror_path, after the copy under way if there is one
+    fn mirror_backups(&mut self) {
+        if self.backup_mirror.is_some() {
+            self.mirror_again = true;
+            return;
+        }
+        self.backup_mirror = backup::mirror_in_background(&self.data_dir, &self.config.backup);
+    }
+
+    // A failing mirror is mentioned once, not on every save, and again once it recovers
+    fn check_backup_mirror(&mut self) {
+        let Some(rx) = &self.backup_mirror else {
+            return;
+        };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Err("the copy stopped".to_string()),
+        };
+        self.backup_mirror = None;
+        let now = chrono::Local::now().format("%H:%M:%S");
+        match result {
+            Ok(copied) => {
+                tracing::debug!(
+                    "copied {} backups to {}",
+                    copied,
+                    self.config.backup.mirror_path
+                );
+                self.mirror_status = Some(format!("{} copied at {}", copied, now));
+                if std::mem::take(&mut self.mirror_failing) {
+                    self.show_message("Backups are being copied to backup.mirror_path again");
                 }
-                event::KeyCode::Char('y')
-                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
-                {
-                    self.copy_current_field();
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "copying backups to {} failed: {}",
+                    self.config.backup.mirror_path,
+                    e
+                );
+                self.mirror_status = Some(format!("failed at {}: {}", now, e));
+                if !std::mem::replace(&mut self.mirror_failing, true) {
+                    self.show_message(&format!(
+                        "Saved, but copying the backup to backup.mirror_path failed: {}",
+                        e
+                    ));
                 }
-                event::KeyCode::Char('d') => {
-                    if self.pending_delete {
-                        // Second 'd' - show confirmation
-                        self.mode = InputMode::ConfirmDeleteEntry;
-                        self.pending_delete = false;
-                    } else {
-                        // First 'd' - set pending
-                        self.pending_delete = true;
+            }
+        }
+        if std::mem::take(&mut self.mirror_again) {
+            self.mirror_backups();
+        }
+    }
+
+    // `q` in navigation mode, guarded the way ui.quit asks for
+    fn quit_key(&mut self) {
+        match self.config.ui.quit {
+            QuitBinding::Q => self.request_quit(false),
+            QuitBinding::Confirm => self.request_quit(true),
+            QuitBinding::DoubleQ if self.pending_quit => {
+                self.pending_quit = false;
+                self.request_quit(false);
+            }
+            QuitBinding::DoubleQ => {
+                self.pending_quit = true;
+                self.show_message("Press q again to quit");
+            }
+            QuitBinding::CtrlC => self.show_message("Press Ctrl+C or type :q to quit"),
+        }
+    }
+
+    // Quit only once entries are safely written and nothing is left half done,
+    // otherwise explain what would be lost
+    fn request_quit(&mut self, always_confirm: bool) {
+        // A mirror has nothing of its own to lose
+        if self.follow.is_some() {
+            self.should_quit = true;
+            return;
+        }
+        // In degraded mode with nothing dirty the entries were saved to an alternate path
+        let saved = (self.degraded && !self.dirty) || self.save().is_ok();
+        if !always_confirm
+            && saved
+            && self.running_entry().is_none()
+            && !self.current_row_incomplete()
+        {
+            self.should_quit = true;
+        } else {
+            self.mode = InputMode::ConfirmQuit;
+        }
+    }
+
+    // Fill in the running entry's end time with the current time, then quit if that saves
+    fn stop_and_quit(&mut self) {
+        if let Some(row) = self.running_entry() {
+            self.entries[row].end_time = chrono::Local::now().format("%H:%M").to_string();
+            self.dirty = true;
+        }
+        match self.save() {
+            Ok(()) => self.should_quit = true,
+            Err(e) => self.show_message(&format!("Could not save: {}", e)),
+        }
+    }
+
+    // Latest row that has a start time but no end time yet
+    // Rewrite the status file whenever what it says changes, about once a minute while running
+    fn update_status_file(&mut self) {
+        if !self.config.status_file.enabled || self.follow.is_some() || self.past_day {
+            return;
+        }
+        let running = self.running_entry().map(|row| &self.entries[row]);
+        let status = TimerStatus::new(running, chrono::Local::now());
+        if self.timer_status.as_ref() == Some(&status) {
+            return;
+        }
+        let path = paths::expand(&self.config.status_file.path);
+        if let Err(e) = status_file::write(&path, &status) {
+            tracing::warn!("writing the status file {} failed: {:#}", path.display(), e);
+            self.show_message(&format!("Could not write the status file: {}", e));
+        }
+        self.timer_status = Some(status);
+    }
+
+    pub fn running_entry(&self) -> Option<usize> {
+        time_entry::running_entry(&self.entries)
+    }
+
+    pub fn current_row_incomplete(&self) -> bool {
+        self.entries
+            .get(self.cursor.row)
+            .is_some_and(|e| !e.is_entirely_empty() && !e.is_complete())
+    }
+
+    fn open_prompt(&mut self, kind: PromptKind) {
+        self.prompt = Some(Prompt {
+            kind,
+            input: String::new(),
+        });
+        self.mode = InputMode::Prompt;
+    }
+
+    // Unlike the webhook, the email is asked for, so say when it's sent too
+    fn check_email(&mut self) {
+        let Some(rx) = &self.email_result else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(())) => {
+                let to = self.config.email.to.join(", ");
+                tracing::info!("emailed the export to {}", to);
+                self.show_message(&format!("Emailed the export to {}", to));
+                self.email_result = None;
+            }
+            Ok(Err(e)) => {
+                tracing::error!("emailing the export failed: {}", e);
+                self.show_message(&format!("Could not email the export: {}", e));
+                self.email_result = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => self.email_result = None,
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+        }
+    }
+
+    fn check_upload(&mut self) {
+        let Some(rx) = &self.upload_result else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(url)) => {
+                tracing::info!("uploaded the export to {}", url);
+                self.show_message(&format!("Uploaded the export to {}", url));
+                self.upload_result = None;
+            }
+            Ok(Err(e)) => {
+                tracing::error!("uploading the export failed: {}", e);
+                self.show_message(&format!("Could not upload the export: {}", e));
+                self.upload_result = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => self.upload_result = None,
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+        }
+    }
+
+    fn submit_prompt(&mut self) {
+        let Some(prompt) = self.prompt.take() else {
+            return;
+        };
+        self.leave_prompt(&prompt.kind);
+        match prompt.kind {
+            PromptKind::SaveAs => self.save_to_alternate_path(prompt.input.trim()),
+            PromptKind::Command => self.run_command(prompt.input.trim()),
+            PromptKind::ZipPassphrase if prompt.input.is_empty() => {
+                self.show_message("The passphrase can't be empty");
+            }
+            PromptKind::ZipPassphrase => {
+                self.export_passphrase = Some(prompt.input);
+                self.run_export(ExportScope::All);
+            }
+            PromptKind::ReportFrom | PromptKind::ReportTo => {
+                match NaiveDate::parse_from_str(prompt.input.trim(), "%Y-%m-%d") {
+                    Ok(date) if prompt.kind == PromptKind::ReportFrom => {
+                        self.set_report_range(date, self.report_range.1.max(date))
                     }
+                    Ok(date) => self.set_report_range(self.report_range.0.min(date), date),
+                    Err(_) => self.show_message("Dates must look like 2025-09-30"),
                 }
+            }
+            PromptKind::Filter => self.set_filter(prompt.input.trim()),
+            PromptKind::DayNote => self.set_day_note(prompt.input.trim()),
+        }
+    }
 
-                event::KeyCode::Char('i') => {
-                    self.pending_delete = false;
-                    self.enter_edit();
-                }
-                event::KeyCode::Char('?') => {
-                    self.pending_delete = false;
-                    self.mode = InputMode::Help;
-                }
-                event::KeyCode::Tab => {
-                    self.pending_delete = false;
-                    self.next_col();
-                }
-                event::KeyCode::BackTab => {
-                    self.pending_delete = false;
-                    self.prev_col();
-                }
-                event::KeyCode::Up => {
-                    self.pending_delete = false;
-                    self.prev_row();
-                }
-                event::KeyCode::Down => {
-                    self.pending_delete = false;
-                    self.next_row();
+    fn leave_prompt(&mut self, kind: &PromptKind) {
+        match kind {
+            PromptKind::SaveAs
+            | PromptKind::Command
+            | PromptKind::ZipPassphrase
+            | PromptKind::Filter
+            | PromptKind::DayNote => {
+                self.mode = InputMode::Navigation;
+                self.update_mode_for_column();
+            }
+            PromptKind::ReportFrom | PromptKind::ReportTo => self.mode = InputMode::Report,
+        }
+    }
+
+    fn save_to_alternate_path(&mut self, path: &str) {
+        if path.is_empty() {
+            return;
+        }
+        let path = paths::expand(path);
+        self.stamp_entries();
+        let result = schema::write_entries(&self.entries)
+            .and_then(|content| fs::write(&path, content).map_err(anyhow::Error::from));
+        match result {
+            Ok(()) => {
+                self.dirty = false;
+                self.show_message(&format!("Entries saved to {}", path.display()));
+            }
+            Err(e) => self.show_message(&format!("Could not save to {}: {}", path.display(), e)),
+        }
+    }
+
+    fn load_history(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        let start = budget::history_start(today);
+        self.archived_days = self.load_archived(start, today);
+        // A day opened with --date is counted from the entries being edited instead
+        let day = self.day;
+        self.archived_days.retain(|archived| archived.date != day);
+        // A --follow mirror reloads on every save and never types a description to offer any for
+        if self.follow.is_none() {
+            self.task_descriptions_job = Some(TaskDescriptions::load(&self.data_dir, today));
+        }
+    }
+
+    // Archived days from `from` to `to`, telling the user about any that couldn't be read
+    fn load_archived(&mut self, from: NaiveDate, to: NaiveDate) -> Vec<Day> {
+        match archive::load_range(&self.data_dir, from, to) {
+            Ok((days, unreadable)) => {
+                if let Some(message) = archive::unreadable_message(&unreadable) {
+                    self.show_message(&message);
                 }
-                event::KeyCode::Left => {
-                    self.pending_delete = false;
-                    self.prev_col();
-                }
-                event::KeyCode::Right => {
-                    self.pending_delete = false;
-                    self.next_col();
-                }
-                _ => {
-                    // Reset pending delete on any other key
-                    self.pending_delete = false;
+                days
+            }
+            Err(e) => {
+                tracing::warn!("reading the archive failed: {:#}", e);
+                self.show_message(&format!("Could not read the archive: {}", e));
+                Vec::new()
+            }
+        }
+    }
+
+    // Offer the archive's descriptions once the background read has been through it
+    fn check_task_descriptions(&mut self) {
+        let Some(rx) = &self.task_descriptions_job else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(descriptions)) => self.task_descriptions = descriptions,
+            Ok(Err(e)) => tracing::warn!("reading descriptions from the archive failed: {}", e),
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {}
+        }
+        self.task_descriptions_job = None;
+    }
+
+    // Check against the URL allowlists once they arrive, the copies from last time until then
+    fn check_allowlists(&mut self) {
+        let Some(rx) = &self.allowlist_job else {
+            return;
+        };
+        let fetched = match rx.try_recv() {
+            Ok(fetched) => fetched,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Vec::new(),
+        };
+        self.allowlist_job = None;
+        if let Some(error) = self.input_rules.use_fetched(fetched).first() {
+            tracing::warn!("could not load the {}", error);
+            self.show_message(&format!("Could not load the {}", error));
+        }
+    }
+
+    // Commands typed after ':' in Navigation mode
+    fn run_command(&mut self, command: &str) {
+        if command.is_empty() {
+            return;
+        }
+        let viewing = matches!(
+            command,
+            "q" | "quit"
+                | "q!"
+                | "validate"
+                | "log"
+                | "changelog"
+                | "legend"
+                | "relative"
+                | "group"
+                | "debug"
+        );
+        if self.follow.is_some() && !viewing && command.parse::<usize>().is_err() {
+            self.show_message("Following another Slothtime, this window is read-only");
+            return;
+        }
+        match command {
+            "q" | "quit" => self.request_quit(false),
+            "q!" => self.should_quit = true,
+            "coalesce" => self.preview_coalesce(self.config.cleanup.min_minutes),
+            "dedupe" => self.preview_dedupe(),
+            "fill" => self.preview_fill(),
+            "sort" => self.sort_entries(),
+            "continue" => self.continue_task(),
+            "validate" => self.validate(),
+            "log" => self.open_log(),
+            "debug" => self.open_debug(),
+            "changelog" => self.open_whats_new(changelog::all()),
+            "legend" => {
+                self.legend = !self.legend;
+                self.show_message(if self.legend {
+                    "Legend shown"
+                } else {
+                    "Legend hidden"
+                });
+            }
+            "group" => self.toggle_grouped(),
+            "relative" => {
+                self.relative_times = !self.relative_times;
+                self.show_message(if self.relative_times {
+                    "When column shown"
+                } else {
+                    "When column hidden"
+                });
+            }
+            _ if command.starts_with("copyday") => self.copy_day(&command["copyday".len()..]),
+            _ if command.starts_with("%s") => self.preview_replace(&command[2..]),
+            "lock" => self.set_locked(true),
+            "unlock" => self.set_locked(false),
+            _ if command.starts_with("protect") => {
+                self.set_protected(true, &command["protect".len()..])
+            }
+            _ if command.starts_with("unprotect") => {
+                self.set_protected(false, &command["unprotect".len()..])
+            }
+            _ if command.starts_with("coalesce ") => {
+                match command["coalesce ".len()..].trim().parse() {
+                    Ok(minutes) => self.preview_coalesce(minutes),
+                    Err(_) => self.show_message("Usage: :coalesce [minutes]"),
                 }
+            }
+            _ => match command.parse::<usize>() {
+                Ok(row) => self.jump_to_row(row),
+                Err(_) => self.show_message(&format!("Unknown command: {}", command)),
             },
-            InputMode::Editing => match key.code {
-                event::KeyCode::Esc => self.exit_edit(),
-                event::KeyCode::Tab => self.next_col(),
-                event::KeyCode::BackTab => self.prev_col(),
-                event::KeyCode::Enter => {
-                    self.next_col();
-                    // stay in edit mode
-                }
-                event::KeyCode::Left => {
-                    if self.text_cursor > 0 {
-                        self.text_cursor -= 1;
+        }
+    }
+
+    fn set_locked(&mut self, locked: bool) {
+        if self.past_day {
+            self.show_message("Only today's entries can be locked");
+            return;
+        }
+        if locked {
+            self.commit_field();
+            self.mode = InputMode::Navigation;
+            self.update_mode_for_column();
+        }
+        if let Err(e) = archive::set_locked(&self.data_dir, locked) {
+            self.show_message(&format!(
+                "Failed to {}: {}",
+                if locked { "lock" } else { "unlock" },
+                e
+            ));
+            return;
+        }
+        self.locked = locked;
+        self.show_message(if locked {
+            "Day locked, :unlock to change its entries again"
+        } else {
+            "Day unlocked"
+        });
+    }
+
+    // Protect columns of the checked rows, or of the current row when none are checked,
+    // once they're approved. Without columns :protect takes protect.columns and
+    // :unprotect lifts every one.
+    fn set_protected(&mut self, protect: bool, columns: &str) {
+        if !self.can_change_entries() {
+            return;
+        }
+        self.commit_field();
+        let keys: Vec<String> = (1..=5).filter_map(TimeEntry::field_key).collect();
+        let mut columns: Vec<String> = columns
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|column| !column.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        if columns.is_empty() {
+            columns = if protect {
+                self.config.protect.columns.clone()
+            } else {
+                keys.clone()
+            };
+        }
+        if let Some(unknown) = columns.iter().find(|column| !keys.contains(column)) {
+            self.show_message(&format!(
+                "Unknown column {}, use {}",
+                unknown,
+                keys.join(", ")
+            ));
+            return;
+        }
+        let mut rows: Vec<usize> = (0..self.entries.len())
+            .filter(|&row| self.entries[row].checked)
+            .collect();
+        let checked = !rows.is_empty();
+        if !checked {
+            rows.push(self.cursor.row);
+        }
+        for &row in &rows {
+            let Some(entry) = self.entries.get_mut(row) else {
+                continue;
+            };
+            if protect {
+                entry.protected.extend(columns.iter().cloned());
+                entry
+                    .protected
+                    .sort_by_key(|key| keys.iter().position(|k| k == key));
+                entry.protected.dedup();
+            } else {
+                entry.protected.retain(|key| !columns.contains(key));
+            }
+        }
+        self.dirty = true;
+        let _ = self.save();
+        let names: Vec<&str> = (1..=5)
+            .filter(|&col| TimeEntry::field_key(col).is_some_and(|key| columns.contains(&key)))
+            .filter_map(TimeEntry::field_name)
+            .collect();
+        let rows = if checked {
+            format!(
+                "{} checked row{}",
+                rows.len(),
+                if rows.len() == 1 { "" } else { "s" }
+            )
+        } else {
+            format!("row {}", self.cursor.row + 1)
+        };
+        self.show_message(&if protect {
+            format!(
+                "Protected {} in {}, :unprotect to change them",
+                names.join(", "),
+                rows
+            )
+        } else {
+            format!("Unprotected {} in {}", names.join(", "), rows)
+        });
+    }
+
+    // False, with a message saying why, when :protect keeps the cell from changing
+    fn can_change_cell(&mut self, row: usize, col: usize) -> bool {
+        let protected = self
+            .entries
+            .get(row)
+            .is_some_and(|entry| entry.is_protected(col));
+        if protected {
+            self.show_message(&format!(
+                "{} is protected in row {}, :unprotect to change it",
+                TimeEntry::field_name(col).unwrap_or_default(),
+                row + 1
+            ));
+        }
+        !protected
+    }
+
+    // Rows with protected cells aren't deleted or merged away
+    fn can_remove_row(&mut self, row: usize) -> bool {
+        let protected = self
+            .entries
+            .get(row)
+            .is_some_and(|entry| !entry.protected.is_empty());
+        if protected {
+            self.show_message(&format!(
+                "Row {} has protected cells, :unprotect it first",
+                row + 1
+            ));
+        }
+        !protected
+    }
+
+    // False, with a message saying why, when the day is locked against changes
+    fn can_change_entries(&mut self) -> bool {
+        if self.locked {
+            self.show_message("This day is locked, :unlock to change it");
+        }
+        !self.locked
+    }
+
+    // Find the cells a find-and-replace would change and list them before changing any
+    fn preview_replace(&mut self, command: &str) {
+        if !self.can_change_entries() {
+            return;
+        }
+        self.commit_field();
+        let col = self.cursor.col.clamp(1, 5);
+        let replace = match Replace::parse(command, col) {
+            Ok(replace) => replace,
+            Err(e) => {
+                self.show_message(&e.to_string());
+                return;
+            }
+        };
+        self.replace_plan = replace.plan(&self.entries);
+        let matches = self.replace_plan.len();
+        self.replace_plan
+            .retain(|change| !self.entries[change.row].is_protected(change.col));
+        if self.replace_plan.is_empty() && matches > 0 {
+            self.show_message("Every match is in a protected cell, :unprotect to change them");
+            return;
+        }
+        if self.replace_plan.is_empty() {
+            let columns = match replace.columns.as_slice() {
+                [col] => TimeEntry::field_name(*col).unwrap_or_default(),
+                _ => "any column",
+            };
+            self.show_message(&format!("No matches in {}", columns));
+            return;
+        }
+        self.mode = InputMode::ConfirmReplace;
+    }
+
+    fn apply_replace(&mut self) {
+        let changes = std::mem::take(&mut self.replace_plan);
+        for change in &changes {
+            if let Some(field) = self
+                .entries
+                .get_mut(change.row)
+                .and_then(|e| e.field_mut(change.col))
+            {
+                *field = change.after.clone();
+            }
+        }
+        let rows = {
+            let mut rows: Vec<usize> = changes.iter().map(|change| change.row).collect();
+            rows.dedup();
+            rows.len()
+        };
+        self.dirty = true;
+        let _ = self.save();
+        self.show_message(&format!(
+            "Replaced {} cell(s) in {} row(s)",
+            changes.len(),
+            rows
+        ));
+        self.mode = InputMode::Navigation;
+        self.update_mode_for_column();
+    }
+
+    // List the parts of the workday no entry covers and offer to fill them
+    fn preview_fill(&mut self) {
+        if !self.can_change_entries() {
+            return;
+        }
+        self.commit_field();
+        let workday = &self.config.workday;
+        let (Some(start), Some(end)) = (
+            TimeEntry::parse_time(&workday.start),
+            TimeEntry::parse_time(&workday.end),
+        ) else {
+            self.show_message("workday.start and workday.end need to be HH:MM times");
+            return;
+        };
+        self.fill_gaps = fill::gaps(&self.entries, start, end);
+        if self.fill_gaps.is_empty() {
+            self.show_message(&format!(
+                "Nothing to fill, {}-{} is covered",
+                workday.start, workday.end
+            ));
+            return;
+        }
+        self.mode = InputMode::ConfirmFill;
+    }
+
+    fn fill_with_placeholders(&mut self) {
+        let gaps = std::mem::take(&mut self.fill_gaps);
+        if let Some(row) = fill::add_placeholders(&mut self.entries, &gaps) {
+            self.cursor.row = row;
+        }
+        self.finish_fill(&format!(
+            "Added {} {} row{}, fill in the task numbers and work codes",
+            gaps.len(),
+            fill::PLACEHOLDER,
+            if gaps.len() == 1 { "" } else { "s" }
+        ));
+    }
+
+    fn fill_by_stretching(&mut self) {
+        let gaps = std::mem::take(&mut self.fill_gaps);
+        let Some(entry) = self.entries.get_mut(self.cursor.row) else {
+            return;
+        };
+        let added = fill::stretch(entry, &gaps);
+        if added == 0 {
+            self.fill_gaps = gaps;
+            self.show_message("No gap starts or ends at the selected entry");
+            return;
+        }
+        self.finish_fill(&format!(
+            "Stretched the selected entry by {}",
+            time_entry::format_duration(added)
+        ));
+    }
+
+    fn finish_fill(&mut self, message: &str) {
+        self.fill_gaps.clear();
+        self.dirty = true;
+        let _ = self.save();
+        self.show_message(message);
+        self.mode = InputMode::Navigation;
+        self.update_mode_for_column();
+    }
+
+    // Work out which short entries would be merged and show them before changing anything
+    fn preview_coalesce(&mut self, min_minutes: u32) {
+        if !self.can_change_entries() {
+            return;
+        }
+        self.commit_field();
+        self.coalesce_plan = coalesce::plan(&self.entries, min_minutes as i64);
+        let entries = &self.entries;
+        self.coalesce_plan.retain(|merge| {
+            entries[merge.rows.clone()]
+                .iter()
+                .all(|entry| entry.protected.is_empty())
+        });
+        if self.coalesce_plan.is_empty() {
+            self.show_message(&format!(
+                "Nothing to merge, no entry under {}m is next to one on the same task",
+                min_minutes
+            ));
+            return;
+        }
+        self.mode = InputMode::ConfirmCoalesce;
+    }
+
+    fn apply_coalesce(&mut self) {
+        let merges = std::mem::take(&mut self.coalesce_plan);
+        let originals = coalesce::apply(&mut self.entries, &merges);
+        let count = originals.len();
+        for entry in originals {
+            self.move_to_trash(entry);
+        }
+        self.cursor.row = self.cursor.row.min(self.entries.len() - 1);
+        self.dirty = true;
+        let _ = self.save();
+        let _ = trash::save(&self.data_dir, &self.trash);
+        self.show_message(&format!(
+            "Merged {} rows into {}, the originals are in the trash (T)",
+            count,
+            merges.len()
+        ));
+        self.mode = InputMode::Navigation;
+        self.update_mode_for_column();
+    }
+
+    fn sort_entries(&mut self) {
+        if !self.can_change_entries() {
+            return;
+        }
+        self.commit_field();
+        dedupe::sort_by_start(&mut self.entries);
+        self.dirty = true;
+        let _ = self.save();
+        self.show_message("Sorted by start time");
+    }
+
+    // List rows on the same task with overlapping times before deleting or merging any
+    fn preview_dedupe(&mut self) {
+        if !self.can_change_entries() {
+            return;
+        }
+        self.commit_field();
+        self.duplicates = dedupe::find(&self.entries);
+        let entries = &self.entries;
+        self.duplicates.retain(|duplicate| {
+            duplicate
+                .rows
+                .iter()
+                .all(|&row| entries[row].protected.is_empty())
+        });
+        if self.duplicates.is_empty() {
+            self.show_message("No duplicates, no two rows on a task overlap");
+            return;
+        }
+        self.mode = InputMode::ConfirmDedupe;
+    }
+
+    fn apply_dedupe(&mut self, merge: bool) {
+        let duplicates = std::mem::take(&mut self.duplicates);
+        let removed = dedupe::apply(&mut self.entries, &duplicates, merge);
+        let count = removed.len();
+        for entry in removed {
+            self.move_to_trash(entry);
+        }
+        self.cursor.row = self.cursor.row.min(self.entries.len() - 1);
+        self.dirty = true;
+        let _ = self.save();
+        let _ = trash::save(&self.data_dir, &self.trash);
+        self.show_message(&if merge {
+            format!(
+                "Merged {} rows into {}, the originals are in the trash (T)",
+                count,
+                duplicates.len()
+            )
+        } else {
+            format!(
+                "Deleted {} duplicate rows, they are in the trash (T)",
+                count
+            )
+        });
+        self.mode = InputMode::Navigation;
+        self.update_mode_for_column();
+    }
+
+    // Rows are numbered from 1 like in the table, out of range numbers go to the last row
+    fn jump_to_row(&mut self, row: usize) {
+        self.commit_field();
+        self.cursor.row = row.clamp(1, self.entries.len()) - 1;
+        self.update_mode_for_column();
+    }
+
+    // `g` then a column's letter or number jumps straight to that cell of the row:
+    // t task number, w work code, d description, s start and e end, or 1 to 5
+    fn go_key(&mut self, key: event::KeyEvent) -> bool {
+        if !std::mem::take(&mut self.pending_go) {
+            if key.code == event::KeyCode::Char('g') && key.modifiers.is_empty() {
+                self.pending_delete = false;
+                self.row_jump.clear();
+                self.pending_go = true;
+                return true;
+            }
+            return false;
+        }
+        let col = match key.code {
+            event::KeyCode::Char('t' | '1') => 1,
+            event::KeyCode::Char('w' | '2') => 2,
+            event::KeyCode::Char('d' | '3') => 3,
+            event::KeyCode::Char('s' | '4') => 4,
+            event::KeyCode::Char('e' | '5') => 5,
+            // Anything else just cancels the jump
+            _ => return true,
+        };
+        self.commit_field();
+        self.cursor.col = col;
+        self.update_mode_for_column();
+        true
+    }
+
+    // What a --follow mirror lets through: moving around, filtering and the views that
+    // only show things. Keys that would change entries or write files are refused.
+    fn read_only_key(&self, key: event::KeyEvent) -> bool {
+        use event::KeyCode::*;
+        let ctrl = key.modifiers.contains(event::KeyModifiers::CONTROL);
+        let moving = !ctrl
+            && matches!(
+                key.code,
+                Up | Down | Left | Right | Tab | BackTab | PageUp | PageDown | Esc
+            );
+        match self.mode {
+            InputMode::Navigation | InputMode::ViewingPopup if self.pending_go => true,
+            InputMode::Navigation => {
+                moving
+                    || match key.code {
+                        Char('c' | 'y') => ctrl,
+                        Char(
+                            'q'
+                            | ':'
+                            | '/'
+                            | 'g'
+                            | '?'
+                            | 'r'
+                            | 'H'
+                            | 'L'
+                            | 'V'
+                            | 'b'
+                            | 'Y'
+                            | '0'..='9',
+                        ) => !ctrl,
+                        Enter => !self.row_jump.is_empty() || self.is_group_head(self.cursor.row),
+                        _ => false,
                     }
-                }
-                event::KeyCode::Right => {
-                    let max_len = self.get_current_field_length();
-                    if self.text_cursor < max_len {
-                        self.text_cursor += 1;
+            }
+            InputMode::ViewingPopup => {
+                moving
+                    || (!ctrl && matches!(key.code, Char(':' | 'g' | 'b')))
+                    || (ctrl && key.code == Char('y'))
+            }
+            InputMode::Report => matches!(
+                key.code,
+                Esc | Char('q' | 'r' | 'f' | 't' | '[' | ']' | 'w' | 'h')
+            ),
+            InputMode::History => key.code != Enter,
+            InputMode::Help
+            | InputMode::WhatsNew
+            | InputMode::Log
+            | InputMode::Debug
+            | InputMode::Validation
+            | InputMode::Prompt => true,
+            _ => false,
+        }
+    }
+
+    fn open_report(&mut self) {
+        self.pending_delete = false;
+        let (from, to) = self.report_range;
+        self.set_report_range(from, to);
+        self.load_recent_days();
+        self.mode = InputMode::Report;
+    }
+
+    // Daily totals for the chart at the top of the report view
+    fn load_recent_days(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        let from = today - chrono::Duration::days(RECENT_DAYS - 1);
+        let archived = self.load_archived(from, today);
+        let current = Day {
+            date: self.day,
+            entries: self.entries.clone(),
+            note: self.day_note.clone(),
+        };
+        let days = totals::days_in_range(archived, current, from, today);
+        self.recent_days = totals::daily_minutes(&days, from, today);
+    }
+
+    fn set_report_range(&mut self, from: NaiveDate, to: NaiveDate) {
+        self.report_range = (from, to);
+        let archived = self.load_archived(from, to);
+        let current = Day {
+            date: self.day,
+            entries: self.entries.clone(),
+            note: self.day_note.clone(),
+        };
+        self.report_days = totals::days_in_range(archived, current, from, to);
+    }
+
+    // Move the report range by whole months, keeping it aligned to month boundaries
+    fn shift_report_month(&mut self, months: i32) {
+        let from = self.report_range.0;
+        let first = budget::month_start(from);
+        let shifted = if months < 0 {
+            first - chrono::Months::new(months.unsigned_abs())
+        } else {
+            first + chrono::Months::new(months as u32)
+        };
+        let last = shifted + chrono::Months::new(1) - chrono::Duration::days(1);
+        self.set_report_range(shifted, last);
+    }
+
+    pub fn hourly_minutes(&self) -> [i64; 24] {
+        totals::hourly_minutes(&self.report_days)
+    }
+
+    pub fn task_totals(&self) -> Vec<TaskTotal> {
+        totals::task_totals(&self.report_days)
+    }
+
+    fn export_task_totals(&mut self) {
+        let (from, to) = self.report_range;
+        match export::export_task_totals(&self.task_totals(), &self.config, from, to) {
+            Ok(saved) => self.show_message(&format!(
+                "Task totals exported to {}{}",
+                saved.path.display(),
+                saved.unmapped_note()
+            )),
+            Err(e) => self.show_message(&format!("Export failed: {}", e)),
+        }
+    }
+
+    // All entries of the report range in one file, for month-end submissions
+    fn export_report_range(&mut self) {
+        let (from, to) = self.report_range;
+        match export::export_range(&self.report_days, &self.config, from, to) {
+            Ok(saved) => self.show_message(&format!(
+                "Entries exported to {}{}",
+                saved.path.display(),
+                saved.unmapped_note()
+            )),
+            Err(e) => self.show_message(&format!("Export failed: {}", e)),
+        }
+    }
+
+    fn export_report_grid(&mut self) {
+        let (from, to) = self.report_range;
+        match export::export_grid(&self.report_days, &self.config, from, to) {
+            Ok(saved) => self.show_message(&format!(
+                "Timesheet exported to {}{}",
+                saved.path.display(),
+                saved.unmapped_note()
+            )),
+            Err(e) => self.show_message(&format!("Export failed: {}", e)),
+        }
+    }
+
+    pub fn budget_usage(&self) -> Vec<BudgetUsage> {
+        let today = chrono::Local::now().date_naive();
+        budget::usage(
+            &self.config.budgets,
+            &self.archived_days,
+            &self.entries,
+            today,
+        )
+    }
+
+    // Time logged to the work code under the cursor today and this week
+    pub fn work_code_minutes(&self) -> Option<(&str, i64, i64)> {
+        if self.cursor.col != 2 {
+            return None;
+        }
+        let code = self.entries.get(self.cursor.row)?.work_code.as_str();
+        if code.is_empty() {
+            return None;
+        }
+        let (today, week) =
+            budget::code_minutes(&self.archived_days, &self.entries, self.day, code);
+        Some((code, today, week))
+    }
+
+    pub fn run(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+        loop {
+            self.update_message_timer();
+            self.check_export();
+            self.check_webhook();
+            self.check_email();
+            self.check_upload();
+            self.check_for_update();
+            self.check_task_descriptions();
+            self.check_backup_mirror();
+            self.check_activity_hints();
+            self.check_allowlists();
+            self.follow_changes();
+            self.update_status_file();
+            self.advance_tutorial();
+            self.check_clock();
+            self.check_day_rollover();
+            let started = std::time::Instant::now();
+            terminal.draw(|f| ui::draw(f, self))?;
+            self.draw_timings.record(started.elapsed());
+            if self.should_quit {
+                break;
+            }
+            if event::poll(std::time::Duration::from_millis(100))? {
+                match event::read()? {
+                    // Only handle key press events, ignore key release events
+                    // This fixes double input on Windows
+                    event::Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        self.handle_key(key);
                     }
+                    event::Event::Paste(text) => self.paste(&text),
+                    _ => {}
                 }
-                event::KeyCode::Home => self.text_cursor = 0,
-                event::KeyCode::End => {
-                    self.text_cursor = self.get_current_field_length();
-                }
-                event::KeyCode::Char(c) => self.insert_char(c),
-                event::KeyCode::Backspace => self.delete_char(),
-                _ => {}
-            },
-            InputMode::ViewingPopup => match key.code {
-                event::KeyCode::Char('i') => self.enter_edit(),
-                event::KeyCode::Char('y')
+                self.offer_descriptions();
+            }
+            if std::mem::take(&mut self.external_edit) {
+                self.edit_externally(terminal)?;
+            }
+        }
+        // Let an export that is still being written finish before quitting. Finishing it
+        // starts its webhook, upload and email, so a failure is only returned after those.
+        let finished = match self.export_job.take() {
+            Some(job) => {
+                let result = job.wait();
+                self.finish_export(job, result)
+            }
+            None => Ok(()),
+        };
+        // and an upload, email or webhook of it that's still being sent
+        if let Some(rx) = self.upload_result.take() {
+            if let Ok(Err(e)) = rx.recv() {
+                tracing::error!("uploading the export failed: {}", e);
+            }
+        }
+        if let Some(rx) = self.email_result.take() {
+            if let Ok(Err(e)) = rx.recv() {
+                tracing::error!("emailing the export failed: {}", e);
+            }
+        }
+        if let Some(rx) = self.webhook_result.take() {
+            if let Ok(Err(e)) = rx.recv() {
+                tracing::error!("export webhook failed: {}", e);
+            }
+        }
+        finished
+    }
+
+    fn handle_key(&mut self, key: event::KeyEvent) {
+        if self.follow.is_some() && !self.read_only_key(key) {
+            self.show_message("Following another Slothtime, this window is read-only");
+            return;
+        }
+        if matches!(self.mode, InputMode::ViewingPopup | InputMode::EditingPopup)
+            && self.arrange_popup(key)
+        {
+            return;
+        }
+        // Enter on a group's first row opens it up instead of its usual meaning
+        let simple = self.config.ui.keymap == Keymap::Simple;
+        if key.code == event::KeyCode::Enter
+            && key.modifiers.is_empty()
+            && self.row_jump.is_empty()
+            && (matches!(self.mode, InputMode::Navigation | InputMode::ViewingPopup)
+                || (simple && matches!(self.mode, InputMode::Editing)))
+            && self.toggle_group()
+        {
+            return;
+        }
+        if simple && self.handle_simple_key(key) {
+            return;
+        }
+        if matches!(self.mode, InputMode::Navigation | InputMode::ViewingPopup) && self.go_key(key)
+        {
+            return;
+        }
+        if matches!(self.mode, InputMode::Navigation) {
+            if !matches!(
+                key.code,
+                event::KeyCode::Char('0'..='9') | event::KeyCode::Enter
+            ) {
+                self.row_jump.clear();
+            }
+            if key.code != event::KeyCode::Char('q') {
+                self.pending_quit = false;
+            }
+        }
+        match self.mode {
+            InputMode::Navigation => match key.code {
+                event::KeyCode::Char('c')
                     if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
                 {
-                    self.copy_current_field();
+                    self.request_quit(false);
                 }
-                event::KeyCode::Tab => self.next_col(),
-                event::KeyCode::BackTab => self.prev_col(),
-                event::KeyCode::Up => {
-                    if self.popup_scroll > 0 {
-                        self.popup_scroll -= 1;
+                event::KeyCode::Char('q') => self.quit_key(),
+                event::KeyCode::Char(':') => self.open_prompt(PromptKind::Command),
+                event::KeyCode::Char('/') => self.open_prompt(PromptKind::Filter),
+                event::KeyCode::Esc if self.filter.is_some() => self.set_filter(""),
+                event::KeyCode::Char('W') => self.open_prompt(PromptKind::SaveAs),
+                event::KeyCode::Char('N') => {
+                    self.open_prompt(PromptKind::DayNote);
+                    if let Some(prompt) = self.prompt.as_mut() {
+                        prompt.input = self.day_note.clone();
                     }
                 }
-                event::KeyCode::Down => {
-                    self.popup_scroll += 1;
-                }
-                event::KeyCode::Left => self.prev_col(),
-                event::KeyCode::Right => self.next_col(),
-                _ => {}
-            },
-            InputMode::EditingPopup => match key.code {
-                event::KeyCode::Esc => {
-                    // Exit edit mode but stay in popup view
-                    self.mode = InputMode::ViewingPopup;
+                event::KeyCode::Char('s')
+                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                {
+                    let scope = if self.config.export.changed_only {
+                        ExportScope::Changed
+                    } else {
+                        ExportScope::All
+                    };
+                    self.run_export(scope);
                 }
-                event::KeyCode::Tab => {
-                    self.next_col();
-                    self.popup_scroll = 0;
+                event::KeyCode::Char('e')
+                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                {
+                    self.mode = InputMode::ExportMenu;
                 }
-                event::KeyCode::BackTab => {
-                    self.prev_col();
-                    self.popup_scroll = 0;
+                event::KeyCode::Char('x')
+                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                {
+                    self.mode = InputMode::ConfirmClearEntries;
                 }
                 event::KeyCode::Char('y')
                     if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
                 {
                     self.copy_current_field();
                 }
-                event::KeyCode::Enter => {
-                    self.insert_char('\n');
+                event::KeyCode::Char('d') => {
+                    if self.pending_delete {
+                        // Second 'd' - show confirmation
+                        if self.can_change_entries() && self.can_remove_row(self.cursor.row) {
+                            self.mode = InputMode::ConfirmDeleteEntry;
+                        }
+                        self.pending_delete = false;
+                    } else {
+                        // First 'd' - set pending
+                        self.pending_delete = true;
+                    }
                 }
-                event::KeyCode::Up => {
-                    self.move_cursor_up_in_text();
+
+                event::KeyCode::Char(c @ ('+' | '-')) if matches!(self.cursor.col, 4 | 5) => {
+                    // Start a relative time, applied to the current one on leaving the cell
+                    self.pending_delete = false;
+                    self.enter_edit();
+                    self.close_time_picker();
+                    self.edit_field(|editor| {
+                        editor.select_all();
+                        editor.insert(&c.to_string());
+                    });
+                }
+                event::KeyCode::Char(c)
+                    if c.is_ascii_digit()
+                        && self.config.ui.type_to_edit
+                        && matches!(self.cursor.col, 4 | 5) =>
+                {
+                    // Spreadsheet style: typing a digit on a time cell replaces its value
+                    self.pending_delete = false;
+                    self.enter_edit();
+                    // The digit is the start of the new value, so no picker
+                    self.close_time_picker();
+                    self.edit_field(|editor| {
+                        editor.select_all();
+                        editor.insert(&c.to_string());
+                    });
+                }
+                event::KeyCode::Char(c) if c.is_ascii_digit() => {
+                    self.pending_delete = false;
+                    self.row_jump.push(c);
+                }
+                event::KeyCode::Char('i') => {
+                    self.pending_delete = false;
+                    self.enter_edit();
+                }
+                event::KeyCode::Char(' ') => {
+                    self.pending_delete = false;
+                    self.toggle_checked();
+                }
+                event::KeyCode::Char('I') => {
+                    self.pending_delete = false;
+                    self.import_calendar();
+                }
+                event::KeyCode::Char('E') => {
+                    self.pending_delete = false;
+                    self.request_external_edit();
+                }
+                event::KeyCode::Char('?') => {
+                    self.pending_delete = false;
+                    self.mode = InputMode::Help;
+                }
+                event::KeyCode::Char('r') => self.open_report(),
+                event::KeyCode::Char('H') => self.open_history(),
+                event::KeyCode::Char('L') => self.open_log(),
+                event::KeyCode::Char('V') => {
+                    self.pending_delete = false;
+                    self.validate();
+                }
+                event::KeyCode::Char('T') => {
+                    self.pending_delete = false;
+                    self.trash_selected = 0;
+                    self.mode = InputMode::Trash;
+                }
+                event::KeyCode::Enter if !self.row_jump.is_empty() => {
+                    let row = std::mem::take(&mut self.row_jump);
+                    self.jump_to_row(row.parse().unwrap_or(usize::MAX));
+                }
+                event::KeyCode::Enter => {
+                    self.pending_delete = false;
+                    self.open_description_editor();
+                }
+                event::KeyCode::Char('o') => {
+                    self.pending_delete = false;
+                    self.insert_row(true);
+                }
+                event::KeyCode::Char('O') => {
+                    self.pending_delete = false;
+                    self.insert_row(false);
+                }
+                event::KeyCode::Char('C') => {
+                    self.pending_delete = false;
+                    self.continue_task();
+                }
+                event::KeyCode::Char('D') => {
+                    self.pending_delete = false;
+                    self.clear_cell();
+                }
+                event::KeyCode::Char('u') => {
+                    self.pending_delete = false;
+                    self.undo_clear_cell();
+                }
+                event::KeyCode::Char('b') => {
+                    self.pending_delete = false;
+                    self.open_link();
+                }
+                event::KeyCode::Char('Y') => {
+                    self.pending_delete = false;
+                    self.copy_day_summary();
+                }
+                event::KeyCode::Tab => {
+                    self.pending_delete = false;
+                    self.next_col();
+                }
+                event::KeyCode::BackTab => {
+                    self.pending_delete = false;
+                    self.prev_col();
+                }
+                event::KeyCode::Up => {
+                    self.pending_delete = false;
+                    self.prev_row();
                 }
                 event::KeyCode::Down => {
-                    self.move_cursor_down_in_text();
+                    self.pending_delete = false;
+                    self.next_row();
                 }
                 event::KeyCode::Left => {
-                    if self.text_cursor > 0 {
-                        self.text_cursor -= 1;
-                    }
+                    self.pending_delete = false;
+                    self.prev_col();
                 }
                 event::KeyCode::Right => {
-                    let max_len = self.get_current_field_length();
-                    if self.text_cursor < max_len {
-                        self.text_cursor += 1;
-                    }
+                    self.pending_delete = false;
+                    self.next_col();
+                }
+                _ => {
+                    // Reset pending delete on any other key
+                    self.pending_delete = false;
+                }
+            },
+            InputMode::Editing => match key.code {
+                event::KeyCode::Esc => self.exit_edit(),
+                event::KeyCode::Tab => self.next_col(),
+                event::KeyCode::BackTab => self.prev_col(),
+                event::KeyCode::Enter => {
+                    self.next_col();
+                    // stay in edit mode
+                }
+                event::KeyCode::Char('t')
+                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                {
+                    self.open_time_picker();
+                }
+                event::KeyCode::Char('l')
+                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                {
+                    self.toggle_ripple();
+                }
+                _ => self.edit_key(key),
+            },
+            InputMode::ViewingPopup => match key.code {
+                event::KeyCode::Char('i') | event::KeyCode::Enter => self.enter_edit(),
+                event::KeyCode::Char('o') => self.insert_row(true),
+                event::KeyCode::Char('O') => self.insert_row(false),
+                event::KeyCode::Char('y')
+                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                {
+                    self.copy_current_field();
+                }
+                event::KeyCode::Char(':') => self.open_prompt(PromptKind::Command),
+                event::KeyCode::Char('E') => self.request_external_edit(),
+                event::KeyCode::Char('D') => self.clear_cell(),
+                event::KeyCode::Char('u') => self.undo_clear_cell(),
+                event::KeyCode::Char('b') => self.open_link(),
+                event::KeyCode::Tab => self.next_col(),
+                event::KeyCode::BackTab => self.prev_col(),
+                event::KeyCode::Up if self.popup_scroll > 0 => {
+                    self.popup_scroll -= 1;
                 }
-                event::KeyCode::Home => self.text_cursor = 0,
-                event::KeyCode::End => {
-                    self.text_cursor = self.get_current_field_length();
+                event::KeyCode::Down => {
+                    self.popup_scroll += 1;
                 }
-                event::KeyCode::Char(c) => self.insert_char(c),
-                event::KeyCode::Backspace => self.delete_char(),
+                event::KeyCode::Left => self.prev_col(),
+                event::KeyCode::Right => self.next_col(),
                 _ => {}
             },
+            InputMode::EditingPopup => match key.code {
+                event::KeyCode::Esc => {
+                    self.commit_field();
+                    // Exit edit mode but stay in popup view, unless the popup only opens for editing
+                    self.mode = if self.auto_popup() {
+                        InputMode::ViewingPopup
+                    } else {
+                        InputMode::Navigation
+                    };
+                    self.auto_save();
+                }
+                event::KeyCode::Tab => self.popup_tab(),
+                event::KeyCode::BackTab => {
+                    self.prev_col();
+                    self.popup_scroll = 0;
+                }
+                event::KeyCode::Char('y')
+                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                {
+                    self.copy_current_field();
+                }
+                event::KeyCode::Char('o')
+                    if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+                {
+                    self.request_external_edit();
+                }
+                event::KeyCode::Enter => self.insert_text("\n"),
+                _ => self.edit_key(key),
+            },
             InputMode::Help => {
                 self.mode = InputMode::Navigation;
             }
+            InputMode::WhatsNew => match key.code {
+                event::KeyCode::Up => {
+                    self.whats_new_scroll = self.whats_new_scroll.saturating_sub(1)
+                }
+                event::KeyCode::Down => self.whats_new_scroll += 1,
+                event::KeyCode::PageUp => {
+                    self.whats_new_scroll = self.whats_new_scroll.saturating_sub(10)
+                }
+                event::KeyCode::PageDown => self.whats_new_scroll += 10,
+                _ => {
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+            },
+            InputMode::Report => match key.code {
+                event::KeyCode::Esc | event::KeyCode::Char('q') | event::KeyCode::Char('r') => {
+                    self.mode = InputMode::Navigation;
+                }
+                event::KeyCode::Char('f') => self.open_prompt(PromptKind::ReportFrom),
+                event::KeyCode::Char('t') => self.open_prompt(PromptKind::ReportTo),
+                event::KeyCode::Char('[') => self.shift_report_month(-1),
+                event::KeyCode::Char(']') => self.shift_report_month(1),
+                event::KeyCode::Char('e') => self.export_task_totals(),
+                event::KeyCode::Char('x') => self.export_report_range(),
+                event::KeyCode::Char('w') => {
+                    let monday = budget::week_start(chrono::Local::now().date_naive());
+                    self.set_report_range(monday, monday + chrono::Duration::days(6));
+                }
+                event::KeyCode::Char('g') => self.export_report_grid(),
+                event::KeyCode::Char('h') => self.report_by_hour = !self.report_by_hour,
+                _ => {}
+            },
             InputMode::ConfirmDeleteEntry => match key.code {
                 event::KeyCode::Char('y') | event::KeyCode::Char('Y') => {
                     self.delete_current_entry();
@@ -297,274 +2035,2198 @@ impl App {
                 event::KeyCode::Char('n') | event::KeyCode::Char('N') | event::KeyCode::Esc => {
                     self.mode = InputMode::Navigation;
                 }
-                _ => {}
-            },
+                _ => {}
+            },
+            InputMode::ExportMenu => match key.code {
+                event::KeyCode::Char('a') => {
+                    self.mode = InputMode::Navigation;
+                    self.run_export(ExportScope::All);
+                }
+                event::KeyCode::Char('c') => {
+                    self.mode = InputMode::Navigation;
+                    self.run_export(ExportScope::Changed);
+                }
+                event::KeyCode::Char('x') => {
+                    self.mode = InputMode::Navigation;
+                    self.run_export(ExportScope::Checked);
+                }
+                event::KeyCode::Char('p') => self.open_prompt(PromptKind::ZipPassphrase),
+                event::KeyCode::Char('m') if self.config.email.to.is_empty() => {
+                    self.show_message("Set email.to in the config to email exports");
+                }
+                event::KeyCode::Char('m') => {
+                    self.mode = InputMode::Navigation;
+                    self.email_export = true;
+                    self.run_export(ExportScope::All);
+                }
+                event::KeyCode::Esc | event::KeyCode::Char('q') => {
+                    self.mode = InputMode::Navigation;
+                }
+                _ => {}
+            },
+            InputMode::Prompt => match key.code {
+                event::KeyCode::Esc => {
+                    if let Some(prompt) = self.prompt.take() {
+                        self.leave_prompt(&prompt.kind);
+                    }
+                }
+                event::KeyCode::Enter => self.submit_prompt(),
+                event::KeyCode::Backspace => {
+                    if let Some(prompt) = self.prompt.as_mut() {
+                        prompt.input.pop();
+                    }
+                }
+                event::KeyCode::Char(c) => {
+                    if let Some(prompt) = self.prompt.as_mut() {
+                        prompt.input.push(c);
+                    }
+                }
+                _ => {}
+            },
+            InputMode::ConfirmExport => match key.code {
+                event::KeyCode::Char('y') | event::KeyCode::Char('Y') => {
+                    if let Some(scope) = self.pending_export.take() {
+                        self.export_confirmed(scope);
+                    }
+                }
+                event::KeyCode::Char('n') | event::KeyCode::Char('N') | event::KeyCode::Esc => {
+                    self.pending_export = None;
+                    self.export_passphrase = None;
+                    self.email_export = false;
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                _ => {}
+            },
+            InputMode::ConfirmReplace => match key.code {
+                event::KeyCode::Char('y') | event::KeyCode::Char('Y') => self.apply_replace(),
+                event::KeyCode::Char('n') | event::KeyCode::Char('N') | event::KeyCode::Esc => {
+                    self.replace_plan.clear();
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                _ => {}
+            },
+            InputMode::ConfirmFill => match key.code {
+                event::KeyCode::Char('p') | event::KeyCode::Char('P') => {
+                    self.fill_with_placeholders()
+                }
+                event::KeyCode::Char('s') | event::KeyCode::Char('S') => self.fill_by_stretching(),
+                event::KeyCode::Up => self.cursor.row = self.cursor.row.saturating_sub(1),
+                event::KeyCode::Down => {
+                    self.cursor.row = (self.cursor.row + 1).min(self.entries.len() - 1)
+                }
+                event::KeyCode::Char('n') | event::KeyCode::Char('N') | event::KeyCode::Esc => {
+                    self.fill_gaps.clear();
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                _ => {}
+            },
+            InputMode::ConfirmDedupe => match key.code {
+                event::KeyCode::Char('d') | event::KeyCode::Char('D') => self.apply_dedupe(false),
+                event::KeyCode::Char('m') | event::KeyCode::Char('M') => self.apply_dedupe(true),
+                event::KeyCode::Char('n') | event::KeyCode::Char('N') | event::KeyCode::Esc => {
+                    self.duplicates.clear();
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                _ => {}
+            },
+            InputMode::ConfirmCoalesce => match key.code {
+                event::KeyCode::Char('y') | event::KeyCode::Char('Y') => self.apply_coalesce(),
+                event::KeyCode::Char('n') | event::KeyCode::Char('N') | event::KeyCode::Esc => {
+                    self.coalesce_plan.clear();
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                _ => {}
+            },
+            InputMode::ConfirmQuit => match key.code {
+                event::KeyCode::Char('w') | event::KeyCode::Char('W') => {
+                    self.open_prompt(PromptKind::SaveAs);
+                }
+                event::KeyCode::Char('s') | event::KeyCode::Char('S') => self.stop_and_quit(),
+                event::KeyCode::Char('q') | event::KeyCode::Char('Q') => self.should_quit = true,
+                event::KeyCode::Esc | event::KeyCode::Char('n') | event::KeyCode::Char('N') => {
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                _ => {}
+            },
+            InputMode::TimePicker => self.time_picker_key(key),
+            InputMode::History => self.history_key(key),
+            InputMode::PickDescription => self.pick_description_key(key),
+            InputMode::Validation => match key.code {
+                event::KeyCode::Up => self.issue_selected = self.issue_selected.saturating_sub(1),
+                event::KeyCode::Down if self.issue_selected + 1 < self.issues.len() => {
+                    self.issue_selected += 1;
+                }
+                event::KeyCode::Enter => self.go_to_issue(),
+                event::KeyCode::Esc | event::KeyCode::Char('q') | event::KeyCode::Char('V') => {
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                _ => {}
+            },
+            InputMode::ClockJump => match key.code {
+                event::KeyCode::Char('s') | event::KeyCode::Char('S') => {
+                    self.settle_clock_jump(false)
+                }
+                event::KeyCode::Char('c') | event::KeyCode::Char('C') => {
+                    self.settle_clock_jump(true)
+                }
+                event::KeyCode::Char('k') | event::KeyCode::Char('K') | event::KeyCode::Esc => {
+                    self.clock_jump = None;
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                _ => {}
+            },
+            InputMode::Rollover => match key.code {
+                event::KeyCode::Char('n') | event::KeyCode::Char('N') => self.start_new_day(true),
+                event::KeyCode::Char('s') | event::KeyCode::Char('S') => self.start_new_day(false),
+                event::KeyCode::Char('k') | event::KeyCode::Char('K') | event::KeyCode::Esc => {
+                    self.kept_day_until = Some(chrono::Local::now().date_naive());
+                    self.show_message(&format!(
+                        "Still on the sheet for {}, exports are dated that day",
+                        self.day.format("%a %Y-%m-%d")
+                    ));
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                _ => {}
+            },
+            InputMode::Repair => match key.code {
+                event::KeyCode::Char('b') => {
+                    let backup = self.load_problem.as_ref().and_then(|p| p.backup.clone());
+                    if let Some((path, entries)) = backup {
+                        let name = path
+                            .file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                            .to_string();
+                        self.repair_entries(entries, &format!("Restored {}", name));
+                    }
+                }
+                event::KeyCode::Char('s') => {
+                    let salvaged = self
+                        .load_problem
+                        .as_ref()
+                        .map(|p| p.salvaged.clone())
+                        .unwrap_or_default();
+                    if !salvaged.is_empty() {
+                        let message = format!("Kept the {} readable entries", salvaged.len());
+                        self.repair_entries(salvaged, &message);
+                    }
+                }
+                event::KeyCode::Char('n') => {
+                    self.repair_entries(Vec::new(), "Started an empty sheet")
+                }
+                // Leaves the damaged file untouched
+                event::KeyCode::Char('q') => self.should_quit = true,
+                _ => {}
+            },
+            InputMode::Log => match key.code {
+                event::KeyCode::Esc | event::KeyCode::Char('q') | event::KeyCode::Char('L') => {
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                event::KeyCode::Up => self.log_scroll += 1,
+                event::KeyCode::Down => self.log_scroll = self.log_scroll.saturating_sub(1),
+                event::KeyCode::PageUp => self.log_scroll += 10,
+                event::KeyCode::PageDown => self.log_scroll = self.log_scroll.saturating_sub(10),
+                event::KeyCode::Char('r') => self.open_log(),
+                _ => {}
+            },
+            InputMode::Debug => match key.code {
+                event::KeyCode::Esc | event::KeyCode::Char('q') => {
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                event::KeyCode::Char('r') => self.open_debug(),
+                _ => {}
+            },
+            InputMode::Trash => match key.code {
+                event::KeyCode::Esc | event::KeyCode::Char('q') | event::KeyCode::Char('T') => {
+                    self.mode = InputMode::Navigation;
+                    self.update_mode_for_column();
+                }
+                event::KeyCode::Up if self.trash_selected > 0 => {
+                    self.trash_selected -= 1;
+                }
+                event::KeyCode::Down if self.trash_selected + 1 < self.trash.len() => {
+                    self.trash_selected += 1;
+                }
+                event::KeyCode::Enter | event::KeyCode::Char('r') => self.restore_from_trash(),
+                event::KeyCode::Char('x') => self.purge_from_trash(),
+                _ => {}
+            },
+        }
+    }
+
+    // The simple keymap never leaves editing: arrows move between cells, Enter moves down and
+    // function keys replace the navigation mode commands. Returns true when the key was handled.
+    fn handle_simple_key(&mut self, key: event::KeyEvent) -> bool {
+        match self.mode {
+            // Dialogs and screens hand back to navigation, which the simple keymap skips
+            InputMode::Navigation => self.enter_edit(),
+            InputMode::ViewingPopup => self.mode = InputMode::EditingPopup,
+            InputMode::Editing | InputMode::EditingPopup => {}
+            _ => return false,
+        }
+        let ctrl = key.modifiers.contains(event::KeyModifiers::CONTROL);
+        let in_popup = matches!(self.mode, InputMode::EditingPopup);
+
+        match key.code {
+            event::KeyCode::F(1) => self.mode = InputMode::Help,
+            event::KeyCode::F(2) => self.run_export(ExportScope::All),
+            event::KeyCode::Char('s') if ctrl => self.run_export(ExportScope::All),
+            event::KeyCode::F(3) => self.mode = InputMode::ExportMenu,
+            event::KeyCode::F(4) => self.open_report(),
+            event::KeyCode::F(6) => self.open_history(),
+            event::KeyCode::F(7) => self.request_external_edit(),
+            event::KeyCode::F(5) => {
+                self.trash_selected = 0;
+                self.mode = InputMode::Trash;
+            }
+            event::KeyCode::F(8) => {
+                if self.can_change_entries() && self.can_remove_row(self.cursor.row) {
+                    self.mode = InputMode::ConfirmDeleteEntry;
+                }
+            }
+            event::KeyCode::F(10) => self.request_quit(false),
+            event::KeyCode::Char('q') if ctrl => self.request_quit(false),
+            event::KeyCode::Enter if !in_popup => self.next_row(),
+            event::KeyCode::Up if !in_popup => self.prev_row(),
+            event::KeyCode::Down if !in_popup => self.next_row(),
+            event::KeyCode::Left if !in_popup => self.prev_col(),
+            event::KeyCode::Right if !in_popup => self.next_col(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn next_col(&mut self) {
+        self.commit_field();
+        if self.cursor.col < 5 {
+            self.cursor.col += 1;
+        } else {
+            // When on last column (End Time), move to next row, first column
+            self.cursor.col = 1;
+            self.next_row();
+        }
+        self.update_mode_for_column();
+    }
+
+    fn prev_col(&mut self) {
+        self.commit_field();
+        if self.cursor.col > 1 {
+            self.cursor.col -= 1;
+        } else {
+            // When on first column (Task Number), go to previous row's last column (End Time)
+            self.cursor.col = 5;
+            if self.cursor.row > 0 {
+                self.cursor.row -= 1;
+            }
+        }
+        self.update_mode_for_column();
+    }
+
+    fn next_row(&mut self) {
+        self.commit_field();
+        let next = (self.cursor.row + 1..self.entries.len()).find(|&row| self.is_listed(row));
+        if let Some(row) = next {
+            self.cursor.row = row;
+        } else if self.cursor.row == self.entries.len() - 1
+            && self.add_blank_row(matches!(
+                self.config.ui.new_row,
+                NewRow::Navigate | NewRow::Both
+            ))
+        {
+            self.cursor.row += 1;
+        }
+        self.update_mode_for_column();
+    }
+
+    // Fill a blank row with the [defaults] as editing it begins, so the blank row kept
+    // at the end stays empty (and isn't taken for a running entry) until it's used
+    fn fill_defaults(&mut self, row: usize) {
+        let defaults = &self.config.defaults;
+        let start = match row
+            .checked_sub(1)
+            .and_then(|previous| self.entries.get(previous))
+        {
+            Some(previous) if defaults.start_after_previous => previous.end_time.trim().to_string(),
+            _ => String::new(),
+        };
+        let Some(entry) = self
+            .entries
+            .get_mut(row)
+            .filter(|entry| entry.is_entirely_empty())
+        else {
+            return;
+        };
+        entry.task_number = defaults.task_prefix.clone();
+        entry.work_code = defaults.work_code.clone();
+        entry.start_time = start;
+        if !entry.is_entirely_empty() {
+            self.dirty = true;
+        }
+    }
+
+    // Vim's o and O: a blank row below or above the cursor, edited from its first cell
+    fn insert_row(&mut self, below: bool) {
+        if !self.can_change_entries() {
+            return;
+        }
+        self.commit_field();
+        let row = if below {
+            self.cursor.row + 1
+        } else {
+            self.cursor.row
+        };
+        self.entries.insert(row, TimeEntry::new());
+        self.cursor.row = row;
+        self.cursor.col = 1;
+        self.dirty = true;
+        self.mode = InputMode::Navigation;
+        self.update_mode_for_column();
+        self.enter_edit();
+    }
+
+    // Pick a finished task back up, e.g. after lunch: a new row on the same task number,
+    // work code and description starting now. A running entry is stopped first.
+    fn continue_task(&mut self) {
+        if !self.can_change_entries() {
+            return;
+        }
+        self.commit_field();
+        let Some(source) = self.entries.get(self.cursor.row).cloned() else {
+            return;
+        };
+        if source.task_number.trim().is_empty() || source.end_time.trim().is_empty() {
+            self.show_message("Only a finished row with a task number can be continued");
+            return;
+        }
+        let now = chrono::Local::now().format("%H:%M").to_string();
+        if let Some(row) = self.running_entry() {
+            self.entries[row].end_time = now.clone();
+        }
+        let entry = TimeEntry {
+            task_number: source.task_number.clone(),
+            work_code: source.work_code,
+            time_entry: source.time_entry,
+            start_time: now,
+            ..TimeEntry::new()
+        };
+        // After the last row in use, taking over the blank row at the end if there is one
+        let row = self
+            .entries
+            .iter()
+            .rposition(|e| !e.is_entirely_empty())
+            .map_or(0, |row| row + 1);
+        if row < self.entries.len() {
+            self.entries[row] = entry;
+        } else {
+            self.entries.push(entry);
+        }
+        self.cursor.row = row;
+        self.dirty = true;
+        self.update_mode_for_column();
+        let _ = self.save();
+
+        let task = source.task_number.trim();
+        let parts: Vec<&TimeEntry> = self
+            .entries
+            .iter()
+            .filter(|e| e.task_number.trim().eq_ignore_ascii_case(task))
+            .collect();
+        let logged: i64 = parts.iter().filter_map(|e| e.duration_minutes()).sum();
+        self.show_message(&format!(
+            "Continuing {}, part {} ({} logged today so far)",
+            task,
+            parts.len(),
+            time_entry::format_duration(logged)
+        ));
+    }
+
+    // Empty the cell under the cursor without going through edit mode, `u` puts it back
+    fn clear_cell(&mut self) {
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        if !self.can_change_entries() || !self.can_change_cell(row, col) {
+            return;
+        }
+        let name = TimeEntry::field_name(col).unwrap_or_default();
+        let Some(entry) = self.entries.get_mut(row) else {
+            return;
+        };
+        let Some(field) = entry.field_mut(col) else {
+            return;
+        };
+        if field.is_empty() {
+            self.show_message(&format!("{} is already empty", name));
+            return;
+        }
+        let old = std::mem::take(field);
+        self.cleared_cell = Some((row, col, old, entry.content_hash()));
+        self.dirty = true;
+        self.auto_save();
+        self.open_editor();
+        self.show_message(&format!("Cleared {}, u to undo", name));
+    }
+
+    // Open the row's ticket or a URL from its description in the browser, the next of
+    // them each time `b` is pressed again on the same row
+    fn open_link(&mut self) {
+        let row = self.cursor.row;
+        let Some(entry) = self.entries.get(row) else {
+            return;
+        };
+        let found = links::for_entry(entry, &self.config.links);
+        if found.is_empty() {
+            self.show_message(
+                "No link in this row, put a URL in the description or set links.task_url",
+            );
+            return;
+        }
+        let index = match self.next_link {
+            Some((link_row, next)) if link_row == row => next % found.len(),
+            _ => 0,
+        };
+        self.next_link = Some((row, index + 1));
+        let url = &found[index];
+        match links::open(url) {
+            Ok(()) if found.len() > 1 => self.show_message(&format!(
+                "Opened {} ({} of {}, b for the next)",
+                url,
+                index + 1,
+                found.len()
+            )),
+            Ok(()) => self.show_message(&format!("Opened {}", url)),
+            Err(e) => {
+                tracing::warn!("opening a link failed: {:#}", e);
+                self.show_message(&format!("{:#}", e));
+            }
+        }
+    }
+
+    // Only while the row is as D left it, so a value never lands in a row that moved
+    fn undo_clear_cell(&mut self) {
+        let Some((row, col, old, hash)) = self.cleared_cell.take() else {
+            self.show_message("Nothing to undo");
+            return;
+        };
+        if !self.can_change_entries() {
+            return;
+        }
+        let Some(entry) = self
+            .entries
+            .get_mut(row)
+            .filter(|entry| entry.content_hash() == hash)
+        else {
+            self.show_message("The row changed since the cell was cleared, nothing to undo");
+            return;
+        };
+        if let Some(field) = entry.field_mut(col) {
+            *field = old;
+        }
+        self.dirty = true;
+        self.auto_save();
+        self.cursor.row = row;
+        self.cursor.col = col;
+        self.update_mode_for_column();
+        let name = TimeEntry::field_name(col).unwrap_or_default();
+        self.show_message(&format!("Restored {} in row {}", name, row + 1));
+    }
+
+    fn prev_row(&mut self) {
+        self.commit_field();
+        if let Some(row) = (0..self.cursor.row).rev().find(|&row| self.is_listed(row)) {
+            self.cursor.row = row;
+        }
+        self.update_mode_for_column();
+    }
+
+    // Rows matching the filter are listed, and so is the cursor's own row so it never disappears
+    pub fn is_listed(&self, row: usize) -> bool {
+        if row == self.cursor.row {
+            return true;
+        }
+        let folded = self.grouped.as_ref().is_some_and(|expanded| {
+            let task = self.entries[row].task_number.trim();
+            !task.is_empty() && !expanded.contains(task) && self.first_of_task(task) != Some(row)
+        });
+        match &self.filter {
+            _ if folded => false,
+            Some(filter) => self.entries[row].matches(filter),
+            None => true,
+        }
+    }
+
+    fn first_of_task(&self, task: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.task_number.trim() == task)
+    }
+
+    // The rows a summary row stands for while :group folds them into it, first row first
+    pub fn folded_rows(&self, row: usize) -> Option<Vec<usize>> {
+        let expanded = self.grouped.as_ref()?;
+        let task = self.entries.get(row)?.task_number.trim();
+        if task.is_empty() || expanded.contains(task) || self.first_of_task(task) != Some(row) {
+            return None;
+        }
+        let rows: Vec<usize> = (row..self.entries.len())
+            .filter(|&other| self.entries[other].task_number.trim() == task)
+            .collect();
+        (rows.len() > 1).then_some(rows)
+    }
+
+    // The first row of a task with more than one row, where Enter folds or unfolds it
+    fn is_group_head(&self, row: usize) -> bool {
+        let Some(entry) = self.entries.get(row).filter(|_| self.grouped.is_some()) else {
+            return false;
+        };
+        let task = entry.task_number.trim();
+        !task.is_empty()
+            && self.first_of_task(task) == Some(row)
+            && self.entries[row + 1..]
+                .iter()
+                .any(|other| other.task_number.trim() == task)
+    }
+
+    fn toggle_grouped(&mut self) {
+        self.commit_field();
+        if self.grouped.take().is_some() {
+            self.show_message("Showing every row");
+            return;
+        }
+        self.grouped = Some(HashSet::new());
+        // Onto the row the cursor's row is folded into
+        let task = self.entries[self.cursor.row].task_number.trim().to_string();
+        if let Some(first) = self.first_of_task(&task).filter(|_| !task.is_empty()) {
+            self.cursor.row = first;
+        }
+        self.update_mode_for_column();
+        self.show_message("Rows grouped by task number, Enter on a group opens it up");
+    }
+
+    // Open up the group under the cursor, or fold it again
+    fn toggle_group(&mut self) -> bool {
+        if !self.is_group_head(self.cursor.row) {
+            return false;
+        }
+        let task = self.entries[self.cursor.row].task_number.trim().to_string();
+        if let Some(expanded) = self.grouped.as_mut() {
+            if !expanded.remove(&task) {
+                expanded.insert(task);
+            }
+        }
+        true
+    }
+
+    fn set_day_note(&mut self, note: &str) {
+        // Exports write the note on a single line
+        let note = note.split_whitespace().collect::<Vec<_>>().join(" ");
+        if note == self.day_note {
+            return;
+        }
+        // An archived day keeps its note in its own file
+        if self.past_day {
+            self.day_note = note;
+            self.dirty = true;
+            match self.save() {
+                Ok(()) => self.show_message("Saved the day's note"),
+                Err(e) => self.show_message(&format!("Could not save the note: {}", e)),
+            }
+            return;
+        }
+        match archive::save_note(&self.data_dir, &note) {
+            Ok(()) if note.is_empty() => self.show_message("Removed today's note"),
+            Ok(()) => self.show_message("Saved today's note"),
+            Err(e) => self.show_message(&format!("Could not save the note: {}", e)),
+        }
+        self.day_note = note;
+    }
+
+    fn set_filter(&mut self, filter: &str) {
+        if filter.is_empty() {
+            self.filter = None;
+            self.show_message("Showing all rows");
+            return;
+        }
+        let first_match = self.entries.iter().position(|entry| entry.matches(filter));
+        self.filter = Some(filter.to_string());
+        match first_match {
+            Some(row) if !self.entries[self.cursor.row].matches(filter) => {
+                self.cursor.row = row;
+                self.update_mode_for_column();
+            }
+            Some(_) => {}
+            None => self.show_message(&format!("No rows contain '{}'", filter)),
+        }
+    }
+
+    // Check warnings plus values breaking a [columns] pattern or missing from the
+    // export's [mappings], flagged with `!`
+    pub fn row_warnings(&self, entry: &TimeEntry) -> Vec<String> {
+        let mut warnings = checks::warnings(entry, &self.config.checks);
+        warnings.extend(self.input_rules.problems(entry));
+        warnings.extend(validate::unmapped(entry, mapping::for_export(&self.config)));
+        warnings
+    }
+
+    // Rows matching the filter, rows with any content, and the time logged on the matches
+    pub fn filter_summary(&self) -> Option<(usize, usize, i64)> {
+        let filter = self.filter.as_ref()?;
+        let entries = self
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_entirely_empty());
+        let (mut shown, mut total, mut minutes) = (0, 0, 0);
+        for entry in entries {
+            total += 1;
+            if entry.matches(filter) {
+                shown += 1;
+                minutes += entry.duration_minutes().unwrap_or(0);
+            }
+        }
+        Some((shown, total, minutes))
+    }
+
+    fn update_mode_for_column(&mut self) {
+        // Auto-show popup when on Time Entry column (3), auto-hide when not
+        if self.cursor.col == 3 {
+            // Convert to popup mode while preserving edit state
+            if self.auto_popup() {
+                match self.mode {
+                    InputMode::Navigation => self.mode = InputMode::ViewingPopup,
+                    InputMode::Editing => self.mode = InputMode::EditingPopup,
+                    _ => {} // Already in popup mode or other mode
+                }
+            }
+        } else {
+            // Convert back to regular mode while preserving edit state
+            match self.mode {
+                InputMode::ViewingPopup => self.mode = InputMode::Navigation,
+                InputMode::EditingPopup => self.mode = InputMode::Editing,
+                _ => {} // Already in regular mode or other mode
+            }
+            self.popup_scroll = 0;
+        }
+        // Start a fresh buffer when switching cells
+        self.open_editor();
+        if self.config.ui.time_picker {
+            self.open_time_picker();
+        }
+        if matches!(self.mode, InputMode::ViewingPopup | InputMode::EditingPopup) {
+            self.refresh_activity_hints();
+        }
+    }
+
+    fn refresh_activity_hints(&mut self) {
+        if !self.config.activitywatch.enabled || self.cursor.row >= self.entries.len() {
+            return;
+        }
+        let entry = &self.entries[self.cursor.row];
+        let range = (entry.start_time.clone(), entry.end_time.clone());
+        if let Some(hints) = &self.activity_hints {
+            if hints.row == self.cursor.row && hints.range == range {
+                return;
+            }
+        }
+
+        let today = chrono::Local::now().date_naive();
+        let to_local = |time: &str| {
+            TimeEntry::parse_time(time)
+                .and_then(|t| today.and_time(t).and_local_timezone(chrono::Local).single())
+        };
+        // A query for another row still running is dropped with its receiver
+        self.activity_job = None;
+        let windows = match (to_local(&range.0), to_local(&range.1)) {
+            (Some(start), Some(end)) if start < end => {
+                self.activity_job = Some(activitywatch::top_windows_in_background(
+                    &self.config.activitywatch.url,
+                    start,
+                    end,
+                    self.config.activitywatch.limit,
+                ));
+                None
+            }
+            _ => Some(Err("Set start and end times to see activity".to_string())),
+        };
+        self.activity_hints = Some(ActivityHints {
+            row: self.cursor.row,
+            range,
+            windows,
+        });
+    }
+
+    // The split layout shows the description in its detail panel instead
+    fn auto_popup(&self) -> bool {
+        self.config.ui.auto_popup && self.config.ui.layout != PaneLayout::Split
+    }
+
+    // Show the windows once the background query has them
+    fn check_activity_hints(&mut self) {
+        let Some(rx) = &self.activity_job else {
+            return;
+        };
+        let windows = match rx.try_recv() {
+            Ok(windows) => windows,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("the ActivityWatch query stopped unexpectedly".to_string())
+            }
+        };
+        self.activity_job = None;
+        if let Some(hints) = &mut self.activity_hints {
+            hints.windows = Some(windows);
+        }
+    }
+
+    // Jump to the description and open the popup editor, regardless of auto-popup
+    // The terminal can only be handed over from the run loop, so this only asks for it
+    fn request_external_edit(&mut self) {
+        if !self.can_change_entries() || !self.can_change_cell(self.cursor.row, 3) {
+            return;
+        }
+        self.commit_field();
+        self.external_edit = true;
+    }
+
+    // Suspend the TUI while $EDITOR has the current row's description, then put the
+    // edited text back through the popup editor so it is tidied like typed text
+    fn edit_externally(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> Result<()> {
+        let Some(entry) = self.entries.get(self.cursor.row) else {
+            return Ok(());
+        };
+        let description = entry.time_entry.clone();
+        term::disable_raw_mode()?;
+        execute!(
+            terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableBracketedPaste
+        )?;
+        let edited = external_editor::edit(&description, &format!("row{}", self.cursor.row + 1));
+        term::enable_raw_mode()?;
+        execute!(
+            terminal.backend_mut(),
+            EnterAlternateScreen,
+            EnableBracketedPaste
+        )?;
+        terminal.clear()?;
+
+        match edited {
+            Ok(edited) if edited == description => self.show_message("Description unchanged"),
+            Ok(edited) => {
+                self.cursor.col = 3;
+                self.mode = InputMode::EditingPopup;
+                self.open_editor();
+                self.edit_field(|editor| {
+                    editor.select_all();
+                    editor.insert(&edited);
+                });
+                self.commit_field();
+                self.mode = InputMode::Navigation;
+                self.update_mode_for_column();
+                self.auto_save();
+                self.show_message("Description updated from the editor");
+            }
+            Err(e) => {
+                tracing::warn!("editing in the external editor failed: {:#}", e);
+                self.show_message(&format!("{:#}", e));
+            }
+        }
+        Ok(())
+    }
+
+    // Ctrl+arrows resize the description popup and Alt+arrows move it. Returns true when
+    // the key was one of those.
+    fn arrange_popup(&mut self, key: event::KeyEvent) -> bool {
+        let popup = &mut self.config.ui.popup;
+        let step =
+            |value: u16, by: i32, min: u16| (value as i32 + by).clamp(min as i32, 100) as u16;
+        if key.modifiers.contains(event::KeyModifiers::CONTROL) {
+            match key.code {
+                event::KeyCode::Left => popup.width = step(popup.width, -5, 30),
+                event::KeyCode::Right => popup.width = step(popup.width, 5, 30),
+                event::KeyCode::Up => popup.height = step(popup.height, -5, 20),
+                event::KeyCode::Down => popup.height = step(popup.height, 5, 20),
+                _ => return false,
+            }
+        } else if key.modifiers.contains(event::KeyModifiers::ALT) {
+            match key.code {
+                event::KeyCode::Left => popup.x = step(popup.x, -10, 0),
+                event::KeyCode::Right => popup.x = step(popup.x, 10, 0),
+                event::KeyCode::Up => popup.y = step(popup.y, -10, 0),
+                event::KeyCode::Down => popup.y = step(popup.y, 10, 0),
+                _ => return false,
+            }
+        } else {
+            return false;
+        }
+        if let Err(e) = self.config.save_popup() {
+            self.show_message(&format!("Could not save the popup size: {}", e));
+        }
+        true
+    }
+
+    fn open_description_editor(&mut self) {
+        if self.cursor.col != 3 {
+            self.cursor.col = 3;
+            self.popup_scroll = 0;
+        }
+        self.mode = InputMode::EditingPopup;
+        self.open_editor();
+        self.refresh_activity_hints();
+    }
+
+    // Load the current field into the editor, cursor at the end and no history
+    fn open_editor(&mut self) {
+        let field = self.current_field().map_or("", String::as_str);
+        self.editor = FieldEditor::new(field);
+    }
+
+    // Characters typed and the column's max_length while editing a limited column
+    pub fn length_counter(&self) -> Option<(usize, usize)> {
+        if !matches!(self.mode, InputMode::Editing | InputMode::EditingPopup) {
+            return None;
+        }
+        let (max, _) = self.input_rules.max_length(self.cursor.col)?;
+        Some((self.editor.text().chars().count(), max))
+    }
+
+    fn current_field(&self) -> Option<&String> {
+        self.entries.get(self.cursor.row)?.field(self.cursor.col)
+    }
+
+    // Reload the buffer if the entry changed underneath it, e.g. a deleted row
+    fn sync_editor(&mut self) {
+        if self.current_field().map_or("", String::as_str) != self.editor.text() {
+            self.open_editor();
+        }
+    }
+
+    // Apply an edit to the field buffer and write the result back to the entry
+    fn edit_field(&mut self, edit: impl FnOnce(&mut FieldEditor)) {
+        if self.locked || !self.can_change_cell(self.cursor.row, self.cursor.col) {
+            return;
+        }
+        if self.folded_rows(self.cursor.row).is_some() {
+            self.toggle_group();
+        }
+        self.fill_defaults(self.cursor.row);
+        self.sync_editor();
+        let limit = self
+            .input_rules
+            .max_length(self.cursor.col)
+            .filter(|(_, hard)| *hard);
+        let before = limit.map(|_| self.editor.clone());
+        edit(&mut self.editor);
+        if let (Some((max, _)), Some(before)) = (limit, before) {
+            // Edits that shorten an over-long value are still let through
+            let length = self.editor.text().chars().count();
+            if length > max && length > before.text().chars().count() {
+                self.editor = before;
+                let name = TimeEntry::field_name(self.cursor.col).unwrap_or_default();
+                self.show_message(&format!("{} is limited to {} characters", name, max));
+                return;
+            }
+        }
+        let row = self.cursor.row;
+        let Some(field) = self
+            .entries
+            .get_mut(row)
+            .and_then(|e| e.field_mut(self.cursor.col))
+        else {
+            return;
+        };
+        if field.as_str() != self.editor.text() {
+            self.editor.text().clone_into(field);
+            self.dirty = true;
+        }
+    }
+
+    // Keys shared by cell and popup editing: cursor movement, Shift to select,
+    // Ctrl+A/C/X/V for the clipboard, Ctrl+Z/R to undo and redo and Alt+letter to
+    // change case
+    fn edit_key(&mut self, key: event::KeyEvent) {
+        let select = key.modifiers.contains(event::KeyModifiers::SHIFT);
+        let ctrl = key.modifiers.contains(event::KeyModifiers::CONTROL)
+            && !key.modifiers.contains(event::KeyModifiers::ALT);
+        let alt = key.modifiers.contains(event::KeyModifiers::ALT)
+            && !key.modifiers.contains(event::KeyModifiers::CONTROL);
+        let multiline = matches!(self.mode, InputMode::EditingPopup);
+        match key.code {
+            event::KeyCode::Left => self.edit_field(|editor| editor.left(select)),
+            event::KeyCode::Right => self.edit_field(|editor| editor.right(select)),
+            event::KeyCode::Home => self.edit_field(|editor| editor.home(select)),
+            event::KeyCode::End => self.edit_field(|editor| editor.end(select)),
+            event::KeyCode::Up if multiline => {
+                self.edit_field(|editor| editor.vertical(-1, select))
+            }
+            event::KeyCode::Down if multiline => {
+                self.edit_field(|editor| editor.vertical(1, select))
+            }
+            event::KeyCode::Backspace => self.edit_field(FieldEditor::backspace),
+            event::KeyCode::Delete => self.edit_field(FieldEditor::delete),
+            event::KeyCode::Char('a') if ctrl => self.edit_field(FieldEditor::select_all),
+            event::KeyCode::Char('c') if ctrl => self.copy_selection(false),
+            event::KeyCode::Char('x') if ctrl => self.copy_selection(true),
+            event::KeyCode::Char('v') if ctrl => self.paste_clipboard(),
+            event::KeyCode::Char('z') if ctrl => {
+                let mut undone = false;
+                self.edit_field(|editor| undone = editor.undo());
+                if !undone {
+                    self.show_message("Nothing to undo");
+                }
+            }
+            event::KeyCode::Char('r') if ctrl => {
+                let mut redone = false;
+                self.edit_field(|editor| redone = editor.redo());
+                if !redone {
+                    self.show_message("Nothing to redo");
+                }
+            }
+            event::KeyCode::Char(_) if ctrl => {}
+            event::KeyCode::Char(c) if alt => self.change_text(c, multiline),
+            event::KeyCode::Char(c) => self.insert_text(&c.to_string()),
+            _ => {}
+        }
+    }
+
+    // Alt+U/L upper- and lower-case the word at the cursor, Alt+C capitalizes the
+    // field's sentences and Alt+J joins the description's lines. Each acts on the
+    // selection instead when there is one, and undoes in one step.
+    fn change_text(&mut self, key: char, multiline: bool) {
+        let mut changed = false;
+        match key.to_ascii_lowercase() {
+            'u' => self.edit_field(|editor| changed = editor.change_word(str::to_uppercase)),
+            'l' => self.edit_field(|editor| changed = editor.change_word(str::to_lowercase)),
+            'c' => {
+                self.edit_field(|editor| changed = editor.change_all(text::capitalize_sentences))
+            }
+            'j' if multiline => {
+                self.edit_field(|editor| changed = editor.change_all(text::join_lines))
+            }
+            _ => return,
+        }
+        if !changed {
+            self.show_message("Nothing to change");
+        }
+    }
+
+    fn popup_tab(&mut self) {
+        if !self.snippet_tab() {
+            self.next_col();
+            self.popup_scroll = 0;
+        }
+    }
+
+    // Tab in the description editor: expand a ";name" snippet before the cursor, or move
+    // on to the next placeholder of the last expansion. False when Tab should leave the cell.
+    fn snippet_tab(&mut self) -> bool {
+        self.sync_editor();
+        let description = self.editor.text();
+        let cursor = text::byte_offset(description, self.editor.cursor());
+        if let Some((start, name)) = snippets::trigger_before(&description[..cursor]) {
+            if let Some(template) = self.config.snippets.get(name) {
+                let mut expanded = description.to_string();
+                expanded.replace_range(start..cursor, template);
+                let end = text::grapheme_count(&expanded[..start + template.len()]);
+                let placeholders = snippets::placeholders(template);
+                self.edit_field(|editor| editor.replace(expanded, end));
+                self.snippet_placeholders = placeholders;
+                self.jump_to_placeholder(start);
+                return true;
+            }
+        }
+        !self.snippet_placeholders.is_empty()
+            && (self.jump_to_placeholder(cursor) || self.jump_to_placeholder(0))
+    }
+
+    // Remove the next of the snippet's placeholders and put the cursor where it was,
+    // ready to type
+    fn jump_to_placeholder(&mut self, from: usize) -> bool {
+        let mut description = self.editor.text().to_string();
+        let Some((start, end, index)) =
+            snippets::next_placeholder(&description, from, &self.snippet_placeholders)
+        else {
+            return false;
+        };
+        let placeholder = self.snippet_placeholders.remove(index);
+        let message = format!(
+            "Type the {}, Tab for the next placeholder",
+            placeholder.trim_matches(['{', '}'])
+        );
+        description.replace_range(start..end, "");
+        let cursor = text::grapheme_count(&description[..start]);
+        self.edit_field(|editor| editor.replace(description, cursor));
+        self.show_message(&message);
+        true
+    }
+
+    fn enter_edit(&mut self) {
+        if !self.can_change_entries() || !self.can_change_cell(self.cursor.row, self.cursor.col) {
+            return;
+        }
+        // A summary row is edited as the row it is, so its group opens up
+        if self.folded_rows(self.cursor.row).is_some() {
+            self.toggle_group();
+        }
+        self.fill_defaults(self.cursor.row);
+        match self.mode {
+            InputMode::ViewingPopup => {
+                self.mode = InputMode::EditingPopup;
+            }
+            _ => {
+                if self.cursor.col == 3 && self.auto_popup() {
+                    self.mode = InputMode::EditingPopup;
+                } else {
+                    self.mode = InputMode::Editing;
+                }
+            }
+        }
+        // Start editing with the cursor at the end of the field
+        self.open_editor();
+        if self.config.ui.time_picker {
+            self.open_time_picker();
+        }
+    }
+
+    // Overlay for Start/End Time cells, opened with Ctrl+T or by ui.time_picker
+    fn open_time_picker(&mut self) {
+        if !matches!(self.mode, InputMode::Editing) || !matches!(self.cursor.col, 4 | 5) {
+            return;
+        }
+        let time = self
+            .current_field()
+            .and_then(|value| TimeEntry::parse_time(value))
+            .unwrap_or_else(|| {
+                // Now, rounded down to the step so Up/Down land on tidy times
+                let now = Self::now_minute();
+                let offset = now.minute() % self.config.ui.time_step.max(1);
+                now - chrono::Duration::minutes(i64::from(offset))
+            });
+        self.time_picker = Some(TimePicker {
+            time,
+            minutes_selected: true,
+        });
+        self.mode = InputMode::TimePicker;
+    }
+
+    fn close_time_picker(&mut self) {
+        if matches!(self.mode, InputMode::TimePicker) {
+            self.time_picker = None;
+            self.mode = InputMode::Editing;
+        }
+    }
+
+    fn now_minute() -> NaiveTime {
+        let now = chrono::Local::now().time();
+        NaiveTime::from_hms_opt(now.hour(), now.minute(), 0).unwrap_or(now)
+    }
+
+    fn time_picker_key(&mut self, key: event::KeyEvent) {
+        let Some(picker) = self.time_picker.as_mut() else {
+            self.mode = InputMode::Editing;
+            return;
+        };
+        let step = self.config.ui.time_step;
+        if key.code == event::KeyCode::Char('l')
+            && key.modifiers.contains(event::KeyModifiers::CONTROL)
+        {
+            self.toggle_ripple();
+            return;
+        }
+        match key.code {
+            event::KeyCode::Up | event::KeyCode::Char('k') | event::KeyCode::Char('+') => {
+                picker.adjust(true, step)
+            }
+            event::KeyCode::Down | event::KeyCode::Char('j') | event::KeyCode::Char('-') => {
+                picker.adjust(false, step)
+            }
+            event::KeyCode::Left
+            | event::KeyCode::Right
+            | event::KeyCode::Char('h')
+            | event::KeyCode::Char('l') => picker.minutes_selected = !picker.minutes_selected,
+            event::KeyCode::Char('n') => picker.time = Self::now_minute(),
+            event::KeyCode::Char('p') => {
+                let previous_end = self
+                    .cursor
+                    .row
+                    .checked_sub(1)
+                    .and_then(|row| TimeEntry::parse_time(&self.entries[row].end_time));
+                match (previous_end, self.time_picker.as_mut()) {
+                    (Some(time), Some(picker)) => picker.time = time,
+                    _ => self.show_message("The previous entry has no end time"),
+                }
+            }
+            event::KeyCode::Enter => self.pick_time(),
+            event::KeyCode::Tab => {
+                self.pick_time();
+                self.next_col();
+            }
+            event::KeyCode::BackTab => {
+                self.pick_time();
+                self.prev_col();
+            }
+            event::KeyCode::Esc => self.close_time_picker(),
+            event::KeyCode::Char(c) if c.is_ascii_digit() => {
+                // Typing a time instead
+                self.close_time_picker();
+                self.edit_field(|editor| {
+                    editor.select_all();
+                    editor.insert(&c.to_string());
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn pick_time(&mut self) {
+        let Some(picker) = self.time_picker else {
+            return;
+        };
+        self.close_time_picker();
+        let value = picker.time.format("%H:%M").to_string();
+        self.edit_field(|editor| {
+            editor.select_all();
+            editor.insert(&value);
+        });
+    }
+
+    fn exit_edit(&mut self) {
+        self.commit_field();
+        self.mode = InputMode::Navigation;
+        if self.cursor.row == self.entries.len() - 1 {
+            self.add_blank_row(matches!(
+                self.config.ui.new_row,
+                NewRow::Edit | NewRow::Both
+            ));
+        }
+        self.auto_save();
+    }
+
+    // Add a blank row at the end when `enabled` by ui.new_row and the last row is complete,
+    // which also keeps a second blank row from piling up behind the first
+    fn add_blank_row(&mut self, enabled: bool) -> bool {
+        let complete = self.entries.last().is_some_and(|entry| entry.is_complete());
+        if !enabled || !complete || self.locked {
+            return false;
+        }
+        self.entries.push(TimeEntry::new());
+        true
+    }
+
+    fn auto_save(&mut self) {
+        if self.config.ui.auto_save && self.dirty {
+            let _ = self.save();
+        }
+    }
+
+    // Tidy up the cell being edited as the cursor leaves it
+    fn commit_field(&mut self) {
+        self.snippet_placeholders.clear();
+        if !matches!(self.mode, InputMode::Editing | InputMode::EditingPopup) {
+            return;
+        }
+        let Some(value) = self.current_field() else {
+            return;
+        };
+        let mut committed = value.clone();
+        if matches!(self.cursor.col, 4 | 5) {
+            // `+15` or `-5` nudge the time the field had before editing
+            let base = if self.editor.text() == value {
+                self.editor.original()
+            } else {
+                ""
+            };
+            if let Some(time) = time_entry::apply_offset(&committed, base) {
+                committed = time;
+            }
+        }
+        if self.config.ui.normalize_whitespace {
+            // Identifiers collapse inner runs of spaces, free text only loses its ends
+            let collapse = matches!(self.cursor.col, 1 | 2);
+            committed = time_entry::normalize_whitespace(&committed, collapse);
+        }
+        committed = self.input_rules.apply(self.cursor.col, committed);
+        let mut refused = None;
+        if matches!(self.cursor.col, 4 | 5) && self.config.checks.block_outside_hours {
+            if let Some(problem) =
+                checks::outside_hours(self.cursor.col, &committed, &self.config.checks)
+            {
+                committed = self.editor.original().to_string();
+                refused = Some(match committed.as_str() {
+                    "" => format!("{}, left it empty", problem),
+                    original => format!("{}, kept {}", problem, original),
+                });
+            }
+        }
+        let new_task = self.cursor.col == 1
+            && !committed.trim().is_empty()
+            && !committed
+                .trim()
+                .eq_ignore_ascii_case(self.editor.original().trim());
+        let moved_time = matches!(self.cursor.col, 4 | 5)
+            .then(|| (self.editor.original().to_string(), committed.clone()));
+        if &committed != value {
+            self.edit_field(|editor| {
+                let cursor = text::grapheme_count(&committed);
+                editor.replace(committed, cursor);
+            });
+        }
+        if let Some(message) = refused {
+            self.show_message(&message);
+        }
+        if new_task && self.config.ui.suggest_descriptions {
+            self.offer_descriptions = Some(self.cursor.row);
+        }
+        if let Some((old, new)) = moved_time {
+            if std::mem::replace(&mut self.ripple, self.config.ui.ripple_times) {
+                self.ripple_time(&old, &new);
+            }
+        }
+    }
+
+    fn toggle_ripple(&mut self) {
+        if !matches!(self.cursor.col, 4 | 5) {
+            return;
+        }
+        self.ripple = !self.ripple;
+        let neighbour = if self.cursor.col == 5 {
+            "the next entry's Start Time"
+        } else {
+            "the previous entry's End Time"
+        };
+        self.show_message(&if self.ripple {
+            format!("Ripple on for this edit, {} moves along", neighbour)
+        } else {
+            format!("Ripple off for this edit, {} stays", neighbour)
+        });
+    }
+
+    // After an End Time moves from `old` to `new`, a next row that started at `old` starts
+    // at `new` instead, and the same for a Start Time and the previous row's end, so back
+    // to back entries stay back to back. A neighbour that would end before it starts is
+    // left alone.
+    fn ripple_time(&mut self, old: &str, new: &str) {
+        let (Some(old), Some(new)) = (TimeEntry::parse_time(old), TimeEntry::parse_time(new))
+        else {
+            return;
+        };
+        if old == new {
+            return;
+        }
+        let row = self.cursor.row;
+        let (neighbour, col) = if self.cursor.col == 5 {
+            (row + 1, 4)
+        } else {
+            match row.checked_sub(1) {
+                Some(previous) => (previous, 5),
+                None => return,
+            }
+        };
+        let Some(entry) = self.entries.get_mut(neighbour) else {
+            return;
+        };
+        let protected = entry.is_protected(col);
+        let Some(field) = entry
+            .field_mut(col)
+            .filter(|field| TimeEntry::parse_time(field) == Some(old))
+        else {
+            return;
+        };
+        if protected {
+            self.show_message(&format!(
+                "Row {}'s {} is protected, it wasn't moved",
+                neighbour + 1,
+                TimeEntry::field_name(col).unwrap_or_default()
+            ));
+            return;
+        }
+        let previous = std::mem::replace(field, new.format("%H:%M").to_string());
+        let start = TimeEntry::parse_time(&entry.start_time);
+        let end = TimeEntry::parse_time(&entry.end_time);
+        if matches!((start, end), (Some(start), Some(end)) if end < start) {
+            if let Some(field) = entry.field_mut(col) {
+                *field = previous;
+            }
+            self.show_message(&format!(
+                "Row {} would end before it starts, its time wasn't moved",
+                neighbour + 1
+            ));
+            return;
+        }
+        self.dirty = true;
+        self.show_message(&format!(
+            "Moved row {}'s {} to {} too",
+            neighbour + 1,
+            TimeEntry::field_name(col).unwrap_or_default(),
+            new.format("%H:%M")
+        ));
+    }
+
+    // Once a task number is entered in a row without a description, offer the last few
+    // written for it, so the same work is phrased the same way in reports
+    fn offer_descriptions(&mut self) {
+        let Some(row) = self.offer_descriptions.take() else {
+            return;
+        };
+        if !matches!(
+            self.mode,
+            InputMode::Navigation
+                | InputMode::Editing
+                | InputMode::ViewingPopup
+                | InputMode::EditingPopup
+        ) {
+            return;
+        }
+        let Some(entry) = self.entries.get(row) else {
+            return;
+        };
+        if !entry.time_entry.trim().is_empty() {
+            return;
+        }
+        let others: Vec<&TimeEntry> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != row)
+            .map(|(_, entry)| entry)
+            .collect();
+        let descriptions =
+            self.task_descriptions
+                .for_task(&entry.task_number, &others, DESCRIPTION_CHOICES);
+        if descriptions.is_empty() {
+            return;
+        }
+        self.description_pick = Some(DescriptionPick {
+            row,
+            task: entry.task_number.trim().to_string(),
+            descriptions,
+            selected: 0,
+            resume: self.mode.clone(),
+        });
+        self.mode = InputMode::PickDescription;
+    }
+
+    fn pick_description_key(&mut self, key: event::KeyEvent) {
+        let Some(pick) = self.description_pick.as_mut() else {
+            return;
+        };
+        match key.code {
+            event::KeyCode::Up => pick.selected = pick.selected.saturating_sub(1),
+            event::KeyCode::Down if pick.selected + 1 < pick.descriptions.len() => {
+                pick.selected += 1;
+            }
+            event::KeyCode::Char(c @ '1'..='9') => {
+                let choice = c as usize - '1' as usize;
+                if choice < pick.descriptions.len() {
+                    pick.selected = choice;
+                    self.use_description(true);
+                }
+            }
+            event::KeyCode::Enter => self.use_description(true),
+            event::KeyCode::Esc => self.use_description(false),
+            _ => {}
         }
     }
 
-    fn next_col(&mut self) {
-        if self.cursor.col < 5 {
-            self.cursor.col += 1;
-        } else {
-            // When on last column (End Time), move to next row, first column
-            self.cursor.col = 1;
-            self.next_row();
+    // Fill in the picked description, or skip them, and carry on where the keys were
+    fn use_description(&mut self, fill: bool) {
+        let Some(pick) = self.description_pick.take() else {
+            return;
+        };
+        self.mode = pick.resume;
+        if !fill {
+            return;
         }
-        self.update_mode_for_column();
+        let Some(entry) = self.entries.get_mut(pick.row) else {
+            return;
+        };
+        entry.time_entry = pick.descriptions[pick.selected].clone();
+        self.dirty = true;
+        self.auto_save();
+        // The cell being edited may be the description
+        self.open_editor();
+        self.show_message(&format!(
+            "Filled in row {}'s description from earlier {} entries",
+            pick.row + 1,
+            pick.task
+        ));
     }
 
-    fn prev_col(&mut self) {
-        if self.cursor.col > 1 {
-            self.cursor.col -= 1;
-        } else {
-            // When on first column (Task Number), go to previous row's last column (End Time)
-            self.cursor.col = 5;
-            if self.cursor.row > 0 {
-                self.cursor.row -= 1;
+    fn insert_text(&mut self, text: &str) {
+        self.edit_field(|editor| editor.insert(text));
+    }
+
+    // Pasted text arrives in one piece, which is also how many IMEs commit composed text
+    fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n");
+        match self.mode {
+            InputMode::Editing | InputMode::EditingPopup => {
+                let multiline = matches!(self.mode, InputMode::EditingPopup);
+                let text: String = text
+                    .chars()
+                    .filter_map(|c| match c {
+                        '\n' if multiline => Some(c),
+                        '\n' | '\r' | '\t' => Some(' '),
+                        c if c.is_control() => None,
+                        c => Some(c),
+                    })
+                    .collect();
+                self.insert_text(&text);
+            }
+            InputMode::Prompt => {
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt
+                        .input
+                        .extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+            InputMode::Navigation | InputMode::ViewingPopup => {
+                self.show_message("Enter edit mode (i) before pasting");
             }
+            _ => {}
         }
-        self.update_mode_for_column();
     }
 
-    fn next_row(&mut self) {
-        if self.cursor.row < self.entries.len() - 1 {
-            self.cursor.row += 1;
-        } else {
-            // Auto-create new row if at the end and current row is complete
-            if self.entries[self.cursor.row].is_complete() {
-                self.entries.push(TimeEntry::new());
-                self.cursor.row += 1;
-            }
+    fn toggle_checked(&mut self) {
+        if let Some(entry) = self.entries.get_mut(self.cursor.row) {
+            entry.checked = !entry.checked;
+            self.dirty = true;
+            self.auto_save();
+        }
+    }
+
+    // Rows in the export with check warnings, listed before exporting
+    pub fn export_warnings(&self, scope: ExportScope) -> Vec<(usize, Vec<String>)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| scope.includes(e))
+            .map(|(i, e)| (i, self.row_warnings(e)))
+            .filter(|(_, warnings)| !warnings.is_empty())
+            .collect()
+    }
+
+    // Everything an export should be checked for, listed without exporting
+    fn validate(&mut self) {
+        self.commit_field();
+        self.issues = validate::issues(
+            &self.entries,
+            &self.config.checks,
+            mapping::for_export(&self.config),
+            &self.input_rules,
+        );
+        self.issue_selected = 0;
+        if self.issues.is_empty() {
+            let rows = self
+                .entries
+                .iter()
+                .filter(|e| !e.is_entirely_empty())
+                .count();
+            self.show_message(&format!("No problems found in {} row(s)", rows));
+            return;
+        }
+        self.mode = InputMode::Validation;
+    }
+
+    fn go_to_issue(&mut self) {
+        let Some(issue) = self.issues.get(self.issue_selected) else {
+            return;
+        };
+        self.filter = None;
+        self.cursor.row = issue.row.min(self.entries.len().saturating_sub(1));
+        if let Some(col) = issue.col {
+            self.cursor.col = col;
         }
+        self.mode = InputMode::Navigation;
         self.update_mode_for_column();
     }
 
-    fn prev_row(&mut self) {
-        if self.cursor.row > 0 {
-            self.cursor.row -= 1;
+    fn run_export(&mut self, scope: ExportScope) {
+        if !self.export_warnings(scope).is_empty() {
+            self.pending_export = Some(scope);
+            self.mode = InputMode::ConfirmExport;
+            return;
+        }
+        self.export_confirmed(scope);
+    }
+
+    fn export_confirmed(&mut self, scope: ExportScope) {
+        self.mode = InputMode::Navigation;
+        if let Err(e) = self.export(scope) {
+            self.show_message(&format!("Export failed: {}", e));
         }
         self.update_mode_for_column();
     }
 
-    fn update_mode_for_column(&mut self) {
-        // Auto-show popup when on Time Entry column (3), auto-hide when not
-        if self.cursor.col == 3 {
-            // Convert to popup mode while preserving edit state
-            match self.mode {
-                InputMode::Navigation => self.mode = InputMode::ViewingPopup,
-                InputMode::Editing => self.mode = InputMode::EditingPopup,
-                _ => {} // Already in popup mode or other mode
-            }
-        } else {
-            // Convert back to regular mode while preserving edit state
-            match self.mode {
-                InputMode::ViewingPopup => self.mode = InputMode::Navigation,
-                InputMode::EditingPopup => self.mode = InputMode::Editing,
-                _ => {} // Already in regular mode or other mode
-            }
-            self.popup_scroll = 0;
+    fn export(&mut self, scope: ExportScope) -> Result<()> {
+        if self.export_job.is_some() {
+            self.export_passphrase = None;
+            self.email_export = false;
+            self.show_message("An export is still running, try again when it's done");
+            return Ok(());
         }
-        // Update text cursor position when switching cells
-        self.update_text_cursor();
-    }
-
-    fn update_text_cursor(&mut self) {
-        // Set text cursor to end of current field
-        if self.cursor.row < self.entries.len() {
-            let entry = &self.entries[self.cursor.row];
-            let text_length = match self.cursor.col {
-                1 => entry.task_number.len(),
-                2 => entry.work_code.len(),
-                3 => entry.time_entry.len(),
-                4 => entry.start_time.len(),
-                5 => entry.end_time.len(),
-                _ => 0,
-            };
-            self.text_cursor = text_length;
+        self.commit_field();
+        let count = self.entries.iter().filter(|e| scope.includes(e)).count();
+        if count == 0 {
+            self.export_passphrase = None;
+            self.email_export = false;
+            self.show_message(match scope {
+                ExportScope::All => "Nothing to export, every row is empty",
+                ExportScope::Changed => "Nothing to export, all rows are already exported",
+                ExportScope::Checked => "Nothing to export, check rows with space first",
+            });
+            return Ok(());
+        }
+        self.export_job = Some(ExportJob::start(
+            &self.entries,
+            &self.day_note,
+            self.day,
+            &self.config,
+            scope,
+            self.export_passphrase.take(),
+            std::mem::take(&mut self.email_export),
+        ));
+        self.show_message(&format!("Exporting {} row(s)...", count));
+        Ok(())
+    }
+
+    fn check_export(&mut self) {
+        let Some(result) = self.export_job.as_ref().and_then(ExportJob::finished) else {
+            return;
+        };
+        let job = self.export_job.take().expect("checked above");
+        if let Err(e) = self.finish_export(job, result) {
+            tracing::error!("export failed: {:#}", e);
+            self.show_message(&format!("Export failed: {}", e));
+        }
+    }
+
+    // Mark what the export wrote as exported and archive the day
+    fn finish_export(
+        &mut self,
+        job: ExportJob,
+        result: std::result::Result<Saved, String>,
+    ) -> Result<()> {
+        let saved = result.map_err(anyhow::Error::msg)?;
+        let (path, note) = (saved.path.clone(), saved.unmapped_note());
+        let scope = job.scope;
+        let exported: Vec<&TimeEntry> = job.snapshot.iter().filter(|e| scope.includes(e)).collect();
+        let count = exported.len();
+        if !self.config.hooks.webhook_url.is_empty() {
+            let summary = ExportSummary::new(job.date, &exported, &path);
+            self.webhook_result = Some(hooks::notify_export(
+                &self.config.hooks.webhook_url,
+                summary,
+            ));
+        }
+        self.mark_exported(&job.snapshot, scope);
+        tracing::info!(
+            "exported {} rows of {} to {}",
+            count,
+            job.date,
+            path.display()
+        );
+        self.show_message(&format!(
+            "Exported {} row(s) to {}{}",
+            count,
+            path.display(),
+            note
+        ));
+        if !self.config.upload.url.trim().is_empty() {
+            self.upload_result = Some(upload::upload_export(&self.config.upload, path.clone()));
+        }
+        if job.email {
+            let period = job.date.format("%Y-%m-%d").to_string();
+            self.email_result = Some(email::send_export(
+                &self.config.email,
+                &period,
+                path.clone(),
+            ));
+            self.show_message(&format!(
+                "Exported {} row(s) to {}{}, emailing it...",
+                count,
+                path.display(),
+                note
+            ));
+        }
+
+        let day = Day {
+            date: job.date,
+            entries: self.entries.clone(),
+            note: self.day_note.clone(),
+        };
+        archive::save_day(&self.data_dir, &day)?;
+        self.load_history();
+        if self.config.export.lock && matches!(scope, ExportScope::All) && !self.past_day {
+            archive::set_locked(&self.data_dir, true)?;
+            self.locked = true;
+            self.show_message(&format!(
+                "Exported {} row(s) to {}{} and locked the day",
+                count,
+                path.display(),
+                note
+            ));
         }
+        self.save()
     }
 
-    fn get_current_field_length(&self) -> usize {
-        if self.cursor.row < self.entries.len() {
-            let entry = &self.entries[self.cursor.row];
-            match self.cursor.col {
-                1 => entry.task_number.len(),
-                2 => entry.work_code.len(),
-                3 => entry.time_entry.len(),
-                4 => entry.start_time.len(),
-                5 => entry.end_time.len(),
-                _ => 0,
+    // Rows are matched to the snapshot the export wrote by position, or by content if rows
+    // were added or removed meanwhile. A row edited while the export ran gets the exported
+    // version's hash, so it shows as changed since the export.
+    fn mark_exported(&mut self, snapshot: &[TimeEntry], scope: ExportScope) {
+        let mut marked = vec![false; self.entries.len()];
+        for (i, written) in snapshot.iter().enumerate() {
+            if !scope.includes(written) {
+                continue;
             }
-        } else {
-            0
+            let hash = written.content_hash();
+            let same = |entry: &TimeEntry| entry.content_hash() == hash;
+            let row = Some(i)
+                .filter(|&i| !marked.get(i).copied().unwrap_or(true) && same(&self.entries[i]))
+                .or_else(|| (0..self.entries.len()).find(|&j| !marked[j] && same(&self.entries[j])))
+                .or_else(|| Some(i).filter(|&i| !marked.get(i).copied().unwrap_or(true)));
+            let Some(row) = row else {
+                continue;
+            };
+            marked[row] = true;
+            self.entries[row].mark_exported(hash);
+            self.entries[row].checked = false;
         }
+        self.dirty = true;
     }
 
-    fn move_cursor_up_in_text(&mut self) {
-        if self.cursor.col != 3 || self.cursor.row >= self.entries.len() {
+    // Only failures are worth interrupting for, the export message already says it worked
+    fn check_webhook(&mut self) {
+        let Some(rx) = &self.webhook_result else {
             return;
+        };
+        match rx.try_recv() {
+            Ok(Err(e)) => {
+                tracing::warn!("export webhook failed: {}", e);
+                self.show_message(&format!("Export webhook failed: {}", e));
+                self.webhook_result = None;
+            }
+            Ok(Ok(())) | Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.webhook_result = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
         }
+    }
 
-        let text = self.entries[self.cursor.row].time_entry.clone();
-        let lines: Vec<&str> = text.lines().collect();
+    fn import_calendar(&mut self) {
+        if !self.can_change_entries() {
+            return;
+        }
+        if self.config.import.ics_path.is_empty() {
+            self.show_message("Set import.ics_path in slothtime.toml to import meetings");
+            return;
+        }
+        let today = chrono::Local::now().date_naive();
+        let imported = match crate::import::import_ics(
+            &self.config.import.ics_path,
+            today,
+            &self.config.import.work_code,
+        ) {
+            Ok(imported) => imported,
+            Err(e) => {
+                tracing::warn!(
+                    "calendar import from {} failed: {:#}",
+                    self.config.import.ics_path,
+                    e
+                );
+                self.show_message(&format!("Calendar import failed: {}", e));
+                return;
+            }
+        };
 
-        if lines.is_empty() {
+        // Skip meetings that were already imported
+        let new: Vec<TimeEntry> = imported
+            .into_iter()
+            .filter(|m| {
+                !self.entries.iter().any(|e| {
+                    e.start_time == m.start_time
+                        && e.end_time == m.end_time
+                        && e.time_entry == m.time_entry
+                })
+            })
+            .collect();
+        if new.is_empty() {
+            self.show_message("No new meetings found for today");
             return;
         }
 
-        // Find current line and position within that line
-        let mut char_count = 0;
-        let mut current_line = 0;
-        let mut pos_in_line = 0;
+        let count = new.len();
+        self.append_entries(new);
+        self.show_message(&format!("Imported {} meeting(s) from calendar", count));
+    }
+
+    fn append_entries(&mut self, new: Vec<TimeEntry>) {
+        // Drop trailing blank rows so new entries land right after existing ones
+        while self.entries.len() > 1 && self.entries.last().is_some_and(|e| e.is_entirely_empty()) {
+            self.entries.pop();
+        }
+        if self.entries.len() == 1 && self.entries[0].is_entirely_empty() {
+            self.entries.clear();
+        }
+        self.entries.extend(new);
+        self.entries.push(TimeEntry::new());
+        if self.cursor.row >= self.entries.len() {
+            self.cursor.row = self.entries.len() - 1;
+        }
+        self.update_mode_for_column();
+        let _ = self.save();
+    }
+
+    // `:copyday FROM [TO] [notimes]` copies every entry of one day onto another, today's
+    // sheet by default. Days are `today`, `yesterday` or YYYY-MM-DD.
+    fn copy_day(&mut self, args: &str) {
+        let mut words: Vec<&str> = args.split_whitespace().collect();
+        let keep_times = words.last() != Some(&"notimes");
+        if !keep_times {
+            words.pop();
+        }
+        let parse = |word: &str| match word {
+            "today" => Some(self.day),
+            "yesterday" => self.day.pred_opt(),
+            _ => NaiveDate::parse_from_str(word, "%Y-%m-%d").ok(),
+        };
+        let (from, to) = match words.as_slice() {
+            [from] => (parse(from), Some(self.day)),
+            [from, to] => (parse(from), parse(to)),
+            _ => (None, None),
+        };
+        let (Some(from), Some(to)) = (from, to) else {
+            self.show_message(
+                "Usage: :copyday FROM [TO] [notimes], days as today, yesterday or 2025-09-30",
+            );
+            return;
+        };
+        if from == to {
+            self.show_message("Pick two different days to copy between");
+            return;
+        }
 
-        for (line_idx, line) in lines.iter().enumerate() {
-            if char_count + line.len() >= self.text_cursor {
-                current_line = line_idx;
-                pos_in_line = self.text_cursor - char_count;
-                break;
+        let source = if from == self.day {
+            self.commit_field();
+            self.entries.clone()
+        } else {
+            match archive::load_day(&self.data_dir, from) {
+                Ok(Some(day)) => day.entries,
+                Ok(None) => {
+                    self.show_message(&format!("Nothing archived for {}", from.format("%Y-%m-%d")));
+                    return;
+                }
+                Err(e) => {
+                    self.show_message(&format!(
+                        "Could not read {}: {}",
+                        from.format("%Y-%m-%d"),
+                        e
+                    ));
+                    return;
+                }
             }
-            char_count += line.len() + 1; // +1 for newline
+        };
+        // Copies start out unexported and unchecked
+        let copies: Vec<TimeEntry> = source
+            .iter()
+            .filter(|entry| !entry.is_entirely_empty())
+            .map(|entry| TimeEntry {
+                task_number: entry.task_number.clone(),
+                work_code: entry.work_code.clone(),
+                time_entry: entry.time_entry.clone(),
+                start_time: if keep_times {
+                    entry.start_time.clone()
+                } else {
+                    String::new()
+                },
+                end_time: if keep_times {
+                    entry.end_time.clone()
+                } else {
+                    String::new()
+                },
+                ..TimeEntry::new()
+            })
+            .collect();
+        if copies.is_empty() {
+            self.show_message(&format!(
+                "{} has no entries to copy",
+                from.format("%Y-%m-%d")
+            ));
+            return;
         }
+        let count = copies.len();
 
-        // Move to previous line if possible
-        if current_line > 0 {
-            let prev_line = lines[current_line - 1];
-            let new_pos_in_line = pos_in_line.min(prev_line.len());
-
-            // Calculate new cursor position
-            let mut new_cursor = 0;
-            for i in 0..(current_line - 1) {
-                new_cursor += lines[i].len() + 1;
+        if to == self.day {
+            if !self.can_change_entries() {
+                return;
+            }
+            self.append_entries(copies);
+        } else {
+            // Other days are added to their archive, like an exported day
+            let mut day = match archive::load_day(&self.data_dir, to) {
+                Ok(day) => day.unwrap_or(Day {
+                    date: to,
+                    entries: Vec::new(),
+                    note: String::new(),
+                }),
+                Err(e) => {
+                    self.show_message(&format!("Could not read {}: {}", to.format("%Y-%m-%d"), e));
+                    return;
+                }
+            };
+            day.entries.extend(copies);
+            if let Err(e) = archive::save_day(&self.data_dir, &day) {
+                self.show_message(&format!("Could not save {}: {}", to.format("%Y-%m-%d"), e));
+                return;
             }
-            new_cursor += new_pos_in_line;
+        }
+        self.show_message(&format!(
+            "Copied {} entries from {} to {}{}",
+            count,
+            from.format("%Y-%m-%d"),
+            if to == self.day {
+                "today".to_string()
+            } else {
+                to.format("%Y-%m-%d").to_string()
+            },
+            if keep_times { "" } else { " without times" }
+        ));
+    }
 
-            self.text_cursor = new_cursor;
+    fn clear_entries(&mut self) {
+        let cleared = std::mem::replace(&mut self.entries, vec![TimeEntry::new()]);
+        for entry in cleared {
+            self.move_to_trash(entry);
+        }
+        self.cursor = Cursor::new();
+        // Clearing starts a new day, which starts without a note or a lock
+        self.set_day_note("");
+        if self.locked {
+            let _ = archive::set_locked(&self.data_dir, false);
+            self.locked = false;
         }
+        let _ = self.save();
+        let _ = trash::save(&self.data_dir, &self.trash);
     }
 
-    fn move_cursor_down_in_text(&mut self) {
-        if self.cursor.col != 3 || self.cursor.row >= self.entries.len() {
+    // A suspend or clock change while an entry runs would count the skipped time as
+    // worked. Ask about it then, and otherwise just say the clock moved.
+    fn check_clock(&mut self) {
+        let Some(jump) = self.clock.check() else {
+            return;
+        };
+        tracing::info!("clock jumped from {} to {}", jump.before, jump.after);
+        let editing = matches!(
+            self.mode,
+            InputMode::Navigation
+                | InputMode::ViewingPopup
+                | InputMode::Editing
+                | InputMode::EditingPopup
+        );
+        let running = self.running_entry().is_some()
+            && !self.locked
+            && self.follow.is_none()
+            && !self.past_day;
+        if jump.skipped > chrono::Duration::zero() && running && editing {
+            self.commit_field();
+            self.clock_jump = Some(jump);
+            self.mode = InputMode::ClockJump;
             return;
         }
+        self.show_message(&format!(
+            "The clock jumped {} by {} at {}, check times entered around then",
+            if jump.skipped > chrono::Duration::zero() {
+                "forward"
+            } else {
+                "back"
+            },
+            time_entry::format_duration(jump.skipped.num_minutes().abs()),
+            jump.before.format("%H:%M")
+        ));
+    }
 
-        let text = self.entries[self.cursor.row].time_entry.clone();
-        let lines: Vec<&str> = text.lines().collect();
+    // Stop the running entry when the clock jumped, and with `resume` continue it in a
+    // new row from when the clock came back
+    fn settle_clock_jump(&mut self, resume: bool) {
+        let (Some(jump), Some(row)) = (self.clock_jump.take(), self.running_entry()) else {
+            self.mode = InputMode::Navigation;
+            return;
+        };
+        self.entries[row].end_time = jump.before.format("%H:%M").to_string();
+        // Past midnight the new row belongs to the next day's sheet, see check_day_rollover
+        if resume && jump.after.date_naive() == jump.before.date_naive() {
+            let entry = TimeEntry {
+                start_time: jump.after.format("%H:%M").to_string(),
+                end_time: String::new(),
+                ..self.entries[row].copy_as_new()
+            };
+            self.entries.insert(row + 1, entry);
+            self.cursor.row = row + 1;
+        }
+        self.dirty = true;
+        let _ = self.save();
+        self.show_message(&format!(
+            "Stopped the entry at {}, the {} the clock skipped isn't counted",
+            jump.before.format("%H:%M"),
+            time_entry::format_duration(jump.skipped.num_minutes())
+        ));
+        self.mode = InputMode::Navigation;
+        self.update_mode_for_column();
+    }
 
-        if lines.is_empty() {
+    // Past midnight the sheet still holds the previous day. Ask what to do once the
+    // user isn't in the middle of a dialog, rather than mixing both days in one sheet.
+    fn check_day_rollover(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        if today == self.day || self.kept_day_until == Some(today) || self.past_day {
+            return;
+        }
+        // The Slothtime being followed archives the day, and the mirror reloads it
+        if self.follow.is_some() {
+            self.day = today;
+            return;
+        }
+        if !matches!(
+            self.mode,
+            InputMode::Navigation
+                | InputMode::ViewingPopup
+                | InputMode::Editing
+                | InputMode::EditingPopup
+        ) {
+            return;
+        }
+        if self.entries.iter().all(TimeEntry::is_entirely_empty) {
+            self.day = today;
             return;
         }
+        self.commit_field();
+        self.mode = InputMode::Rollover;
+    }
 
-        // Find current line and position within that line
-        let mut char_count = 0;
-        let mut current_line = 0;
-        let mut pos_in_line = 0;
+    // Archive the previous day and start an empty sheet for today. A running entry
+    // is stopped at 23:59, and with `carry` continued in a new row from 00:00.
+    fn start_new_day(&mut self, carry: bool) {
+        let today = chrono::Local::now().date_naive();
+        let mut carried = None;
+        if let Some(row) = time_entry::running_entry(&self.entries) {
+            self.entries[row].end_time = "23:59".to_string();
+            carried = Some(TimeEntry {
+                start_time: "00:00".to_string(),
+                end_time: String::new(),
+                ..self.entries[row].copy_as_new()
+            });
+        }
+        let day = Day {
+            date: self.day,
+            entries: self.entries.clone(),
+            note: self.day_note.clone(),
+        };
+        if let Err(e) = archive::save_day(&self.data_dir, &day) {
+            tracing::error!("archiving {} failed: {:#}", self.day, e);
+            self.show_message(&format!("Failed to archive {}: {}", self.day, e));
+            return;
+        }
+        self.clear_entries();
+        if let (true, Some(entry)) = (carry, carried) {
+            self.entries.insert(0, entry);
+            let _ = self.save();
+        }
+        self.show_message(&format!(
+            "Started {}, {} was archived",
+            today.format("%a %Y-%m-%d"),
+            self.day.format("%a %Y-%m-%d")
+        ));
+        self.day = today;
+        self.kept_day_until = None;
+        self.load_history();
+        self.mode = InputMode::Navigation;
+        self.update_mode_for_column();
+    }
 
-        for (line_idx, line) in lines.iter().enumerate() {
-            if char_count + line.len() >= self.text_cursor {
-                current_line = line_idx;
-                pos_in_line = self.text_cursor - char_count;
-                break;
-            }
-            char_count += line.len() + 1; // +1 for newline
+    // Deleted entries are kept in the trash until purged, blank rows are just dropped
+    fn move_to_trash(&mut self, entry: TimeEntry) {
+        if entry.is_entirely_empty() {
+            return;
         }
+        self.trash.push(TrashedEntry {
+            entry,
+            deleted_at: chrono::Local::now(),
+        });
+    }
 
-        // Move to next line if possible
-        if current_line < lines.len() - 1 {
-            let next_line = lines[current_line + 1];
-            let new_pos_in_line = pos_in_line.min(next_line.len());
+    // Replace the damaged entries.json, keeping it under another name
+    fn repair_entries(&mut self, entries: Vec<TimeEntry>, done: &str) {
+        let kept = match repair::set_aside(&self.data_dir) {
+            Ok(path) => path,
+            Err(e) => {
+                self.show_message(&format!("Could not move the damaged file aside: {}", e));
+                return;
+            }
+        };
+        self.entries = if entries.is_empty() {
+            vec![TimeEntry::new()]
+        } else {
+            entries
+        };
+        self.cursor = Cursor::new();
+        self.load_problem = None;
+        let _ = self.save();
+        self.show_message(&format!(
+            "{}, the damaged file is kept as {}",
+            done,
+            kept.display()
+        ));
+        self.mode = InputMode::Navigation;
+        self.update_mode_for_column();
+    }
 
-            // Calculate new cursor position
-            let mut new_cursor = 0;
-            for i in 0..=current_line {
-                new_cursor += lines[i].len() + 1;
+    fn advance_tutorial(&mut self) {
+        if let Some(step) = self.tutorial {
+            if (demo::STEPS[step].done)(self) {
+                self.tutorial = Some(step + 1).filter(|next| *next < demo::STEPS.len());
             }
-            new_cursor += new_pos_in_line;
+        }
+    }
 
-            self.text_cursor = new_cursor;
+    fn open_history(&mut self) {
+        self.pending_delete = false;
+        self.commit_field();
+        let today = chrono::Local::now().date_naive();
+        match HistorySearch::open(&self.data_dir, today) {
+            Ok(history) => {
+                self.history = Some(history);
+                self.mode = InputMode::History;
+            }
+            Err(e) => self.show_message(&format!("Could not read the archive: {}", e)),
         }
-        // Note: Removed auto-scroll call to avoid borrow issues for now
     }
 
-    fn enter_edit(&mut self) {
-        match self.mode {
-            InputMode::ViewingPopup => {
-                self.mode = InputMode::EditingPopup;
+    fn history_key(&mut self, key: event::KeyEvent) {
+        let Some(history) = self.history.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(event::KeyModifiers::CONTROL);
+        let mut read = Ok(());
+        match key.code {
+            event::KeyCode::Esc => {
+                self.history = None;
+                self.mode = InputMode::Navigation;
+                self.update_mode_for_column();
             }
-            _ => {
-                if self.cursor.col == 3 {
-                    self.mode = InputMode::EditingPopup;
+            event::KeyCode::Up => history.selected = history.selected.saturating_sub(1),
+            event::KeyCode::Down => read = history.select_next(),
+            event::KeyCode::Enter => self.copy_from_history(),
+            event::KeyCode::Char('y') if ctrl => {
+                let description = history
+                    .selected_entry()
+                    .map(|(_, entry)| entry.time_entry.clone())
+                    .unwrap_or_default();
+                if description.is_empty() {
+                    self.show_message("No description to copy");
                 } else {
-                    self.mode = InputMode::Editing;
+                    self.set_clipboard(&description, "Description");
                 }
             }
+            event::KeyCode::Backspace => {
+                let mut query = history.query().to_string();
+                query.pop();
+                read = history.set_query(query);
+            }
+            event::KeyCode::Char(c) if !ctrl => {
+                let query = format!("{}{}", history.query(), c);
+                read = history.set_query(query);
+            }
+            _ => {}
         }
-        // Update text cursor when entering edit mode
-        self.update_text_cursor();
-    }
-
-    fn exit_edit(&mut self) {
-        self.mode = InputMode::Navigation;
-        // auto-create new row if last and complete
-        if self.cursor.row == self.entries.len() - 1 && self.entries[self.cursor.row].is_complete()
-        {
-            self.entries.push(TimeEntry::new());
+        if let Err(e) = read {
+            self.show_message(&format!("Could not read the archive: {}", e));
         }
     }
 
-    fn insert_char(&mut self, c: char) {
-        if self.cursor.row >= self.entries.len() {
+    // Reuse an old entry in the current row: its description, plus the task number
+    // and work code when the row has none yet
+    fn copy_from_history(&mut self) {
+        if !self.can_change_entries() || !self.can_change_cell(self.cursor.row, 3) {
             return;
         }
-        let entry = &mut self.entries[self.cursor.row];
-        let field = match self.cursor.col {
-            1 => &mut entry.task_number,
-            2 => &mut entry.work_code,
-            3 => &mut entry.time_entry,
-            4 => &mut entry.start_time,
-            5 => &mut entry.end_time,
-            _ => return,
+        let Some((date, old)) = self.history.as_ref().and_then(|h| h.selected_entry()) else {
+            return;
         };
-
-        // Insert character at cursor position
-        if self.text_cursor <= field.len() {
-            field.insert(self.text_cursor, c);
-            self.text_cursor += 1;
+        let old = old.clone();
+        let Some(entry) = self.entries.get_mut(self.cursor.row) else {
+            return;
+        };
+        if entry.task_number.is_empty() {
+            entry.task_number = old.task_number;
         }
+        if entry.work_code.is_empty() {
+            entry.work_code = old.work_code;
+        }
+        entry.time_entry = old.time_entry;
+        self.dirty = true;
+        self.auto_save();
+        self.history = None;
+        self.mode = InputMode::Navigation;
+        self.update_mode_for_column();
+        self.show_message(&format!(
+            "Copied the entry from {} into row {}",
+            date.format("%Y-%m-%d"),
+            self.cursor.row + 1
+        ));
     }
 
-    fn delete_char(&mut self) {
-        if self.cursor.row >= self.entries.len() || self.text_cursor == 0 {
+    fn restore_from_trash(&mut self) {
+        if !self.can_change_entries() {
             return;
         }
-        let entry = &mut self.entries[self.cursor.row];
-        let field = match self.cursor.col {
-            1 => &mut entry.task_number,
-            2 => &mut entry.work_code,
-            3 => &mut entry.time_entry,
-            4 => &mut entry.start_time,
-            5 => &mut entry.end_time,
-            _ => return,
-        };
-
-        // Delete character before cursor position
-        if self.text_cursor > 0 && self.text_cursor <= field.len() {
-            field.remove(self.text_cursor - 1);
-            self.text_cursor -= 1;
+        if self.trash_selected >= self.trash.len() {
+            return;
         }
-    }
+        let restored = self.trash.remove(self.trash_selected).entry;
 
-    fn export(&self) -> Result<()> {
-        crate::export::export_csv(&self.entries, &self.config)?;
-        self.save_entries()
+        // Put it back above the trailing blank row, if there is one
+        if self.entries.last().is_some_and(|e| e.is_entirely_empty()) {
+            let index = self.entries.len() - 1;
+            self.entries.insert(index, restored);
+        } else {
+            self.entries.push(restored);
+        }
+        if self.trash_selected > 0 && self.trash_selected >= self.trash.len() {
+            self.trash_selected -= 1;
+        }
+        let _ = self.save();
+        let _ = trash::save(&self.data_dir, &self.trash);
+        self.show_message("Entry restored");
     }
 
-    fn clear_entries(&mut self) {
-        self.entries = vec![TimeEntry::new()];
-        self.cursor = Cursor::new();
-        let _ = self.save_entries();
+    fn purge_from_trash(&mut self) {
+        if self.trash_selected >= self.trash.len() {
+            return;
+        }
+        self.trash.remove(self.trash_selected);
+        if self.trash_selected > 0 && self.trash_selected >= self.trash.len() {
+            self.trash_selected -= 1;
+        }
+        let _ = trash::save(&self.data_dir, &self.trash);
+        self.show_message("Entry permanently deleted");
     }
 
     fn delete_current_entry(&mut self) {
+        let deleted = self.entries[self.cursor.row].clone();
+        self.move_to_trash(deleted);
+        let _ = trash::save(&self.data_dir, &self.trash);
+
         if self.entries.len() <= 1 {
             // Don't delete the last entry, just clear it
             self.entries[0] = TimeEntry::new();
@@ -582,10 +4244,140 @@ impl App {
         // Reset cursor column and update mode
         self.cursor.col = 1;
         self.update_mode_for_column();
-        let _ = self.save_entries();
+        let _ = self.save();
+    }
+
+    // The log screen, showing the end of today's log
+    fn open_log(&mut self) {
+        self.commit_field();
+        self.log_view = logging::recent_lines(LOG_VIEW_LINES).map_err(|e| e.to_string());
+        self.log_scroll = 0;
+        self.mode = InputMode::Log;
+    }
+
+    // Figures for telling why Slothtime got slow or a save failed: how much it keeps,
+    // how long saving and drawing take, and the latest problems logged
+    fn open_debug(&mut self) {
+        self.commit_field();
+        let mut view = vec![
+            (
+                "Entries".to_string(),
+                format!(
+                    "{} row{}, {} in use",
+                    self.entries.len(),
+                    if self.entries.len() == 1 { "" } else { "s" },
+                    self.entries
+                        .iter()
+                        .filter(|entry| !entry.is_entirely_empty())
+                        .count()
+                ),
+            ),
+            (
+                "entries.json".to_string(),
+                stats::file_size(&self.data_dir.join("entries.json")),
+            ),
+            (
+                "Archive".to_string(),
+                match archive::dates(&self.data_dir) {
+                    Ok(dates) => format!("{} days", dates.len()),
+                    Err(e) => format!("unknown ({})", e),
+                },
+            ),
+            ("Trash".to_string(), format!("{} entries", self.trash.len())),
+            (
+                "Backups".to_string(),
+                match backup::usage(&self.data_dir) {
+                    Ok((count, bytes)) => format!("{}, {}", count, stats::size(bytes)),
+                    Err(e) => format!("unknown ({})", e),
+                },
+            ),
+            ("Saves".to_string(), self.save_timings.summary()),
+            ("Backup writes".to_string(), self.backup_timings.summary()),
+            (
+                "Backup mirror".to_string(),
+                match &self.mirror_status {
+                    _ if self.config.backup.mirror_path.trim().is_empty() => {
+                        "not set up".to_string()
+                    }
+                    Some(status) => status.clone(),
+                    None => "nothing copied yet".to_string(),
+                },
+            ),
+            ("Redraws".to_string(), self.draw_timings.summary()),
+            (
+                "Failed saves".to_string(),
+                match self.save_failures.last() {
+                    Some((at, e)) => format!(
+                        "{}, last at {}: {}",
+                        self.save_failures.len(),
+                        at.format("%H:%M:%S"),
+                        e
+                    ),
+                    None => "none since the last save".to_string(),
+                },
+            ),
+        ];
+        // The log's latest errors and warnings, newest first
+        let problems: Vec<String> = match logging::recent_lines(LOG_VIEW_LINES) {
+            Ok((_, lines)) => lines
+                .into_iter()
+                .rev()
+                .filter(|line| line.contains(" ERROR ") || line.contains(" WARN "))
+                .take(DEBUG_PROBLEMS)
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        if problems.is_empty() {
+            view.push(("Problems logged".to_string(), "none".to_string()));
+        }
+        for (i, line) in problems.into_iter().enumerate() {
+            let label = if i == 0 { "Problems logged" } else { "" };
+            view.push((label.to_string(), line));
+        }
+        self.debug_view = view;
+        self.mode = InputMode::Debug;
+    }
+
+    fn open_whats_new(&mut self, notes: String) {
+        self.commit_field();
+        self.whats_new = notes;
+        self.whats_new_scroll = 0;
+        self.mode = InputMode::WhatsNew;
+    }
+
+    // Only a newer version is worth interrupting for, a failed check just goes to the log
+    fn check_for_update(&mut self) {
+        let Some(rx) = &self.update_check else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(Some(release))) => {
+                tracing::info!(
+                    "slothtime {} is available at {}",
+                    release.version,
+                    release.page
+                );
+                self.show_message(&format!(
+                    "Slothtime {} is out (this is {}), see {}",
+                    release.version,
+                    changelog::VERSION,
+                    release.page
+                ));
+                self.update_check = None;
+            }
+            Ok(Err(e)) => {
+                tracing::warn!("checking for a newer version failed: {}", e);
+                self.update_check = None;
+            }
+            Ok(Ok(None)) | Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.update_check = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+        }
     }
 
     fn show_message(&mut self, msg: &str) {
+        tracing::debug!("status: {}", msg);
         self.status_message = Some(msg.to_string());
         self.message_timer = Some(std::time::Instant::now());
     }
@@ -600,33 +4392,63 @@ impl App {
     }
 
     fn copy_current_field(&mut self) {
-        if self.cursor.row >= self.entries.len() {
+        let Some(field_name) = TimeEntry::field_name(self.cursor.col) else {
+            self.show_message("Invalid field");
+            return;
+        };
+        let Some(field_content) = self.current_field().cloned() else {
             self.show_message("No entry to copy from");
             return;
-        }
-
-        let entry = &self.entries[self.cursor.row];
-        let (field_content, field_name) = match self.cursor.col {
-            1 => (&entry.task_number, "Task Number"),
-            2 => (&entry.work_code, "Work Code"),
-            3 => (&entry.time_entry, "Time Entry"),
-            4 => (&entry.start_time, "Start Time"),
-            5 => (&entry.end_time, "End Time"),
-            _ => {
-                self.show_message("Invalid field");
-                return;
-            }
         };
 
         if field_content.is_empty() {
             self.show_message(&format!("{} is empty", field_name));
             return;
         }
+        self.set_clipboard(&field_content, field_name);
+    }
+
+    // Ctrl+C and Ctrl+X while editing
+    fn copy_selection(&mut self, cut: bool) {
+        self.sync_editor();
+        let selected = if cut {
+            let mut removed = None;
+            self.edit_field(|editor| removed = editor.cut());
+            removed
+        } else {
+            self.editor.selected_text().map(String::from)
+        };
+        let Some(selected) = selected else {
+            self.show_message("Nothing selected, hold Shift with the arrow keys to select");
+            return;
+        };
+        self.set_clipboard(&selected, "Selection");
+    }
+
+    fn paste_clipboard(&mut self) {
+        match Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+            Ok(text) => self.paste(&text),
+            Err(_) => self.show_message("Could not read the clipboard"),
+        }
+    }
+
+    // The whole day as a Markdown table with totals, for a status email or chat
+    fn copy_day_summary(&mut self) {
+        self.commit_field();
+        if self.entries.iter().all(TimeEntry::is_entirely_empty) {
+            self.show_message("No entries to copy");
+            return;
+        }
+        let now = chrono::Local::now().time();
+        let text = day_summary::markdown(self.day, &self.day_note, &self.entries, now);
+        self.set_clipboard(&text, "The day's entries");
+    }
 
+    fn set_clipboard(&mut self, content: &str, name: &str) {
         match Clipboard::new() {
-            Ok(mut clipboard) => match clipboard.set_text(field_content) {
+            Ok(mut clipboard) => match clipboard.set_text(content) {
                 Ok(()) => {
-                    self.show_message(&format!("{} copied to clipboard!", field_name));
+                    self.show_message(&format!("{} copied to clipboard!", name));
                 }
                 Err(_) => {
                     self.show_message("Failed to copy to clipboard");
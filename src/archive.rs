@@ -0,0 +1,193 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::time_entry::{self, TimeEntry};
+
+// A finished day's entries, stored as archive/YYYY-MM-DD.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Day {
+    pub date: NaiveDate,
+    pub entries: Vec<TimeEntry>,
+    #[serde(default)]
+    pub note: String, // Free text about the day, e.g. "WFH, afternoon at client site"
+}
+
+fn archive_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("archive")
+}
+
+// Today's note lives next to entries.json until the day is archived
+fn note_file(data_dir: &Path) -> PathBuf {
+    data_dir.join("note.txt")
+}
+
+pub fn load_note(data_dir: &Path) -> String {
+    fs::read_to_string(note_file(data_dir))
+        .map(|note| note.trim().to_string())
+        .unwrap_or_default()
+}
+
+pub fn save_note(data_dir: &Path, note: &str) -> Result<()> {
+    fs::write(note_file(data_dir), note)?;
+    Ok(())
+}
+
+// A day is locked once it has been submitted, so its entries aren't changed by accident
+fn lock_file(data_dir: &Path) -> PathBuf {
+    data_dir.join("locked")
+}
+
+pub fn is_locked(data_dir: &Path) -> bool {
+    lock_file(data_dir).exists()
+}
+
+pub fn set_locked(data_dir: &Path, locked: bool) -> Result<()> {
+    let path = lock_file(data_dir);
+    if locked {
+        fs::write(path, chrono::Local::now().to_rfc3339())?;
+    } else if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+// Where the day's note, lock and finished days are kept, for `--follow` to watch
+pub fn day_files(data_dir: &Path) -> Vec<PathBuf> {
+    vec![
+        note_file(data_dir),
+        lock_file(data_dir),
+        archive_dir(data_dir),
+    ]
+}
+
+fn day_file(data_dir: &Path, date: NaiveDate) -> PathBuf {
+    archive_dir(data_dir).join(format!("{}.json", date.format("%Y-%m-%d")))
+}
+
+pub fn save_day(data_dir: &Path, day: &Day) -> Result<()> {
+    fs::create_dir_all(archive_dir(data_dir))?;
+    let content = serde_json::to_string(day)?;
+    fs::write(day_file(data_dir, day.date), content)?;
+    Ok(())
+}
+
+// The archived day on `date`, if it was archived
+pub fn load_day(data_dir: &Path, date: NaiveDate) -> Result<Option<Day>> {
+    let path = day_file(data_dir, date);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut day: Day = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if time_entry::assign_ids(&mut day.entries) {
+        save_day(data_dir, &day)?;
+    }
+    Ok(Some(day))
+}
+
+// The dates of every archived day, oldest first, from the file names alone so a long
+// archive can be read a day at a time as it's needed
+pub fn dates(data_dir: &Path) -> Result<Vec<NaiveDate>> {
+    let dir = archive_dir(data_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut dates = Vec::new();
+    for dir_entry in fs::read_dir(dir)? {
+        let path = dir_entry?.path();
+        let date = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok());
+        if let Some(date) = date {
+            dates.push(date);
+        }
+    }
+    dates.sort();
+    Ok(dates)
+}
+
+// Load every archived day between `from` and `to` (inclusive), oldest first, with the
+// dates whose files couldn't be read. Those are skipped so one damaged file doesn't
+// hide the rest of the range.
+pub fn load_range(
+    data_dir: &Path,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<(Vec<Day>, Vec<NaiveDate>)> {
+    let mut days = Vec::new();
+    let mut unreadable = Vec::new();
+    for date in dates(data_dir)? {
+        if date < from || date > to {
+            continue;
+        }
+        match load_day(data_dir, date) {
+            Ok(day) => days.extend(day),
+            Err(e) => {
+                tracing::warn!("skipping the archived day {}: {:#}", date, e);
+                unreadable.push(date);
+            }
+        }
+    }
+    Ok((days, unreadable))
+}
+
+// What to tell the user about the days load_range skipped
+pub fn unreadable_message(dates: &[NaiveDate]) -> Option<String> {
+    if dates.is_empty() {
+        return None;
+    }
+    let dates: Vec<String> = dates
+        .iter()
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .collect();
+    Some(format!(
+        "Skipped archived day(s) that could not be read: {}",
+        dates.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("slothtime-archive-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 10, day).unwrap()
+    }
+
+    #[test]
+    fn unreadable_days_are_skipped() {
+        let dir = data_dir("range");
+        for day in [1, 2, 5] {
+            let day = Day {
+                date: date(day),
+                entries: vec![TimeEntry::new()],
+                note: String::new(),
+            };
+            save_day(&dir, &day).unwrap();
+        }
+        fs::write(day_file(&dir, date(3)), "{\"date\":\"2025-10-03\",\"entr").unwrap();
+
+        let (days, unreadable) = load_range(&dir, date(2), date(5)).unwrap();
+        let dates: Vec<NaiveDate> = days.iter().map(|day| day.date).collect();
+        assert_eq!(dates, vec![date(2), date(5)]);
+        assert_eq!(unreadable, vec![date(3)]);
+        assert_eq!(
+            unreadable_message(&unreadable).as_deref(),
+            Some("Skipped archived day(s) that could not be read: 2025-10-03")
+        );
+        assert_eq!(unreadable_message(&[]), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+use crate::config::Backup;
+use crate::paths;
+use crate::schema;
+use crate::time_entry::TimeEntry;
+
+fn backup_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("backups")
+}
+
+// Backups oldest first. The timestamped names sort in the order they were written.
+fn list(data_dir: &Path) -> Result<Vec<PathBuf>> {
+    list_in(&backup_dir(data_dir))
+}
+
+fn list_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("entries-"))
+        })
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+fn read(path: &Path) -> Result<String> {
+    let mut content = String::new();
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        GzDecoder::new(fs::File::open(path)?).read_to_string(&mut content)?;
+    } else {
+        content = fs::read_to_string(path)?;
+    }
+    Ok(content)
+}
+
+fn hash(content: &str) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, content.as_bytes());
+    digest
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// How many backups there are and the space they take, for :debug
+pub fn usage(data_dir: &Path) -> Result<(usize, u64)> {
+    let backups = list(data_dir)?;
+    let bytes = backups
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    Ok((backups.len(), bytes))
+}
+
+// The newest backup that can still be read as entries
+pub fn newest_readable(data_dir: &Path) -> Option<(PathBuf, Vec<TimeEntry>)> {
+    list(data_dir).ok()?.into_iter().rev().find_map(|path| {
+        let entries = schema::read_entries(&read(&path).ok()?).ok()?;
+        Some((path, entries))
+    })
+}
+
+// Keep a timestamped copy of the saved entries in backups/, unless it would be the
+// same as the latest one. `latest` holds the hash of the latest backup, read from disk
+// only the first time, so an ordinary save doesn't read the last backup back. True when
+// a new one was written.
+pub fn snapshot(
+    data_dir: &Path,
+    content: &str,
+    settings: &Backup,
+    latest: &mut Option<String>,
+) -> Result<bool> {
+    if !settings.enabled {
+        return Ok(false);
+    }
+    if latest.is_none() {
+        // An unreadable latest backup just means writing a fresh one
+        *latest = list(data_dir)?
+            .last()
+            .and_then(|path| read(path).ok())
+            .map(|latest| hash(&latest));
+    }
+    let hash = hash(content);
+    if latest.as_deref() == Some(hash.as_str()) {
+        return Ok(false);
+    }
+
+    fs::create_dir_all(backup_dir(data_dir))?;
+    let name = chrono::Local::now()
+        .format("entries-%Y%m%d-%H%M%S%.3f")
+        .to_string();
+    if settings.compress {
+        let path = backup_dir(data_dir).join(format!("{}.json.gz", name));
+        let mut encoder = GzEncoder::new(fs::File::create(&path)?, Compression::default());
+        encoder.write_all(content.as_bytes())?;
+        encoder.finish()?;
+    } else {
+        fs::write(backup_dir(data_dir).join(format!("{}.json", name)), content)?;
+    }
+    *latest = Some(hash);
+
+    let backups = list(data_dir)?;
+    if settings.keep > 0 && backups.len() > settings.keep {
+        for old in &backups[..backups.len() - settings.keep] {
+            fs::remove_file(old)?;
+        }
+    }
+    Ok(true)
+}
+
+// Copy the backups missing from backup.mirror_path there on a background thread, so a
+// slow or unreachable network folder never holds up a save. Each run catches up on
+// whatever an earlier failed one left out. The receiver yields how many were copied.
+pub fn mirror_in_background(
+    data_dir: &Path,
+    settings: &Backup,
+) -> Option<Receiver<Result<usize, String>>> {
+    if !settings.enabled || settings.mirror_path.trim().is_empty() {
+        return None;
+    }
+    let (tx, rx) = mpsc::channel();
+    let data_dir = data_dir.to_path_buf();
+    let mirror_dir = paths::expand(settings.mirror_path.trim());
+    let keep = settings.keep;
+    std::thread::spawn(move || {
+        let result = mirror(&data_dir, &mirror_dir, keep).map_err(|e| format!("{:#}", e));
+        let _ = tx.send(result);
+    });
+    Some(rx)
+}
+
+fn mirror(data_dir: &Path, mirror_dir: &Path, keep: usize) -> Result<usize> {
+    fs::create_dir_all(mirror_dir)
+        .with_context(|| format!("can't create {}", mirror_dir.display()))?;
+    let mirrored: Vec<PathBuf> = list_in(mirror_dir)?;
+    let mut copied = 0;
+    for backup in list(data_dir)? {
+        let Some(name) = backup.file_name() else {
+            continue;
+        };
+        let target = mirror_dir.join(name);
+        if mirrored.contains(&target) {
+            continue;
+        }
+        // Written under another name and renamed once complete, so a sync client or a
+        // restore never picks up half a file
+        let partial = mirror_dir.join(format!(".{}.partial", name.to_string_lossy()));
+        fs::copy(&backup, &partial)?;
+        fs::rename(&partial, &target)?;
+        copied += 1;
+    }
+
+    let mirrored = list_in(mirror_dir)?;
+    if keep > 0 && mirrored.len() > keep {
+        for old in &mirrored[..mirrored.len() - keep] {
+            fs::remove_file(old)?;
+        }
+    }
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("slothtime-backup-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn unchanged_entries_are_not_backed_up_again() {
+        let dir = data_dir("unchanged");
+        let settings = Backup::default();
+        let mut latest = None;
+        assert!(snapshot(&dir, "[1]", &settings, &mut latest).unwrap());
+        assert!(!snapshot(&dir, "[1]", &settings, &mut latest).unwrap());
+        // A new App only has the backup on disk to go by
+        assert!(!snapshot(&dir, "[1]", &settings, &mut None).unwrap());
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        assert!(snapshot(&dir, "[2]", &settings, &mut latest).unwrap());
+        assert_eq!(list(&dir).unwrap().len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn old_backups_beyond_keep_are_removed() {
+        let dir = data_dir("keep");
+        let settings = Backup {
+            keep: 2,
+            compress: true,
+            ..Backup::default()
+        };
+        let mut latest = None;
+        for content in ["[1]", "[2]", "[3]"] {
+            snapshot(&dir, content, &settings, &mut latest).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        let backups = list(&dir).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(read(&backups[1]).unwrap(), "[3]");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::HashMap;
+
+use crate::archive::Day;
+use crate::config::Budget;
+use crate::time_entry::TimeEntry;
+
+// Share of a budget at which we start warning
+const WARN_RATIO: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Week,
+    Month,
+}
+
+impl Period {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Period::Week => "week",
+            Period::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BudgetUsage {
+    pub work_code: String,
+    pub period: Period,
+    pub used_minutes: i64,
+    pub budget_minutes: i64,
+}
+
+impl BudgetUsage {
+    pub fn ratio(&self) -> f64 {
+        if self.budget_minutes == 0 {
+            return 0.0;
+        }
+        self.used_minutes as f64 / self.budget_minutes as f64
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.ratio() >= WARN_RATIO
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.used_minutes > self.budget_minutes
+    }
+}
+
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+pub fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap()
+}
+
+// Earliest date any budget period containing `today` can reach back to
+pub fn history_start(today: NaiveDate) -> NaiveDate {
+    week_start(today).min(month_start(today))
+}
+
+// Logged time against every configured budget. Archived days supply history, while
+// `today_entries` stands in for today so unexported edits count too.
+pub fn usage(
+    budgets: &HashMap<String, Budget>,
+    archived: &[Day],
+    today_entries: &[TimeEntry],
+    today: NaiveDate,
+) -> Vec<BudgetUsage> {
+    let mut usages = Vec::new();
+    let mut codes: Vec<&String> = budgets.keys().collect();
+    codes.sort();
+
+    for code in codes {
+        let budget = &budgets[code];
+        let periods = [
+            (Period::Week, budget.weekly_hours, week_start(today)),
+            (Period::Month, budget.monthly_hours, month_start(today)),
+        ];
+        for (period, hours, start) in periods {
+            let Some(hours) = hours else {
+                continue;
+            };
+            let archived_minutes: i64 = archived
+                .iter()
+                .filter(|day| day.date >= start && day.date < today)
+                .map(|day| minutes_for_code(&day.entries, code))
+                .sum();
+            usages.push(BudgetUsage {
+                work_code: code.clone(),
+                period,
+                used_minutes: archived_minutes + minutes_for_code(today_entries, code),
+                budget_minutes: (hours * 60.0).round() as i64,
+            });
+        }
+    }
+    usages
+}
+
+// Minutes logged to `code` today and in the week so far, counted the same way as budgets
+pub fn code_minutes(
+    archived: &[Day],
+    today_entries: &[TimeEntry],
+    today: NaiveDate,
+    code: &str,
+) -> (i64, i64) {
+    let today_minutes = minutes_for_code(today_entries, code);
+    let earlier_minutes: i64 = archived
+        .iter()
+        .filter(|day| day.date >= week_start(today) && day.date < today)
+        .map(|day| minutes_for_code(&day.entries, code))
+        .sum();
+    (today_minutes, today_minutes + earlier_minutes)
+}
+
+fn minutes_for_code(entries: &[TimeEntry], code: &str) -> i64 {
+    entries
+        .iter()
+        .filter(|e| e.work_code == code)
+        .filter_map(|e| e.duration_minutes())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 10, day).unwrap()
+    }
+
+    fn day(day: u32, entries: Vec<TimeEntry>) -> Day {
+        Day {
+            date: date(day),
+            entries,
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn periods_start_on_monday_and_the_first() {
+        // 2025-10-01 is a Wednesday
+        assert_eq!(
+            week_start(date(1)),
+            NaiveDate::from_ymd_opt(2025, 9, 29).unwrap()
+        );
+        assert_eq!(week_start(date(6)), date(6));
+        assert_eq!(month_start(date(15)), date(1));
+        assert_eq!(
+            history_start(date(1)),
+            NaiveDate::from_ymd_opt(2025, 9, 29).unwrap()
+        );
+        assert_eq!(history_start(date(15)), date(1));
+    }
+
+    #[test]
+    fn usage_counts_archived_days_and_today() {
+        let budgets = HashMap::from([(
+            "DEV".to_string(),
+            Budget {
+                weekly_hours: Some(4.0),
+                monthly_hours: Some(10.0),
+            },
+        )]);
+        let archived = vec![
+            day(3, vec![entry("", "DEV", "09:00", "10:00")]), // Last week
+            day(
+                6,
+                vec![
+                    entry("", "DEV", "09:00", "11:00"),
+                    entry("", "OPS", "11:00", "12:00"),
+                ],
+            ),
+            day(8, vec![entry("", "DEV", "09:00", "10:00")]), // Today, counted from the sheet instead
+        ];
+        let today = vec![entry("", "DEV", "13:00", "14:30")];
+        let usages = usage(&budgets, &archived, &today, date(8));
+
+        assert_eq!(usages.len(), 2);
+        assert_eq!(
+            (usages[0].period, usages[0].used_minutes),
+            (Period::Week, 210)
+        );
+        assert!(usages[0].is_warning() && !usages[0].is_exceeded());
+        assert_eq!(
+            (usages[1].period, usages[1].used_minutes),
+            (Period::Month, 270)
+        );
+        assert!(!usages[1].is_warning());
+    }
+
+    #[test]
+    fn code_minutes_are_today_and_the_week_so_far() {
+        let archived = vec![day(6, vec![entry("", "DEV", "09:00", "10:00")])];
+        let today = vec![
+            entry("", "DEV", "09:00", "09:30"),
+            entry("", "DEV", "10:00", ""),
+        ];
+        assert_eq!(code_minutes(&archived, &today, date(8), "DEV"), (30, 90));
+        assert_eq!(code_minutes(&archived, &today, date(8), "OPS"), (0, 0));
+    }
+}
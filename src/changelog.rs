@@ -0,0 +1,148 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use crate::config;
+
+const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// "1.10.2" as numbers, so it sorts after "1.9.0"
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+// The version a `## ` heading is for. `## Unreleased` holds the notes of this build,
+// which get their own version number when it's released.
+fn heading_version(heading: &str) -> Option<Vec<u64>> {
+    if heading.trim().eq_ignore_ascii_case("unreleased") {
+        parse_version(VERSION)
+    } else {
+        parse_version(heading)
+    }
+}
+
+// The `## <version>` sections whose version `include` picks, without the title and
+// introduction above them
+fn sections(include: impl Fn(&[u64]) -> bool) -> String {
+    let mut out = Vec::new();
+    let mut included = false;
+    for line in CHANGELOG.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            included = heading_version(heading).is_some_and(|version| include(&version));
+        }
+        if included {
+            out.push(line);
+        }
+    }
+    out.join("\n").trim().to_string()
+}
+
+// Every version's notes, for :changelog
+pub fn all() -> String {
+    sections(|_| true)
+}
+
+// Shared by every project, so an upgrade is announced once
+fn seen_file() -> PathBuf {
+    config::data_dir().join("last_version")
+}
+
+// What's new since the version Slothtime last ran as. A fresh install has nothing to
+// catch up on, but someone who used Slothtime before versions were recorded sees this
+// version's notes. They keep coming back until `mark_seen`.
+pub fn unseen() -> Option<String> {
+    let seen = fs::read_to_string(seen_file()).ok();
+    let current = parse_version(VERSION)?;
+    let notes = match seen.as_deref().map(parse_version) {
+        Some(Some(seen)) => {
+            sections(|version| version > seen.as_slice() && version <= current.as_slice())
+        }
+        Some(None) => return None,
+        None if config::data_dir().join("entries.json").exists() => {
+            sections(|version| version == current.as_slice())
+        }
+        None => return None,
+    };
+    (!notes.is_empty()).then_some(notes)
+}
+
+// Record this version once its notes have been shown, or there were none to show
+pub fn mark_seen() {
+    let file = seen_file();
+    if fs::read_to_string(&file).ok().as_deref().map(str::trim) == Some(VERSION) {
+        return;
+    }
+    if let Err(e) = fs::write(&file, VERSION) {
+        tracing::warn!("could not record the version in {}: {}", file.display(), e);
+    }
+}
+
+// A release newer than this version
+pub struct Release {
+    pub version: String,
+    pub page: String,
+}
+
+// Ask `url` (a GitHub latest release endpoint) for the newest version on a background
+// thread. The receiver yields the version and its page when it's newer than this one.
+pub fn check_latest(url: &str) -> Receiver<Result<Option<Release>, String>> {
+    let (tx, rx) = mpsc::channel();
+    let url = url.to_string();
+    std::thread::spawn(move || {
+        let result = latest(&url).map_err(|e| format!("{:#}", e));
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+fn latest(url: &str) -> Result<Option<Release>> {
+    let release: serde_json::Value = ureq::get(url)
+        .timeout(Duration::from_secs(10))
+        .set("Accept", "application/vnd.github+json")
+        .call()?
+        .into_json()?;
+    let tag = release["tag_name"]
+        .as_str()
+        .ok_or_else(|| anyhow!("the release has no tag_name"))?;
+    let page = release["html_url"].as_str().unwrap_or(url).to_string();
+    let newer = parse_version(tag).is_some_and(|latest| Some(latest) > parse_version(VERSION));
+    Ok(newer.then(|| Release {
+        version: tag.trim_start_matches('v').to_string(),
+        page,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_compare_as_numbers() {
+        assert_eq!(parse_version("v1.10.2"), Some(vec![1, 10, 2]));
+        assert!(parse_version("1.10.0") > parse_version("1.9.0"));
+        assert_eq!(parse_version("1.0.0-beta"), None);
+        assert_eq!(parse_version("Unreleased"), None);
+    }
+
+    #[test]
+    fn unreleased_notes_belong_to_this_build() {
+        assert_eq!(heading_version("Unreleased"), parse_version(VERSION));
+        assert_eq!(heading_version("0.3.1"), Some(vec![0, 3, 1]));
+        assert_eq!(heading_version("Someday"), None);
+
+        let current = parse_version(VERSION).unwrap();
+        let notes = sections(|version| version == current.as_slice());
+        assert!(notes.starts_with("## Unreleased"));
+        assert!(!notes.contains("# Changelog"));
+        assert!(sections(|version| version > current.as_slice()).is_empty());
+        assert_eq!(all(), notes);
+    }
+}
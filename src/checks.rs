@@ -0,0 +1,123 @@
+use crate::config::Checks;
+use crate::time_entry::{format_duration, TimeEntry};
+
+// Durations that are valid but probably typos, like 19:00 typed for 09:00
+pub fn warnings(entry: &TimeEntry, checks: &Checks) -> Vec<String> {
+    let mut warnings: Vec<String> = [(4, &entry.start_time), (5, &entry.end_time)]
+        .into_iter()
+        .filter_map(|(col, time)| outside_hours(col, time, checks))
+        .collect();
+    let start = TimeEntry::parse_time(&entry.start_time);
+    let end = TimeEntry::parse_time(&entry.end_time);
+    let (Some(start), Some(end)) = (start, end) else {
+        return warnings;
+    };
+
+    if end < start {
+        if checks.end_before_start {
+            warnings.push("End Time is before Start Time".to_string());
+        }
+        return warnings;
+    }
+    let minutes = (end - start).num_minutes();
+    if minutes == 0 && checks.zero_length {
+        warnings.push("Entry is zero minutes long".to_string());
+    }
+    if checks.max_hours > 0.0 && minutes as f64 > checks.max_hours * 60.0 {
+        warnings.push(format!(
+            "{} is longer than {}h",
+            format_duration(minutes),
+            checks.max_hours
+        ));
+    }
+    warnings
+}
+
+// A time before checks.earliest or after checks.latest, like 21:00 typed for 09:00.
+// `col` names the Start or End Time in the warning.
+pub fn outside_hours(col: usize, time: &str, checks: &Checks) -> Option<String> {
+    let time = TimeEntry::parse_time(time)?;
+    let name = TimeEntry::field_name(col).unwrap_or("Time");
+    let earliest = TimeEntry::parse_time(&checks.earliest).filter(|&earliest| time < earliest);
+    let latest = TimeEntry::parse_time(&checks.latest).filter(|&latest| time > latest);
+    match (earliest, latest) {
+        (Some(earliest), _) => Some(format!(
+            "{} {} is before {}",
+            name,
+            time.format("%H:%M"),
+            earliest.format("%H:%M")
+        )),
+        (None, Some(latest)) => Some(format!(
+            "{} {} is after {}",
+            name,
+            time.format("%H:%M"),
+            latest.format("%H:%M")
+        )),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    #[test]
+    fn suspicious_durations_are_flagged() {
+        let checks = Checks::default();
+        assert!(warnings(&entry("", "", "09:00", "17:00"), &checks).is_empty());
+        assert!(warnings(&entry("", "", "09:00", ""), &checks).is_empty());
+        assert_eq!(
+            warnings(&entry("", "", "09:00", "19:00"), &checks),
+            vec![format!("{} is longer than 8h", format_duration(600))]
+        );
+        assert_eq!(
+            warnings(&entry("", "", "09:00", "09:00"), &checks),
+            vec!["Entry is zero minutes long"]
+        );
+        assert_eq!(
+            warnings(&entry("", "", "17:00", "09:00"), &checks),
+            vec!["End Time is before Start Time"]
+        );
+    }
+
+    #[test]
+    fn disabled_checks_stay_quiet() {
+        let checks = Checks {
+            max_hours: 0.0,
+            zero_length: false,
+            end_before_start: false,
+            ..Checks::default()
+        };
+        for (start, end) in [("09:00", "23:00"), ("09:00", "09:00"), ("17:00", "09:00")] {
+            assert!(warnings(&entry("", "", start, end), &checks).is_empty());
+        }
+    }
+
+    #[test]
+    fn times_outside_working_hours_are_named() {
+        let checks = Checks {
+            earliest: "07:00".to_string(),
+            latest: "19:00".to_string(),
+            ..Checks::default()
+        };
+        assert_eq!(
+            outside_hours(4, "0630", &checks).as_deref(),
+            Some("Start Time 06:30 is before 07:00")
+        );
+        assert_eq!(
+            outside_hours(5, "21:00", &checks).as_deref(),
+            Some("End Time 21:00 is after 19:00")
+        );
+        assert_eq!(outside_hours(5, "19:00", &checks), None);
+        assert_eq!(outside_hours(4, "", &checks), None);
+        assert_eq!(outside_hours(4, "06:30", &Checks::default()), None);
+        assert_eq!(
+            warnings(&entry("", "", "06:00", "20:00"), &checks)[..2],
+            [
+                "Start Time 06:00 is before 07:00".to_string(),
+                "End Time 20:00 is after 19:00".to_string()
+            ]
+        );
+    }
+}
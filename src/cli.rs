@@ -0,0 +1,112 @@
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use clap_complete::Shell;
+
+use crate::config::{ExportFormat, InvoiceFormat};
+use crate::plaintext::PlainFormat;
+
+#[derive(Debug, Parser)]
+#[command(name = "slothtime", version, about = "Terminal time tracking")]
+pub struct Cli {
+    /// Use a named project with its own entries and config overlay
+    #[arg(long, global = true)]
+    pub project: Option<String>,
+
+    /// Try Slothtime on sample data with tutorial hints, leaving your own data alone
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Show a live, read-only mirror of the entries another Slothtime is editing
+    #[arg(long, conflicts_with = "demo")]
+    pub follow: bool,
+
+    /// Open an archived day (YYYY-MM-DD) to correct it, saving it back to the archive
+    #[arg(long, conflicts_with_all = ["demo", "follow"])]
+    pub date: Option<NaiveDate>,
+
+    /// Also log debug details to ~/.slothtime/logs, like every status bar message
+    #[arg(long, global = true)]
+    pub verbose: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List the named projects
+    Projects,
+    /// Print hours per task number for a date range
+    Report {
+        /// First day to include (YYYY-MM-DD), defaults to the start of this month
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day to include (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Print CSV instead of a table
+        #[arg(long)]
+        csv: bool,
+        /// Print the hours logged in each hour of the day instead, to see when you work
+        #[arg(long)]
+        by_hour: bool,
+    },
+    /// Export every entry in a date range to one file
+    Export {
+        /// First day to include (YYYY-MM-DD), defaults to the start of this month
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day to include (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Export hours per task and day as a timesheet grid, this week by default
+        #[arg(long)]
+        grid: bool,
+        /// File type, defaults to export.format from the config
+        #[arg(long, value_enum)]
+        format: Option<ExportFormat>,
+        /// Write the entries for timewarrior (`timew import`) or timeclock/hledger instead
+        #[arg(long, value_enum, conflicts_with_all = ["grid", "format"])]
+        plaintext: Option<PlainFormat>,
+        /// Also email the file to email.to from the config
+        #[arg(long)]
+        email: bool,
+    },
+    /// Write an invoice for a date range, billing each task's hours at [invoice] rates
+    Invoice {
+        /// First day to bill (YYYY-MM-DD), defaults to the start of this month
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day to bill (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Invoice number, defaults to the year and month of the last day, e.g. 2025-09
+        #[arg(long)]
+        number: Option<String>,
+        /// File type, defaults to invoice.format from the config
+        #[arg(long, value_enum)]
+        format: Option<InvoiceFormat>,
+        /// Replace an invoice already written with the same number
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check entries for anything an export would warn about, without writing a file
+    Validate {
+        /// First day to check (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day to check (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
+    /// Serve Prometheus metrics about today's entries at /metrics
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:9184")]
+        addr: String,
+    },
+    /// Print a shell completion script
+    Completions { shell: Shell },
+    /// Print the man page in roff format
+    Man,
+}
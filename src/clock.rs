@@ -0,0 +1,87 @@
+use chrono::{DateTime, Duration, Local};
+use std::time::Instant;
+
+// Differences between the wall clock and the monotonic clock smaller than this are
+// scheduling noise, not a jump
+const JUMP_THRESHOLD_SECS: i64 = 90;
+
+// The wall clock moving by more than the time that actually passed: a suspend
+// (the monotonic clock stops while asleep on Linux and macOS), a manual change or
+// an NTP correction
+#[derive(Debug, Clone, Copy)]
+pub struct ClockJump {
+    pub before: DateTime<Local>, // Wall clock time last seen before the jump
+    pub after: DateTime<Local>,
+    pub skipped: Duration, // Wall clock time that passed without running time passing, negative when it went back
+}
+
+// Compares the wall clock with the monotonic clock on every tick of the run loop
+#[derive(Debug)]
+pub struct ClockWatch {
+    wall: DateTime<Local>,
+    monotonic: Instant,
+}
+
+impl ClockWatch {
+    pub fn new() -> Self {
+        Self {
+            wall: Local::now(),
+            monotonic: Instant::now(),
+        }
+    }
+
+    pub fn check(&mut self) -> Option<ClockJump> {
+        self.observe(Local::now(), Instant::now())
+    }
+
+    // Compare a reading of both clocks with the last one
+    fn observe(&mut self, wall: DateTime<Local>, monotonic: Instant) -> Option<ClockJump> {
+        let elapsed =
+            Duration::from_std(monotonic - self.monotonic).unwrap_or_else(|_| Duration::zero());
+        let skipped = (wall - self.wall) - elapsed;
+        let jump = (skipped.num_seconds().abs() >= JUMP_THRESHOLD_SECS).then_some(ClockJump {
+            before: self.wall,
+            after: wall,
+            skipped,
+        });
+        self.wall = wall;
+        self.monotonic = monotonic;
+        jump
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jumps_are_the_wall_clock_outrunning_the_monotonic_one() {
+        let mut watch = ClockWatch::new();
+        let (wall, monotonic) = (watch.wall, watch.monotonic);
+        let after_sleep = wall + Duration::hours(2);
+        let jump = watch
+            .observe(after_sleep, monotonic + std::time::Duration::from_secs(60))
+            .unwrap();
+        assert_eq!((jump.before, jump.after), (wall, after_sleep));
+        assert_eq!(jump.skipped, Duration::minutes(119));
+
+        // Later readings compare with the last one, not the first
+        let later = monotonic + std::time::Duration::from_secs(120);
+        assert!(watch
+            .observe(after_sleep + Duration::seconds(89), later)
+            .is_none());
+    }
+
+    #[test]
+    fn going_back_is_a_negative_jump() {
+        let mut watch = ClockWatch::new();
+        let (wall, monotonic) = (watch.wall, watch.monotonic);
+        let jump = watch
+            .observe(
+                wall - Duration::minutes(5),
+                monotonic + std::time::Duration::from_secs(1),
+            )
+            .unwrap();
+        assert_eq!(jump.skipped, Duration::seconds(-301));
+    }
+}
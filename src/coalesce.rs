@@ -0,0 +1,156 @@
+use std::ops::Range;
+
+use crate::time_entry::TimeEntry;
+
+// A run of consecutive rows on the same task that cleanup folds into one entry
+#[derive(Debug, Clone)]
+pub struct Merge {
+    pub rows: Range<usize>,
+    pub entry: TimeEntry,
+}
+
+// Find entries shorter than `min_minutes` sitting next to an entry on the same task
+// number, and fold each such run into a single entry from its first start to its last
+// end. Rows separated by a gap longer than `min_minutes` are left alone so a merge
+// never bills much time that wasn't tracked.
+pub fn plan(entries: &[TimeEntry], min_minutes: i64) -> Vec<Merge> {
+    let mut merges = Vec::new();
+    let mut start = 0;
+    while start < entries.len() {
+        let mut merged = entries[start].clone();
+        let mut end = start + 1;
+        while end < entries.len() && can_join(&merged, &entries[end], min_minutes) {
+            merged = join(merged, &entries[end]);
+            end += 1;
+        }
+        if end - start > 1 {
+            merges.push(Merge {
+                rows: start..end,
+                entry: merged,
+            });
+        }
+        start = end;
+    }
+    merges
+}
+
+// Replace each run with its merged entry, handing back the original rows
+pub fn apply(entries: &mut Vec<TimeEntry>, merges: &[Merge]) -> Vec<TimeEntry> {
+    let mut replaced = Vec::new();
+    // From the bottom up so the row ranges of earlier merges stay valid
+    for merge in merges.iter().rev() {
+        replaced.extend(entries.splice(merge.rows.clone(), [merge.entry.clone()]));
+    }
+    replaced
+}
+
+fn can_join(a: &TimeEntry, b: &TimeEntry, min_minutes: i64) -> bool {
+    let task = a.task_number.trim();
+    if task.is_empty() || task != b.task_number.trim() {
+        return false;
+    }
+    let (Some(a_minutes), Some(b_minutes)) = (a.duration_minutes(), b.duration_minutes()) else {
+        return false;
+    };
+    let (Some(a_end), Some(b_start)) = (
+        TimeEntry::parse_time(&a.end_time),
+        TimeEntry::parse_time(&b.start_time),
+    ) else {
+        return false;
+    };
+    let gap = (b_start - a_end).num_minutes();
+    (a_minutes < min_minutes || b_minutes < min_minutes) && (0..=min_minutes).contains(&gap)
+}
+
+// `b` follows `a`. The longer entry's work code is kept and the descriptions are joined,
+// unless `b`'s is already one of those joined into `a`.
+fn join(a: TimeEntry, b: &TimeEntry) -> TimeEntry {
+    let a_longer = a.duration_minutes() >= b.duration_minutes();
+    let description = match (a.time_entry.trim(), b.time_entry.trim()) {
+        (_, "") => a.time_entry.clone(),
+        ("", _) => b.time_entry.clone(),
+        (first, second) if first.split("; ").any(|part| part == second) => a.time_entry.clone(),
+        (first, second) => format!("{}; {}", first, second),
+    };
+    TimeEntry {
+        work_code: if a_longer || b.work_code.trim().is_empty() {
+            a.work_code.clone()
+        } else {
+            b.work_code.clone()
+        },
+        time_entry: description,
+        end_time: b.end_time.clone(),
+        checked: a.checked || b.checked,
+        ..a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    #[test]
+    fn short_entries_on_a_task_are_folded_together() {
+        let entries = vec![
+            entry("PROJ-1", "DEV", "09:00", "09:05").described("Review"),
+            entry("PROJ-1", "DEV", "09:05", "10:00").described("Fix"),
+            entry("PROJ-2", "DEV", "10:00", "10:03").described("Meeting"),
+        ];
+        let merges = plan(&entries, 10);
+        assert_eq!(merges.len(), 1);
+        assert_eq!(merges[0].rows, 0..2);
+        assert_eq!(merges[0].entry.time_entry, "Review; Fix");
+        assert_eq!(merges[0].entry.start_time, "09:00");
+        assert_eq!(merges[0].entry.end_time, "10:00");
+    }
+
+    #[test]
+    fn long_gaps_and_long_entries_are_left_alone() {
+        let gap = vec![
+            entry("PROJ-1", "DEV", "09:00", "09:05").described("A"),
+            entry("PROJ-1", "DEV", "09:30", "09:35").described("B"),
+        ];
+        assert!(plan(&gap, 10).is_empty());
+        let long = vec![
+            entry("PROJ-1", "DEV", "09:00", "10:00").described("A"),
+            entry("PROJ-1", "DEV", "10:00", "11:00").described("B"),
+        ];
+        assert!(plan(&long, 10).is_empty());
+    }
+
+    #[test]
+    fn repeated_descriptions_are_kept_once() {
+        let entries = vec![
+            entry("PROJ-1", "DEV", "09:00", "09:05").described("Review; Fix"),
+            entry("PROJ-1", "DEV", "09:05", "09:08").described("Fix"),
+        ];
+        assert_eq!(plan(&entries, 10)[0].entry.time_entry, "Review; Fix");
+    }
+
+    #[test]
+    fn part_of_a_description_is_still_joined() {
+        let entries = vec![
+            entry("PROJ-1", "DEV", "09:00", "09:05").described("Code review"),
+            entry("PROJ-1", "DEV", "09:05", "09:08").described("review"),
+        ];
+        assert_eq!(
+            plan(&entries, 10)[0].entry.time_entry,
+            "Code review; review"
+        );
+    }
+
+    #[test]
+    fn apply_replaces_runs_and_returns_the_originals() {
+        let mut entries = vec![
+            entry("PROJ-1", "DEV", "09:00", "09:05").described("A"),
+            entry("PROJ-1", "DEV", "09:05", "09:08").described("B"),
+            entry("PROJ-2", "DEV", "09:08", "10:00").described("C"),
+        ];
+        let merges = plan(&entries, 10);
+        let replaced = apply(&mut entries, &merges);
+        assert_eq!(replaced.len(), 2);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].task_number, "PROJ-2");
+    }
+}
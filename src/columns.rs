@@ -0,0 +1,343 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use crate::config::{ColumnRules, Columns};
+use crate::paths;
+use crate::time_entry::TimeEntry;
+
+// One column's rules with the pattern compiled and the allowlist loaded
+struct ColumnCheck {
+    col: usize,
+    name: &'static str,
+    rules: ColumnRules,
+    pattern: Option<Regex>,
+    allowlist: Option<HashSet<String>>,
+}
+
+// The content fetched for each column's allowlist URL, or why it couldn't be
+pub type FetchedAllowlists = Vec<(usize, Result<String, String>)>;
+
+// The [columns] rules, prepared once at startup
+pub struct InputRules {
+    columns: Vec<ColumnCheck>,
+    cache_dir: PathBuf, // The last copy of each URL allowlist, used until it's fetched again
+    pub load_errors: Vec<String>, // Allowlists that couldn't be read, their values go unchecked
+}
+
+impl InputRules {
+    // Allowlist files are read straight away, URLs start from the copy fetched last time
+    // and are only fetched by `fetch_in_background` or `fetch_now`
+    pub fn new(config: &Columns, data_dir: &Path) -> Result<Self> {
+        let cache_dir = data_dir.join("allowlists");
+        let columns = [
+            (1, "Task Number", "task_number", &config.task_number),
+            (2, "Work Code", "work_code", &config.work_code),
+            (3, "Time Entry", "time_entry", &config.time_entry),
+        ];
+        let mut checks = Vec::new();
+        let mut load_errors = Vec::new();
+        for (col, name, key, rules) in columns {
+            let pattern = if rules.pattern.is_empty() {
+                None
+            } else {
+                let regex = Regex::new(&rules.pattern)
+                    .with_context(|| format!("invalid pattern in [columns.{}]", key))?;
+                Some(regex)
+            };
+            let allowlist = if rules.allowlist.is_empty() {
+                None
+            } else if is_url(&rules.allowlist) {
+                fs::read_to_string(cached_copy(&cache_dir, &rules.allowlist))
+                    .ok()
+                    .and_then(|content| parse_allowlist(&content).ok())
+            } else {
+                match fs::read_to_string(paths::expand(&rules.allowlist))
+                    .map_err(anyhow::Error::from)
+                    .and_then(|content| parse_allowlist(&content))
+                {
+                    Ok(values) => Some(values),
+                    Err(e) => {
+                        load_errors.push(format!("{} allowlist: {}", name, e));
+                        None
+                    }
+                }
+            };
+            checks.push(ColumnCheck {
+                col,
+                name,
+                rules: rules.clone(),
+                pattern,
+                allowlist,
+            });
+        }
+        Ok(Self {
+            columns: checks,
+            cache_dir,
+            load_errors,
+        })
+    }
+
+    // Fetch the URL allowlists on a background thread so a slow server doesn't hold up
+    // starting. None when no column has one.
+    pub fn fetch_in_background(&self) -> Option<Receiver<FetchedAllowlists>> {
+        let urls = self.urls();
+        if urls.is_empty() {
+            return None;
+        }
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let fetched = urls
+                .into_iter()
+                .map(|(col, url)| (col, fetch(&url).map_err(|e| format!("{:#}", e))))
+                .collect();
+            let _ = tx.send(fetched);
+        });
+        Some(rx)
+    }
+
+    // Fetch the URL allowlists and wait for them, for the command line
+    pub fn fetch_now(&mut self) -> Vec<String> {
+        let fetched = self
+            .urls()
+            .into_iter()
+            .map(|(col, url)| (col, fetch(&url).map_err(|e| format!("{:#}", e))))
+            .collect();
+        self.use_fetched(fetched)
+    }
+
+    // Check against the fetched lists from now on and keep a copy of each for the next
+    // start. A list that couldn't be fetched stays as it was, and is reported.
+    pub fn use_fetched(&mut self, fetched: FetchedAllowlists) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (col, content) in fetched {
+            let Some(check) = self.columns.iter_mut().find(|c| c.col == col) else {
+                continue;
+            };
+            let fallback = if check.allowlist.is_some() {
+                ", using the last copy"
+            } else {
+                ""
+            };
+            let values = content.and_then(|content| {
+                let values = parse_allowlist(&content).map_err(|e| e.to_string())?;
+                let cached = cached_copy(&self.cache_dir, &check.rules.allowlist);
+                if let Err(e) =
+                    fs::create_dir_all(&self.cache_dir).and_then(|_| fs::write(&cached, &content))
+                {
+                    tracing::warn!(
+                        "could not keep a copy of the allowlist in {}: {}",
+                        cached.display(),
+                        e
+                    );
+                }
+                Ok(values)
+            });
+            match values {
+                Ok(values) => check.allowlist = Some(values),
+                Err(e) => errors.push(format!("{} allowlist: {}{}", check.name, e, fallback)),
+            }
+        }
+        errors
+    }
+
+    fn urls(&self) -> Vec<(usize, String)> {
+        self.columns
+            .iter()
+            .filter(|check| is_url(&check.rules.allowlist))
+            .map(|check| (check.col, check.rules.allowlist.clone()))
+            .collect()
+    }
+
+    // Uppercase and prefix a value typed into column `col`
+    pub fn apply(&self, col: usize, value: String) -> String {
+        let Some(check) = self.columns.iter().find(|c| c.col == col) else {
+            return value;
+        };
+        let rules = &check.rules;
+        let mut value = if rules.uppercase {
+            value.to_uppercase()
+        } else {
+            value
+        };
+        if !value.is_empty() && !value.starts_with(&rules.prefix) {
+            value.insert_str(0, &rules.prefix);
+        }
+        if let Some((max, true)) = self.max_length(col) {
+            if let Some((cut, _)) = value.char_indices().nth(max) {
+                value.truncate(cut);
+            }
+        }
+        value
+    }
+
+    // The most characters column `col` takes, and whether typing more is refused
+    pub fn max_length(&self, col: usize) -> Option<(usize, bool)> {
+        let check = self.columns.iter().find(|c| c.col == col)?;
+        let rules = &check.rules;
+        (rules.max_length > 0).then_some((rules.max_length, rules.hard_limit))
+    }
+
+    // Filled in columns that don't match their pattern or aren't on their allowlist
+    pub fn problems(&self, entry: &TimeEntry) -> Vec<String> {
+        let mut problems = Vec::new();
+        for check in &self.columns {
+            let Some(value) = entry.field(check.col).filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            if let Some(pattern) = &check.pattern {
+                if !pattern.is_match(value) {
+                    problems.push(format!(
+                        "{} doesn't match {}",
+                        check.name, check.rules.pattern
+                    ));
+                }
+            }
+            let length = value.chars().count();
+            if check.rules.max_length > 0 && length > check.rules.max_length {
+                problems.push(format!(
+                    "{} is {} characters, the limit is {}",
+                    check.name, length, check.rules.max_length
+                ));
+            }
+            if let Some(allowlist) = &check.allowlist {
+                if !allowlist.contains(value.as_str()) {
+                    problems.push(format!("{} {} is not on the allowlist", check.name, value));
+                }
+            }
+        }
+        problems
+    }
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+fn fetch(url: &str) -> Result<String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(5))
+        .build();
+    Ok(agent.get(url).call()?.into_string()?)
+}
+
+// Where the last copy fetched from `url` is kept, named by a hash of the URL
+fn cached_copy(cache_dir: &Path, url: &str) -> PathBuf {
+    let hash = ring::digest::digest(&ring::digest::SHA256, url.as_bytes());
+    let name: String = hash.as_ref()[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    cache_dir.join(format!("{}.txt", name))
+}
+
+// Valid values, one per line with `#` starting a comment, or a JSON array of strings
+fn parse_allowlist(content: &str) -> Result<HashSet<String>> {
+    if content.trim_start().starts_with('[') {
+        let values: Vec<String> = serde_json::from_str(content)?;
+        return Ok(values.into_iter().map(|v| v.trim().to_string()).collect());
+    }
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ColumnRules;
+    use crate::time_entry::entry;
+
+    const URL: &str = "https://example.com/codes.txt";
+
+    fn data_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("slothtime-columns-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn url_rules() -> Columns {
+        Columns {
+            work_code: ColumnRules {
+                allowlist: URL.to_string(),
+                ..ColumnRules::default()
+            },
+            ..Columns::default()
+        }
+    }
+
+    #[test]
+    fn allowlists_are_lines_or_json() {
+        let lines = parse_allowlist("# codes\nDEV\n\n  QA  \n").unwrap();
+        assert_eq!(lines, HashSet::from(["DEV".to_string(), "QA".to_string()]));
+        let json = parse_allowlist(r#"[" DEV", "QA"]"#).unwrap();
+        assert_eq!(json, lines);
+        assert!(parse_allowlist("[DEV").is_err());
+    }
+
+    #[test]
+    fn fetched_allowlists_are_kept_for_the_next_start() {
+        let dir = data_dir("kept");
+        let mut rules = InputRules::new(&url_rules(), &dir).unwrap();
+        assert!(rules.problems(&entry("", "XYZ", "", "")).is_empty());
+
+        assert!(rules
+            .use_fetched(vec![(2, Ok("DEV\n".to_string()))])
+            .is_empty());
+        assert_eq!(
+            rules.problems(&entry("", "XYZ", "", "")),
+            ["Work Code XYZ is not on the allowlist"]
+        );
+
+        let mut restarted = InputRules::new(&url_rules(), &dir).unwrap();
+        assert_eq!(restarted.problems(&entry("", "XYZ", "", "")).len(), 1);
+        let errors = restarted.use_fetched(vec![(2, Err("timed out".to_string()))]);
+        assert_eq!(
+            errors,
+            ["Work Code allowlist: timed out, using the last copy"]
+        );
+        assert!(restarted.problems(&entry("", "DEV", "", "")).is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn patterns_and_lengths_are_checked() {
+        let config = Columns {
+            task_number: ColumnRules {
+                uppercase: true,
+                prefix: "PROJ-".to_string(),
+                pattern: r"^PROJ-\d+$".to_string(),
+                max_length: 8,
+                ..ColumnRules::default()
+            },
+            ..Columns::default()
+        };
+        let rules = InputRules::new(&config, &data_dir("patterns")).unwrap();
+        assert_eq!(rules.apply(1, "12".to_string()), "PROJ-12");
+        assert_eq!(rules.apply(1, "proj-7".to_string()), "PROJ-7");
+        let long = entry("PROJ-12x", "", "", "");
+        assert_eq!(
+            rules.problems(&long),
+            [r"Task Number doesn't match ^PROJ-\d+$"]
+        );
+        assert!(InputRules::new(
+            &Columns {
+                work_code: ColumnRules {
+                    pattern: "(".to_string(),
+                    ..ColumnRules::default()
+                },
+                ..Columns::default()
+            },
+            &data_dir("invalid"),
+        )
+        .is_err());
+    }
+}
@@ -1,19 +1,470 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::schema;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub file: PathBuf,
+    #[serde(skip)]
+    pub profile: Option<String>, // From SLOTHTIME_PROFILE, merged over the file
+    #[serde(default = "default_version")]
+    pub version: u32, // Format version, see schema.rs
     pub export: Export,
     pub ui: Ui,
+    #[serde(default)]
+    pub import: Import,
+    #[serde(default)]
+    pub activitywatch: ActivityWatch,
+    #[serde(default)]
+    pub budgets: HashMap<String, Budget>,
+    #[serde(default)]
+    pub hooks: Hooks,
+    #[serde(default)]
+    pub email: Email,
+    #[serde(default)]
+    pub upload: Upload,
+    #[serde(default)]
+    pub snippets: HashMap<String, String>,
+    #[serde(default)]
+    pub checks: Checks,
+    #[serde(default)]
+    pub columns: Columns,
+    #[serde(default)]
+    pub status_file: StatusFile,
+    #[serde(default)]
+    pub cleanup: Cleanup,
+    #[serde(default)]
+    pub backup: Backup,
+    #[serde(default)]
+    pub workday: Workday,
+    #[serde(default)]
+    pub updates: Updates,
+    #[serde(default)]
+    pub links: Links,
+    #[serde(default)]
+    pub protect: Protect,
+    #[serde(default)]
+    pub defaults: Defaults,
+    #[serde(default)]
+    pub invoice: Invoice,
+    #[serde(default)]
+    pub mappings: HashMap<String, Mapping>, // By destination: csv, ods, html, timew or timeclock
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Export {
     pub path: String,
-    pub format: String,
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub changed_only: bool,
+    #[serde(default)]
+    pub lock: bool, // Lock the day after exporting all of it
+    // Letterhead of HTML exports, each left out when empty
+    #[serde(default)]
+    pub company: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub logo: String, // Image path or URL
+    #[serde(default)]
+    pub encoding: Encoding, // Of CSV exports
+    #[serde(default)]
+    pub newlines: Newlines, // Line breaks inside CSV cells
+    #[serde(default)]
+    pub crlf: bool, // End CSV lines with \r\n
+}
+
+// File type of exports. ODS is the OpenDocument spreadsheet LibreOffice uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Ods,
+    Html, // A styled page to print or save as PDF from the browser
+}
+
+// Any case, as export.format was free text before and configs have "CSV"
+impl<'de> Deserialize<'de> for ExportFormat {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let format = String::deserialize(deserializer)?;
+        match format.trim().to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "ods" => Ok(ExportFormat::Ods),
+            "html" => Ok(ExportFormat::Html),
+            _ => Err(serde::de::Error::unknown_variant(
+                &format,
+                &["csv", "ods", "html"],
+            )),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ods => "ods",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+// Text encoding of CSV files. Excel on Windows only reads UTF-8 as such after a byte
+// order mark, and older import tools want Windows-1252.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Encoding {
+    #[default]
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[serde(rename = "utf-8-bom")]
+    Utf8Bom,
+    #[serde(rename = "windows-1252")]
+    Windows1252,
+}
+
+// What becomes of line breaks in descriptions in CSV exports: kept inside the quoted
+// cell, joined with "; ", or written as the two characters \n
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Newlines {
+    #[default]
+    Keep,
+    Semicolon,
+    Escape,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Import {
+    pub ics_path: String,
+    pub work_code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivityWatch {
+    pub enabled: bool,
+    pub url: String,
+    pub limit: usize,
+}
+
+impl Default for ActivityWatch {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "http://localhost:5600".to_string(),
+            limit: 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    pub webhook_url: String, // Receives a JSON summary after each export, empty to disable
+}
+
+// Emailing exports, from the export menu or `slothtime export --email`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Email {
+    pub to: Vec<String>,
+    pub from: String,
+    pub subject: String,  // `{date}` is replaced by the exported day or range
+    pub sendmail: String, // Used when smtp.host is empty
+    pub smtp: Smtp,
+}
+
+impl Default for Email {
+    fn default() -> Self {
+        Self {
+            to: Vec::new(),
+            from: String::new(),
+            subject: "Timesheet {date}".to_string(),
+            sendmail: "sendmail -t".to_string(),
+            smtp: Smtp::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Smtp {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: String,
+    pub password: String, // SLOTHTIME_SMTP_PASSWORD is used when empty
+}
+
+impl Default for Smtp {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 587,
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            password: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    #[default]
+    StartTls, // Plain connection upgraded with STARTTLS, usually port 587
+    Tls, // TLS from the start, usually port 465
+    None,
+}
+
+// Copying each export to a shared folder once it's written
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Upload {
+    pub url: String,      // A WebDAV folder or s3://bucket/prefix, empty to disable
+    pub username: String, // The access key id for S3
+    pub password: String, // The secret access key for S3
+    pub region: String,   // S3 only, us-east-1 when empty
+    pub endpoint: String, // S3 compatible servers, e.g. http://localhost:9000 for MinIO
+}
+
+// A small file describing the running entry, for status bars outside the terminal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusFile {
+    pub enabled: bool,
+    pub path: String, // A .json copy is written next to it
+}
+
+impl Default for StatusFile {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "~/.slothtime/status".to_string(),
+        }
+    }
+}
+
+// Names and IDs the system an export goes to expects instead of Slothtime's own
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Mapping {
+    pub columns: HashMap<String, String>, // Header renames, e.g. work_code = "Activity"
+    pub work_codes: HashMap<String, String>, // Work code to the destination's ID, all listed when any are
+}
+
+// Asking GitHub for a newer release on startup, off unless opted in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Updates {
+    pub check: bool,
+    pub url: String, // A GitHub "latest release" API endpoint
+}
+
+impl Default for Updates {
+    fn default() -> Self {
+        Self {
+            check: false,
+            url: "https://api.github.com/repos/LostRhapsody/slothtime-rs/releases/latest"
+                .to_string(),
+        }
+    }
+}
+
+// Where `b` opens an entry's task number, besides the URLs in its description
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Links {
+    pub task_url: String, // e.g. "https://jira.example.com/browse/{task}", empty for none
+}
+
+// The columns :protect keeps from changing when it isn't given any
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Protect {
+    pub columns: Vec<String>, // Keys like "task_number", "start_time"
+}
+
+impl Default for Protect {
+    fn default() -> Self {
+        Self {
+            columns: ["task_number", "start_time", "end_time"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+// Timestamped copies of entries.json kept in backups/ on each save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Backup {
+    pub enabled: bool,
+    pub compress: bool,      // Write .json.gz instead of .json
+    pub keep: usize,         // Oldest backups beyond this many are removed, 0 keeps all
+    pub mirror_path: String, // A second folder backups are copied to, empty for none
+}
+
+impl Default for Backup {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            compress: false,
+            keep: 200,
+            mirror_path: String::new(),
+        }
+    }
+}
+
+// Settings for the :coalesce command that merges very short entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Cleanup {
+    pub min_minutes: u32, // Entries shorter than this are merged into a neighbour on the same task
+}
+
+impl Default for Cleanup {
+    fn default() -> Self {
+        Self { min_minutes: 5 }
+    }
+}
+
+// The hours a day's entries should cover, used by :fill to find gaps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workday {
+    pub start: String, // HH:MM
+    pub end: String,
+}
+
+impl Default for Workday {
+    fn default() -> Self {
+        Self {
+            start: "09:00".to_string(),
+            end: "16:30".to_string(),
+        }
+    }
+}
+
+// Filled into a new row when you start editing it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Defaults {
+    pub work_code: String,
+    pub task_prefix: String,        // e.g. "PROJ-", typed on from there
+    pub start_after_previous: bool, // Start where the row above ends
+}
+
+// Billing a client for a date range with `slothtime invoice`. The letterhead is
+// export.company, export.name and export.logo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Invoice {
+    pub format: InvoiceFormat,
+    pub currency: String, // Written before amounts, e.g. "$" or "EUR "
+    pub rate: f64,        // Per hour, for work codes without their own rate
+    pub rates: HashMap<String, f64>, // Per hour by work code
+    pub tax_name: String,
+    pub tax_rate: f64,   // Percent added to the subtotal, 0 for none
+    pub address: String, // Yours, under the letterhead
+    pub client: String,  // Name and address billed, lines kept
+    pub due_days: u32,   // Payment due after the invoice date, 0 to leave out
+    pub notes: String,   // e.g. payment details, at the bottom
+}
+
+impl Default for Invoice {
+    fn default() -> Self {
+        Self {
+            format: InvoiceFormat::default(),
+            currency: "$".to_string(),
+            rate: 0.0,
+            rates: HashMap::new(),
+            tax_name: "Tax".to_string(),
+            tax_rate: 0.0,
+            address: String::new(),
+            client: String::new(),
+            due_days: 30,
+            notes: String::new(),
+        }
+    }
+}
+
+// HTML prints to PDF from the browser, like the HTML export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum InvoiceFormat {
+    #[default]
+    Html,
+    Markdown,
+}
+
+impl InvoiceFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            InvoiceFormat::Html => "html",
+            InvoiceFormat::Markdown => "md",
+        }
+    }
+}
+
+// Sanity checks flagged in the table and before exporting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Checks {
+    pub max_hours: f64, // Longest believable single entry, 0 to disable
+    pub zero_length: bool,
+    pub end_before_start: bool,
+    pub earliest: String, // HH:MM no time should be before, empty for no limit
+    pub latest: String,
+    pub block_outside_hours: bool, // Refuse times outside earliest-latest instead of flagging them
+}
+
+impl Default for Checks {
+    fn default() -> Self {
+        Self {
+            max_hours: 8.0,
+            zero_length: true,
+            end_before_start: true,
+            earliest: String::new(),
+            latest: String::new(),
+            block_outside_hours: false,
+        }
+    }
+}
+
+// Input rules applied to the text columns when leaving a cell
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Columns {
+    pub task_number: ColumnRules,
+    pub work_code: ColumnRules,
+    pub time_entry: ColumnRules,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnRules {
+    pub uppercase: bool,
+    pub prefix: String,  // Added to values that don't start with it, e.g. "PROJ-"
+    pub pattern: String, // Regex values must match, empty to accept anything
+    pub allowlist: String, // File or http(s) URL listing the accepted values
+    pub max_length: usize, // Characters the system the time goes to accepts, 0 for no limit
+    pub hard_limit: bool, // Refuse typing past max_length instead of only flagging it
+}
+
+// Hour caps for a single work code
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Budget {
+    pub weekly_hours: Option<f64>,
+    pub monthly_hours: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +472,152 @@ pub struct Ui {
     pub show_instructions: bool,
     pub auto_save: bool,
     pub time_bar: TimeBar,
+    #[serde(default)]
+    pub layout: PaneLayout,
+    #[serde(default = "default_true")]
+    pub auto_popup: bool,
+    #[serde(default)]
+    pub type_to_edit: bool,
+    #[serde(default)]
+    pub keymap: Keymap,
+    #[serde(default = "default_true")]
+    pub normalize_whitespace: bool,
+    #[serde(default)]
+    pub quit: QuitBinding,
+    #[serde(default)]
+    pub density: Density,
+    #[serde(default)]
+    pub first_column: FirstColumn,
+    #[serde(default)]
+    pub time_picker: bool, // Open the picker whenever a time cell is edited
+    #[serde(default = "default_time_step")]
+    pub time_step: u32, // Minutes the picker's Up/Down move by
+    #[serde(default)]
+    pub palette: Palette,
+    #[serde(default)]
+    pub ascii_only: bool, // Draw borders, bars and marks with plain ASCII characters
+    #[serde(default)]
+    pub new_row: NewRow,
+    #[serde(default)]
+    pub popup: Popup,
+    #[serde(default = "default_true")]
+    pub markdown: bool, // Show descriptions as light Markdown outside edit mode
+    #[serde(default = "default_true")]
+    pub suggest_descriptions: bool, // Offer a task number's earlier descriptions
+    #[serde(default)]
+    pub ripple_times: bool, // Moving a time moves the touching neighbour's too
+    #[serde(default = "default_true")]
+    pub legend: bool, // Explain the row marks in view under the table
+    #[serde(default)]
+    pub relative_times: bool, // A When column, e.g. "running 45m" or "2h ago"
+}
+
+// Size and place of the description popup as percentages of the screen, changed with
+// Ctrl+arrows and Alt+arrows in the popup and saved back to the config file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Popup {
+    pub width: u16,
+    pub height: u16,
+    pub x: u16, // Where the popup sits in the space left beside it, 0 left, 50 centred, 100 right
+    pub y: u16, // The same from top to bottom
+}
+
+impl Default for Popup {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            height: 60,
+            x: 50,
+            y: 50,
+        }
+    }
+}
+
+// When a blank row is added below a complete last row. Only one blank row is ever
+// added at the end, `o` and `O` insert rows whatever this says.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NewRow {
+    Off,
+    Navigate, // Moving down past the last row
+    Edit,     // Leaving edit mode on the last row
+    #[default]
+    Both,
+}
+
+// Colours used on screen. The NO_COLOR environment variable always picks monochrome.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    #[default]
+    Default,
+    Monochrome,   // No colours, only bold, reversed and dim text
+    Deuteranopia, // Blue, yellow and magenta instead of telling red from green
+}
+
+// What the narrow column in front of each row shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FirstColumn {
+    #[default]
+    Number, // Row number followed by the status glyphs
+    Date,   // Day the row was last exported
+    Status, // Only the status glyphs
+    None,
+}
+
+// "compact" drops the blank line between table rows so about twice as many fit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+// What pressing `q` in navigation mode does. `:q` always quits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum QuitBinding {
+    #[default]
+    #[serde(rename = "q")]
+    Q,
+    #[serde(rename = "confirm")]
+    Confirm,
+    #[serde(rename = "qq")]
+    DoubleQ,
+    #[serde(rename = "ctrl-c")]
+    CtrlC,
+}
+
+// "vim" is modal (i to edit, Esc to leave), "simple" is always editing like a spreadsheet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Keymap {
+    #[default]
+    Vim,
+    Simple,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_version() -> u32 {
+    schema::CONFIG_VERSION
+}
+
+fn default_time_step() -> u32 {
+    5
+}
+
+// "table" shows the description in a popup on column 3, "split" keeps a detail panel beside the table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaneLayout {
+    #[default]
+    Table,
+    Split,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,18 +627,30 @@ pub struct TimeBar {
     pub show_date: bool,
 }
 
+// Directory holding the config, entries and archived days
+pub fn data_dir() -> PathBuf {
+    let home_dir = dirs::home_dir().unwrap();
+    let config_dir = home_dir.join(".slothtime");
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir).unwrap();
+    }
+    config_dir
+}
+
 impl Default for Config {
     fn default() -> Self {
-        // Get home dir/ location for config
-        let home_dir = dirs::home_dir().unwrap();
-        let config_dir = home_dir.join(".slothtime");
-        if !config_dir.exists() {
-            fs::create_dir_all(&config_dir).unwrap();
-        }
-        let file = config_dir.join("slothtime.toml");
+        let file = data_dir().join("slothtime.toml");
         let export = Export {
             path: "~/Documents/slothtime_exports".to_string(),
-            format: "csv".to_string(),
+            format: ExportFormat::default(),
+            changed_only: false,
+            lock: false,
+            company: String::new(),
+            name: String::new(),
+            logo: String::new(),
+            encoding: Encoding::default(),
+            newlines: Newlines::default(),
+            crlf: false,
         };
         let ui = Ui {
             show_instructions: true,
@@ -51,22 +660,114 @@ impl Default for Config {
                 format_24hr: false,
                 show_date: false,
             },
+            layout: PaneLayout::default(),
+            auto_popup: true,
+            type_to_edit: false,
+            keymap: Keymap::default(),
+            normalize_whitespace: true,
+            quit: QuitBinding::default(),
+            density: Density::default(),
+            first_column: FirstColumn::default(),
+            time_picker: false,
+            time_step: default_time_step(),
+            palette: Palette::default(),
+            ascii_only: false,
+            new_row: NewRow::default(),
+            popup: Popup::default(),
+            markdown: true,
+            suggest_descriptions: true,
+            ripple_times: false,
+            legend: true,
+            relative_times: false,
         };
-        Self { file, export, ui }
+        Self {
+            file,
+            profile: None,
+            version: schema::CONFIG_VERSION,
+            export,
+            ui,
+            import: Import::default(),
+            activitywatch: ActivityWatch::default(),
+            budgets: HashMap::new(),
+            hooks: Hooks::default(),
+            email: Email::default(),
+            upload: Upload::default(),
+            snippets: HashMap::new(),
+            checks: Checks::default(),
+            columns: Columns::default(),
+            status_file: StatusFile::default(),
+            cleanup: Cleanup::default(),
+            workday: Workday::default(),
+            backup: Backup::default(),
+            updates: Updates::default(),
+            links: Links::default(),
+            protect: Protect::default(),
+            defaults: Defaults::default(),
+            invoice: Invoice::default(),
+            mappings: HashMap::new(),
+        }
     }
 }
 
 impl Config {
-    pub fn load() -> Result<Self> {
+    // Load the base config, with the SLOTHTIME_PROFILE profile and then the project's
+    // slothtime.toml merged over it
+    pub fn load(project: Option<&str>) -> Result<Self> {
         let config = Self::default();
-        if config.file.exists() {
-            let content = fs::read_to_string(config.file)?;
-            let config: Config = toml::from_str(&content)?;
-            Ok(config)
-        } else {
+        if !config.file.exists() {
             config.save()?;
-            Ok(config)
         }
+        let content = fs::read_to_string(&config.file)?;
+        let mut value = schema::migrate_config(toml::from_str(&content)?)?;
+
+        let profile = profile();
+        if let Some(profile) = &profile {
+            let file = profile_file(profile)?;
+            let content = fs::read_to_string(&file).with_context(|| {
+                format!(
+                    "SLOTHTIME_PROFILE is {}, but {} can't be read",
+                    profile,
+                    file.display()
+                )
+            })?;
+            merge_toml(
+                &mut value,
+                schema::migrate_config(toml::from_str(&content)?)?,
+            );
+        }
+
+        if let Some(project) = project {
+            let overlay_file = project_dir(project)?.join("slothtime.toml");
+            if overlay_file.exists() {
+                let overlay = toml::from_str(&fs::read_to_string(overlay_file)?)?;
+                merge_toml(&mut value, schema::migrate_config(overlay)?);
+            }
+        }
+
+        let mut config: Config = value.try_into()?;
+        config.file = Self::default().file;
+        config.profile = profile;
+        Ok(config)
+    }
+
+    // Write ui.popup into the config file, leaving the rest of the file as it is on disk
+    // rather than saving settings that came from a project's overlay
+    pub fn save_popup(&self) -> Result<()> {
+        let mut value: toml::Value = match fs::read_to_string(&self.file) {
+            Ok(content) => toml::from_str(&content)?,
+            Err(_) => toml::Value::Table(toml::map::Map::new()),
+        };
+        let Some(table) = value.as_table_mut() else {
+            return Ok(());
+        };
+        let ui = table
+            .entry("ui")
+            .or_insert_with(|| toml::Value::Table(toml::map::Map::new()));
+        if let Some(ui) = ui.as_table_mut() {
+            ui.insert("popup".to_string(), toml::Value::try_from(self.ui.popup)?);
+        }
+        fs::write(&self.file, toml::to_string(&value)?)?;
+        Ok(())
     }
 
     pub fn save(&self) -> Result<()> {
@@ -75,3 +776,90 @@ impl Config {
         Ok(())
     }
 }
+
+// Overlay tables are merged key by key, any other value replaces the base one
+fn merge_toml(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+// The profile SLOTHTIME_PROFILE names, e.g. `work`, when it's set
+fn profile() -> Option<String> {
+    std::env::var("SLOTHTIME_PROFILE")
+        .ok()
+        .map(|profile| profile.trim().to_string())
+        .filter(|profile| !profile.is_empty())
+}
+
+// A profile's settings live in ~/.slothtime/profiles/<name>.toml, shared by every project
+fn profile_file(name: &str) -> Result<PathBuf> {
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("invalid profile name '{}'", name);
+    }
+    Ok(data_dir().join("profiles").join(format!("{}.toml", name)))
+}
+
+// Each project keeps its entries, archive and trash in ~/.slothtime/projects/<name>
+pub fn project_dir(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("invalid project name '{}'", name);
+    }
+    let dir = data_dir().join("projects").join(name);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+pub fn list_projects() -> Result<Vec<String>> {
+    let dir = data_dir().join("projects");
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut projects: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    projects.sort();
+    Ok(projects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Formats {
+        formats: Vec<ExportFormat>,
+    }
+
+    #[test]
+    fn export_format_ignores_case() {
+        let parsed: Formats =
+            toml::from_str(r#"formats = ["CSV", "Ods", "html", " HTML "]"#).unwrap();
+        assert_eq!(
+            parsed.formats,
+            vec![
+                ExportFormat::Csv,
+                ExportFormat::Ods,
+                ExportFormat::Html,
+                ExportFormat::Html
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_export_format_is_refused() {
+        assert!(toml::from_str::<Formats>(r#"formats = ["xlsx"]"#).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{NaiveDate, NaiveTime};
+use std::collections::BTreeMap;
+use unicode_width::UnicodeWidthStr;
+
+use crate::time_entry::{self, TimeEntry};
+
+const HEADER: [&str; 6] = ["Start", "End", "Time", "Task", "Code", "Description"];
+
+// The day's entries as a Markdown table padded into columns, so it reads the same in a
+// plain text status email as where Markdown is rendered, followed by the total and the
+// time per task. The running entry counts up to `now`.
+pub fn markdown(date: NaiveDate, note: &str, entries: &[TimeEntry], now: NaiveTime) -> String {
+    let running = time_entry::running_entry(entries);
+    let mut rows: Vec<[String; 6]> = Vec::new();
+    let mut by_task: BTreeMap<String, i64> = BTreeMap::new();
+    let mut total = 0;
+    for (row, entry) in entries.iter().enumerate() {
+        if entry.is_entirely_empty() {
+            continue;
+        }
+        let (end, minutes) = if running == Some(row) {
+            let start = TimeEntry::parse_time(&entry.start_time);
+            (
+                "running".to_string(),
+                start.map(|start| (now - start).num_minutes().max(0)),
+            )
+        } else {
+            (entry.end_time.trim().to_string(), entry.duration_minutes())
+        };
+        if let Some(minutes) = minutes {
+            total += minutes;
+            *by_task
+                .entry(entry.task_number.trim().to_string())
+                .or_default() += minutes;
+        }
+        rows.push([
+            entry.start_time.trim().to_string(),
+            end,
+            minutes.map(time_entry::format_duration).unwrap_or_default(),
+            cell(&entry.task_number),
+            cell(&entry.work_code),
+            cell(&entry.time_entry),
+        ]);
+    }
+
+    let mut widths = HEADER.map(UnicodeWidthStr::width);
+    for row in &rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.width());
+        }
+    }
+    let line = |values: &[String]| {
+        let cells: Vec<String> = values
+            .iter()
+            .zip(widths)
+            .map(|(value, width)| format!("{}{}", value, " ".repeat(width - value.width())))
+            .collect();
+        format!("| {} |", cells.join(" | ")).trim_end().to_string()
+    };
+
+    let mut out = format!("## {}", date.format("%a %Y-%m-%d"));
+    if !note.trim().is_empty() {
+        out.push_str(&format!(" - {}", note.trim()));
+    }
+    out.push_str("\n\n");
+    out.push_str(&line(&HEADER.map(String::from)));
+    out.push('\n');
+    let rule: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+    out.push_str(&format!("|{}|\n", rule.join("|")));
+    for row in &rows {
+        out.push_str(&line(row));
+        out.push('\n');
+    }
+    out.push_str(&format!(
+        "\n**Total: {}**\n",
+        time_entry::format_duration(total)
+    ));
+    for (task, minutes) in &by_task {
+        let task = if task.is_empty() {
+            "No task number"
+        } else {
+            task.as_str()
+        };
+        out.push_str(&format!(
+            "- {}: {}\n",
+            task,
+            time_entry::format_duration(*minutes)
+        ));
+    }
+    out
+}
+
+// One line, with pipes escaped so they don't split the cell
+fn cell(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
+        .replace('|', "\\|")
+}
@@ -0,0 +1,227 @@
+use chrono::NaiveTime;
+use std::cmp::Ordering;
+
+use crate::time_entry::TimeEntry;
+
+// Rows that look like the same piece of work logged more than once, e.g. after a
+// paste landed twice or a timer was started on top of a manual entry
+#[derive(Debug, Clone)]
+pub struct Duplicate {
+    pub rows: Vec<usize>,  // The first row is the one kept when deleting
+    pub exact: bool,       // Every field is the same
+    pub merged: TimeEntry, // All the rows as one entry covering their times
+}
+
+// Order rows by start time, keeping the order of rows with the same start. Rows
+// without a start time go last.
+pub fn sort_by_start(entries: &mut [TimeEntry]) {
+    entries.sort_by(|a, b| {
+        match (
+            TimeEntry::parse_time(&a.start_time),
+            TimeEntry::parse_time(&b.start_time),
+        ) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    });
+}
+
+// Group rows on the same task number whose times overlap. Rows without a task number
+// or start time are never duplicates, and a running entry only matches one that
+// started at the same time.
+pub fn find(entries: &[TimeEntry]) -> Vec<Duplicate> {
+    let mut grouped = vec![false; entries.len()];
+    let mut duplicates = Vec::new();
+    for (i, first) in entries.iter().enumerate() {
+        if grouped[i] {
+            continue;
+        }
+        let rows: Vec<usize> = std::iter::once(i)
+            .chain((i + 1..entries.len()).filter(|&j| !grouped[j] && same_work(first, &entries[j])))
+            .collect();
+        if rows.len() < 2 {
+            continue;
+        }
+        for &row in &rows {
+            grouped[row] = true;
+        }
+        let exact = rows[1..]
+            .iter()
+            .all(|&row| same_fields(first, &entries[row]));
+        duplicates.push(Duplicate {
+            merged: merge(entries, &rows),
+            rows,
+            exact,
+        });
+    }
+    duplicates
+}
+
+// Drop every row but the first of each group, or with `merge` replace the group by its
+// merged entry. The rows taken out are handed back, all of a merged group's included.
+pub fn apply(
+    entries: &mut Vec<TimeEntry>,
+    duplicates: &[Duplicate],
+    merge: bool,
+) -> Vec<TimeEntry> {
+    let mut removed = Vec::new();
+    let mut drop: Vec<usize> = Vec::new();
+    for duplicate in duplicates {
+        let kept = duplicate.rows[0];
+        if merge {
+            removed.push(std::mem::replace(
+                &mut entries[kept],
+                duplicate.merged.clone(),
+            ));
+        }
+        drop.extend(&duplicate.rows[1..]);
+    }
+    // From the bottom up so the row numbers still to remove stay valid
+    drop.sort_unstable();
+    for row in drop.into_iter().rev() {
+        removed.push(entries.remove(row));
+    }
+    removed
+}
+
+fn span(entry: &TimeEntry) -> Option<(NaiveTime, Option<NaiveTime>)> {
+    let start = TimeEntry::parse_time(&entry.start_time)?;
+    Some((start, TimeEntry::parse_time(&entry.end_time)))
+}
+
+fn same_work(a: &TimeEntry, b: &TimeEntry) -> bool {
+    let task = a.task_number.trim();
+    if task.is_empty() || !task.eq_ignore_ascii_case(b.task_number.trim()) {
+        return false;
+    }
+    match (span(a), span(b)) {
+        (Some((a_start, Some(a_end))), Some((b_start, Some(b_end)))) => {
+            a_start == b_start || (a_start < b_end && b_start < a_end)
+        }
+        (Some((a_start, _)), Some((b_start, _))) => a_start == b_start,
+        _ => false,
+    }
+}
+
+fn same_fields(a: &TimeEntry, b: &TimeEntry) -> bool {
+    (1..=5).all(|col| a.field(col).map(|f| f.trim()) == b.field(col).map(|f| f.trim()))
+}
+
+// The earliest start to the latest end, left running if any row is. The first row's
+// work code is kept and different descriptions are joined, each once.
+fn merge(entries: &[TimeEntry], rows: &[usize]) -> TimeEntry {
+    let group: Vec<&TimeEntry> = rows.iter().map(|&row| &entries[row]).collect();
+    let start = group
+        .iter()
+        .min_by_key(|entry| TimeEntry::parse_time(&entry.start_time))
+        .map(|entry| entry.start_time.clone())
+        .unwrap_or_default();
+    let end = if group.iter().any(|entry| entry.end_time.trim().is_empty()) {
+        String::new()
+    } else {
+        group
+            .iter()
+            .max_by_key(|entry| TimeEntry::parse_time(&entry.end_time))
+            .map(|entry| entry.end_time.clone())
+            .unwrap_or_default()
+    };
+    let mut descriptions: Vec<&str> = Vec::new();
+    for entry in &group {
+        let description = entry.time_entry.trim();
+        let seen = descriptions
+            .iter()
+            .any(|d| d.split("; ").any(|part| part == description));
+        if !description.is_empty() && !seen {
+            descriptions.push(description);
+        }
+    }
+    TimeEntry {
+        time_entry: descriptions.join("; "),
+        start_time: start,
+        end_time: end,
+        checked: group.iter().any(|entry| entry.checked),
+        ..group[0].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    #[test]
+    fn overlapping_rows_on_a_task_are_duplicates() {
+        let entries = vec![
+            entry("PROJ-1", "DEV", "09:00", "10:00").described("Review"),
+            entry("PROJ-2", "DEV", "09:30", "10:00").described("Meeting"),
+            entry("proj-1", "DEV", "09:30", "10:30").described("Review"),
+            entry("PROJ-1", "DEV", "11:00", "12:00").described("Later"),
+        ];
+        let duplicates = find(&entries);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].rows, [0, 2]);
+        assert!(!duplicates[0].exact);
+        assert_eq!(duplicates[0].merged.start_time, "09:00");
+        assert_eq!(duplicates[0].merged.end_time, "10:30");
+        assert_eq!(duplicates[0].merged.time_entry, "Review");
+    }
+
+    #[test]
+    fn running_entries_only_match_the_same_start() {
+        let entries = vec![
+            entry("PROJ-1", "DEV", "09:00", "").described("A"),
+            entry("PROJ-1", "DEV", "09:00", "").described("A"),
+        ];
+        let duplicates = find(&entries);
+        assert!(duplicates[0].exact);
+        assert_eq!(duplicates[0].merged.end_time, "");
+        assert!(find(&[
+            entry("PROJ-1", "DEV", "09:00", "").described("A"),
+            entry("PROJ-1", "DEV", "09:10", "").described("A")
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn part_of_a_description_is_still_joined() {
+        let entries = vec![
+            entry("PROJ-1", "DEV", "09:00", "10:00").described("Code review"),
+            entry("PROJ-1", "DEV", "09:00", "10:00").described("review"),
+            entry("PROJ-1", "DEV", "09:00", "10:00").described("Code review"),
+        ];
+        assert_eq!(find(&entries)[0].merged.time_entry, "Code review; review");
+    }
+
+    #[test]
+    fn apply_keeps_the_first_row_or_the_merge() {
+        let entries = vec![
+            entry("PROJ-1", "DEV", "09:00", "10:00").described("A"),
+            entry("PROJ-1", "DEV", "09:30", "10:30").described("B"),
+        ];
+        let duplicates = find(&entries);
+
+        let mut deleted = entries.clone();
+        let removed = apply(&mut deleted, &duplicates, false);
+        assert_eq!((deleted.len(), removed.len()), (1, 1));
+        assert_eq!(deleted[0].time_entry, "A");
+
+        let mut merged = entries.clone();
+        let removed = apply(&mut merged, &duplicates, true);
+        assert_eq!((merged.len(), removed.len()), (1, 2));
+        assert_eq!(merged[0].time_entry, "A; B");
+    }
+
+    #[test]
+    fn rows_without_a_start_sort_last() {
+        let mut entries = vec![
+            entry("PROJ-3", "DEV", "", ""),
+            entry("PROJ-2", "DEV", "10:00", ""),
+            entry("PROJ-1", "DEV", "09:00", ""),
+        ];
+        sort_by_start(&mut entries);
+        let order: Vec<&str> = entries.iter().map(|e| e.task_number.as_str()).collect();
+        assert_eq!(order, ["PROJ-1", "PROJ-2", "PROJ-3"]);
+    }
+}
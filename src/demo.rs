@@ -0,0 +1,144 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app::{App, InputMode};
+use crate::archive::{self, Day};
+use crate::config::Config;
+use crate::schema;
+use crate::time_entry::TimeEntry;
+
+// One hint of the --demo tutorial, done once the app reaches the state it asks for
+pub struct Step {
+    pub hint: &'static str,
+    pub done: fn(&App) -> bool,
+}
+
+pub const STEPS: &[Step] = &[
+    Step {
+        hint: "Move between cells with the arrow keys or Tab. Go down to row 3.",
+        done: |app| app.cursor.row == 2,
+    },
+    Step {
+        hint: "Press i to edit the selected cell.",
+        done: |app| matches!(app.mode, InputMode::Editing),
+    },
+    Step {
+        hint: "Type something, then press Esc to go back to navigation.",
+        done: |app| matches!(app.mode, InputMode::Navigation),
+    },
+    Step {
+        hint: "Move right to the Time Entry column, its description opens by itself.",
+        done: |app| matches!(app.mode, InputMode::EditingPopup | InputMode::ViewingPopup),
+    },
+    Step {
+        hint: "Tab moves on from the description. Then press / and type DEV and Enter to only list DEV rows.",
+        done: |app| app.filter.is_some(),
+    },
+    Step {
+        hint: "Esc shows every row again. Press r for the report view.",
+        done: |app| matches!(app.mode, InputMode::Report),
+    },
+    Step {
+        hint: "Esc leaves the report. Press H to search the earlier days.",
+        done: |app| matches!(app.mode, InputMode::History),
+    },
+    Step {
+        hint: "That's the tour. Press Esc, then ? for every key. Ctrl+S exports to a throwaway folder, q quits the demo.",
+        done: |app| matches!(app.mode, InputMode::Help),
+    },
+];
+
+fn entry(
+    task_number: &str,
+    work_code: &str,
+    description: &str,
+    start: &str,
+    end: &str,
+) -> TimeEntry {
+    TimeEntry {
+        task_number: task_number.to_string(),
+        work_code: work_code.to_string(),
+        time_entry: description.to_string(),
+        start_time: start.to_string(),
+        end_time: end.to_string(),
+        ..TimeEntry::new()
+    }
+}
+
+fn sample_day() -> Vec<TimeEntry> {
+    vec![
+        entry(
+            "PROJ-101",
+            "DEV",
+            "Fix login redirect loop",
+            "09:00",
+            "10:30",
+        ),
+        entry("PROJ-102", "DEV", "Review pagination PR", "10:30", "11:15"),
+        entry("ADMIN", "MEET", "Standup", "11:15", "11:30"),
+        entry(
+            "PROJ-101",
+            "DEV",
+            "Write tests for the redirect fix",
+            "12:30",
+            "14:00",
+        ),
+        entry(
+            "PROJ-205",
+            "OPS",
+            "Rotate staging certificates",
+            "14:00",
+            "15:10",
+        ),
+    ]
+}
+
+// The demo's data folder, removed when it's dropped so a failed start, an error or a
+// panic doesn't leave it behind
+pub struct DemoDir(PathBuf);
+
+impl DemoDir {
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for DemoDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// A throwaway data folder filled with sample entries and a few archived days, and a
+// default config pointing exports into it, so the demo never touches real data
+pub fn setup(today: NaiveDate) -> Result<(Config, DemoDir)> {
+    let demo_dir =
+        DemoDir(std::env::temp_dir().join(format!("slothtime-demo-{}", std::process::id())));
+    let dir = demo_dir.path();
+    fs::create_dir_all(dir)?;
+
+    let mut entries = sample_day();
+    entries.push(entry("PROJ-102", "DEV", "", "15:10", ""));
+    fs::write(dir.join("entries.json"), schema::write_entries(&entries)?)?;
+    for days_ago in [1, 2, 3, 6, 7] {
+        let mut entries = sample_day();
+        entries.truncate(2 + days_ago as usize % 4);
+        archive::save_day(
+            dir,
+            &Day {
+                date: today - Duration::days(days_ago),
+                entries,
+                note: String::new(),
+            },
+        )?;
+    }
+
+    let mut config = Config {
+        file: dir.join("slothtime.toml"),
+        ..Config::default()
+    };
+    config.export.path = dir.join("exports").to_string_lossy().to_string();
+    Ok((config, demo_dir))
+}
@@ -0,0 +1,397 @@
+use std::ops::Range;
+
+use crate::text::{self, LineIndex};
+
+// Undo steps kept for the field being edited
+const MAX_UNDO: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EditKind {
+    Insert,
+    Delete,
+    Replace,
+}
+
+// The text of the cell being edited, with a grapheme cursor, a selection and undo
+// history. Positions count grapheme clusters like the rest of the text module.
+#[derive(Debug, Clone, Default)]
+pub struct FieldEditor {
+    text: String,
+    original: String, // The field as it was when editing started
+    cursor: usize,
+    anchor: Option<usize>, // Where the selection started, the cursor being its other end
+    undo: Vec<(String, usize)>,
+    redo: Vec<(String, usize)>,
+    last_edit: Option<EditKind>, // Consecutive typing or deleting undoes as one step
+    lines: Option<LineIndex>,
+}
+
+impl FieldEditor {
+    // Start editing `text` with the cursor at its end
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            original: text.to_string(),
+            cursor: text::grapheme_count(text),
+            ..Self::default()
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    // Selected graphemes, None when nothing is selected
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+        match anchor.cmp(&self.cursor) {
+            std::cmp::Ordering::Less => Some(anchor..self.cursor),
+            std::cmp::Ordering::Greater => Some(self.cursor..anchor),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        let selection = self.selection()?;
+        Some(&self.text[self.byte_range(selection)])
+    }
+
+    fn byte_range(&self, range: Range<usize>) -> Range<usize> {
+        text::byte_offset(&self.text, range.start)..text::byte_offset(&self.text, range.end)
+    }
+
+    fn len(&self) -> usize {
+        text::grapheme_count(&self.text)
+    }
+
+    // Move the cursor, extending the selection when `select` is held and dropping it otherwise
+    pub fn move_to(&mut self, index: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = index.min(self.len());
+        self.last_edit = None;
+    }
+
+    pub fn left(&mut self, select: bool) {
+        match self.selection() {
+            Some(selection) if !select => self.move_to(selection.start, false),
+            _ => self.move_to(self.cursor.saturating_sub(1), select),
+        }
+    }
+
+    pub fn right(&mut self, select: bool) {
+        match self.selection() {
+            Some(selection) if !select => self.move_to(selection.end, false),
+            _ => self.move_to(self.cursor + 1, select),
+        }
+    }
+
+    pub fn home(&mut self, select: bool) {
+        self.move_to(0, select);
+    }
+
+    pub fn end(&mut self, select: bool) {
+        self.move_to(self.len(), select);
+    }
+
+    // Move to the same display column on a neighbouring line
+    pub fn vertical(&mut self, lines: isize, select: bool) {
+        let index = self.lines.get_or_insert_with(|| LineIndex::new(&self.text));
+        let (line, column) = index.line_and_column(&self.text, self.cursor);
+        let Some(target) = line.checked_add_signed(lines) else {
+            return;
+        };
+        if let Some(cursor) = index.index_at(&self.text, target, column) {
+            self.move_to(cursor, select);
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.len();
+        self.last_edit = None;
+    }
+
+    // Save the current state for undo, unless this edit continues the previous one
+    fn record(&mut self, kind: EditKind) {
+        if self.last_edit != Some(kind) || kind == EditKind::Replace || self.selection().is_some() {
+            self.undo.push((self.text.clone(), self.cursor));
+            if self.undo.len() > MAX_UNDO {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.last_edit = Some(kind);
+        self.lines = None;
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let Some(selection) = self.selection() else {
+            self.anchor = None;
+            return false;
+        };
+        let bytes = self.byte_range(selection.clone());
+        self.text.replace_range(bytes, "");
+        self.cursor = selection.start;
+        self.anchor = None;
+        true
+    }
+
+    // Type or paste `s` over the selection
+    pub fn insert(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        // Each typed word undoes on its own
+        if s.starts_with(char::is_whitespace) {
+            self.last_edit = None;
+        }
+        self.record(EditKind::Insert);
+        self.delete_selection();
+        self.cursor = text::insert_str(&mut self.text, self.cursor, s);
+    }
+
+    pub fn backspace(&mut self) {
+        if self.selection().is_none() && self.cursor == 0 {
+            return;
+        }
+        self.record(EditKind::Delete);
+        if !self.delete_selection() {
+            text::remove_before(&mut self.text, self.cursor);
+            self.cursor -= 1;
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.selection().is_none() && self.cursor >= self.len() {
+            return;
+        }
+        self.record(EditKind::Delete);
+        if !self.delete_selection() {
+            text::remove_before(&mut self.text, self.cursor + 1);
+        }
+    }
+
+    // Remove the selection and hand it back for the clipboard
+    pub fn cut(&mut self) -> Option<String> {
+        let selected = self.selected_text()?.to_string();
+        self.record(EditKind::Replace);
+        self.delete_selection();
+        Some(selected)
+    }
+
+    // Swap in new text as a single undo step, e.g. an expanded snippet
+    pub fn replace(&mut self, text: String, cursor: usize) {
+        self.record(EditKind::Replace);
+        self.text = text;
+        self.anchor = None;
+        self.cursor = cursor.min(self.len());
+    }
+
+    // Rewrite the selection, or the word the cursor is in or next to, with `change`.
+    // False when there is nothing to change.
+    pub fn change_word(&mut self, change: impl Fn(&str) -> String) -> bool {
+        let range = match self.selection() {
+            Some(selection) => self.byte_range(selection),
+            None => match self.word_at_cursor() {
+                Some(range) => range,
+                None => return false,
+            },
+        };
+        self.change_range(range, change)
+    }
+
+    // Rewrite the selection, or the whole field when nothing is selected
+    pub fn change_all(&mut self, change: impl Fn(&str) -> String) -> bool {
+        let range = match self.selection() {
+            Some(selection) => self.byte_range(selection),
+            None => 0..self.text.len(),
+        };
+        self.change_range(range, change)
+    }
+
+    // A changed selection stays selected, so another change can follow
+    fn change_range(&mut self, range: Range<usize>, change: impl Fn(&str) -> String) -> bool {
+        let changed = change(&self.text[range.clone()]);
+        if changed == self.text[range.clone()] {
+            return false;
+        }
+        let selected = self.selection().is_some();
+        let mut text = self.text.clone();
+        text.replace_range(range.clone(), &changed);
+        let start = text::grapheme_count(&text[..range.start]);
+        let end = text::grapheme_count(&text[..range.start + changed.len()]);
+        self.replace(text, end);
+        if selected {
+            self.anchor = Some(start);
+        }
+        true
+    }
+
+    // Bytes of the word (anything between spaces) around the cursor, or the next one
+    // when the cursor sits between spaces
+    fn word_at_cursor(&self) -> Option<Range<usize>> {
+        let word = |chars: &mut dyn Iterator<Item = char>| -> usize {
+            chars
+                .take_while(|c| !c.is_whitespace())
+                .map(char::len_utf8)
+                .sum()
+        };
+        let cursor = text::byte_offset(&self.text, self.cursor);
+        let mut start = cursor - word(&mut self.text[..cursor].chars().rev());
+        let mut end = cursor + word(&mut self.text[cursor..].chars());
+        if start == end {
+            start = cursor
+                + self.text[cursor..]
+                    .chars()
+                    .take_while(|c| c.is_whitespace())
+                    .map(char::len_utf8)
+                    .sum::<usize>();
+            end = start + word(&mut self.text[start..].chars());
+        }
+        (start < end).then_some(start..end)
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some((text, cursor)) = self.undo.pop() else {
+            return false;
+        };
+        self.redo
+            .push((std::mem::replace(&mut self.text, text), self.cursor));
+        self.restore(cursor);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some((text, cursor)) = self.redo.pop() else {
+            return false;
+        };
+        self.undo
+            .push((std::mem::replace(&mut self.text, text), self.cursor));
+        self.restore(cursor);
+        true
+    }
+
+    fn restore(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.len());
+        self.anchor = None;
+        self.last_edit = None;
+        self.lines = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(editor: &mut FieldEditor, s: &str) {
+        for c in s.chars() {
+            editor.insert(&c.to_string());
+        }
+    }
+
+    #[test]
+    fn consecutive_typing_undoes_a_word_at_a_time() {
+        let mut editor = FieldEditor::new("");
+        typed(&mut editor, "Fix login bug");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "Fix login");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "Fix");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "");
+        assert!(!editor.undo());
+
+        // Moving the cursor starts a new step even mid-word
+        typed(&mut editor, "ab");
+        editor.left(false);
+        typed(&mut editor, "c");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "ab");
+    }
+
+    #[test]
+    fn replace_is_one_step_to_undo_and_redo() {
+        let mut editor = FieldEditor::new("");
+        typed(&mut editor, "brb");
+        editor.replace("be right back".to_string(), 13);
+        assert_eq!((editor.text(), editor.cursor()), ("be right back", 13));
+        assert!(editor.undo());
+        assert_eq!((editor.text(), editor.cursor()), ("brb", 3));
+        assert!(editor.redo());
+        assert_eq!((editor.text(), editor.cursor()), ("be right back", 13));
+        assert!(!editor.redo());
+
+        // A new edit clears what could be redone
+        assert!(editor.undo());
+        typed(&mut editor, "!");
+        assert!(!editor.redo());
+        assert_eq!(editor.text(), "brb!");
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let mut editor = FieldEditor::new("Fix login bug");
+        editor.move_to(4, false);
+        editor.move_to(9, true);
+        assert_eq!(editor.selected_text(), Some("login"));
+        typed(&mut editor, "signup");
+        assert_eq!((editor.text(), editor.cursor()), ("Fix signup bug", 10));
+        assert_eq!(editor.selection(), None);
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "Fix login bug");
+    }
+
+    #[test]
+    fn left_and_right_collapse_the_selection() {
+        let mut editor = FieldEditor::new("日本語 text");
+        editor.move_to(1, false);
+        editor.right(true);
+        editor.right(true);
+        assert_eq!(editor.selected_text(), Some("本語"));
+        editor.left(false);
+        assert_eq!((editor.cursor(), editor.selection()), (1, None));
+
+        editor.right(true);
+        editor.right(true);
+        editor.right(false);
+        assert_eq!((editor.cursor(), editor.selection()), (3, None));
+
+        // Without a selection they move one grapheme, stopping at the ends
+        editor.home(false);
+        editor.left(false);
+        assert_eq!(editor.cursor(), 0);
+        editor.end(false);
+        editor.right(false);
+        assert_eq!(editor.cursor(), 8);
+    }
+
+    #[test]
+    fn vertical_keeps_the_display_column() {
+        let mut editor = FieldEditor::new("hello\nhi\n日本語");
+        editor.move_to(4, false);
+        editor.vertical(1, false);
+        // "hi" is shorter, so the cursor lands at its end
+        assert_eq!(editor.cursor(), 8);
+        editor.vertical(1, false);
+        // Column 2 is after the first wide character
+        assert_eq!(editor.cursor(), 10);
+        editor.vertical(-2, true);
+        assert_eq!(editor.cursor(), 2);
+        assert_eq!(editor.selected_text(), Some("llo\nhi\n日"));
+        // There is no line above the first
+        editor.vertical(-1, false);
+        assert_eq!(editor.cursor(), 2);
+    }
+}
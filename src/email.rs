@@ -0,0 +1,386 @@
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{Email, SmtpSecurity};
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+// Email an export on a background thread so a slow mail server doesn't freeze the UI.
+// The receiver yields the outcome once the message is handed over.
+pub fn send_export(config: &Email, period: &str, path: PathBuf) -> Receiver<Result<(), String>> {
+    let (tx, rx) = mpsc::channel();
+    let (config, period) = (config.clone(), period.to_string());
+    std::thread::spawn(move || {
+        let result = send(&config, &period, &path).map_err(|e| format!("{:#}", e));
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+// Send the file at `path` as an attachment, through `smtp.host` when it's set and
+// the sendmail command otherwise. `period` fills `{date}` in the subject.
+pub fn send(config: &Email, period: &str, path: &Path) -> Result<()> {
+    if config.to.is_empty() {
+        bail!("no recipients, set email.to in the config");
+    }
+    let message = message(config, period, path)?;
+    if config.smtp.host.trim().is_empty() {
+        sendmail(&config.sendmail, &message)
+    } else {
+        smtp(config, &message)
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => "text/csv",
+        Some("ods") => "application/vnd.oasis.opendocument.spreadsheet",
+        Some("html") => "text/html",
+        Some("zip") => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+// Header text as-is when it's ASCII, and as RFC 2047 encoded words otherwise, each
+// short enough to stay within the 75 characters an encoded word is allowed
+fn header_text(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in text.chars() {
+        if chunk.len() + c.len_utf8() > 45 {
+            words.push(std::mem::take(&mut chunk));
+        }
+        chunk.push(c);
+    }
+    words.push(chunk);
+    words
+        .iter()
+        .map(|word| format!("=?utf-8?B?{}?=", STANDARD.encode(word)))
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+// A multipart message with a line of text and the export attached, lines ending in CRLF
+fn message(config: &Email, period: &str, path: &Path) -> Result<String> {
+    let file = fs::read(path).with_context(|| format!("Could not read {}", path.display()))?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().replace('"', ""))
+        .unwrap_or_else(|| "export".to_string());
+    let boundary = format!(
+        "slothtime-{}",
+        chrono::Local::now()
+            .timestamp_nanos_opt()
+            .unwrap_or_default()
+    );
+
+    let mut headers = Vec::new();
+    if !config.from.trim().is_empty() {
+        headers.push(format!("From: {}", config.from.trim()));
+    }
+    headers.push(format!("To: {}", config.to.join(", ")));
+    headers.push(format!(
+        "Subject: {}",
+        header_text(&config.subject.replace("{date}", period))
+    ));
+    headers.push(format!("Date: {}", chrono::Local::now().to_rfc2822()));
+    headers.push("MIME-Version: 1.0".to_string());
+    headers.push(format!(
+        "Content-Type: multipart/mixed; boundary=\"{}\"",
+        boundary
+    ));
+
+    let encoded = STANDARD.encode(&file);
+    // Base64 lines of 76 characters, as MIME asks for
+    let attachment: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(76)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+
+    let mut lines = headers;
+    lines.extend([
+        String::new(),
+        format!("--{}", boundary),
+        "Content-Type: text/plain; charset=utf-8".to_string(),
+        String::new(),
+        format!("Timesheet for {}, exported by Slothtime.", period),
+        String::new(),
+        format!("--{}", boundary),
+        format!("Content-Type: {}; name=\"{}\"", content_type(path), name),
+        "Content-Transfer-Encoding: base64".to_string(),
+        format!("Content-Disposition: attachment; filename=\"{}\"", name),
+        String::new(),
+    ]);
+    lines.extend(attachment.into_iter().map(str::to_string));
+    lines.push(format!("--{}--", boundary));
+    Ok(lines.join("\r\n") + "\r\n")
+}
+
+// Pipe the message to e.g. `sendmail -t`, which takes the recipients from its headers
+// and expects local line endings
+fn sendmail(command: &str, message: &str) -> Result<()> {
+    let mut parts = command.split_whitespace();
+    let program = parts.next().unwrap_or("sendmail");
+    let mut child = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Could not start {}", program))?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("{} has no input", program))?
+        .write_all(message.replace("\r\n", "\n").as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            stderr.trim()
+        );
+    }
+    Ok(())
+}
+
+// The connection to the mail server, encrypted once TLS starts
+enum Stream {
+    Plain(TcpStream),
+    Tls(Box<rustls::StreamOwned<rustls::ClientConnection, TcpStream>>),
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Stream::Plain(stream) => stream.read(buf),
+            Stream::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Stream::Plain(stream) => stream.write(buf),
+            Stream::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Stream::Plain(stream) => stream.flush(),
+            Stream::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+impl Stream {
+    fn into_tls(self, host: &str) -> Result<Self> {
+        let tcp = match self {
+            Stream::Plain(tcp) => tcp,
+            tls => return Ok(tls),
+        };
+        let roots = rustls::RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        let tls_config = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+        let name = rustls::pki_types::ServerName::try_from(host.to_string())?;
+        let connection = rustls::ClientConnection::new(Arc::new(tls_config), name)?;
+        Ok(Stream::Tls(Box::new(rustls::StreamOwned::new(
+            connection, tcp,
+        ))))
+    }
+
+    // One reply, which may span several lines, e.g. `250-SIZE` ... `250 OK`
+    fn reply(&mut self) -> Result<(u16, String)> {
+        let mut text = String::new();
+        loop {
+            let mut line = Vec::new();
+            let mut byte = [0u8];
+            while !line.ends_with(b"\n") {
+                if self.read(&mut byte)? == 0 {
+                    bail!("the mail server closed the connection");
+                }
+                line.push(byte[0]);
+            }
+            let line = String::from_utf8_lossy(&line).trim_end().to_string();
+            let code = line.get(..3).and_then(|code| code.parse().ok());
+            let Some(code) = code else {
+                bail!("unexpected reply from the mail server: {}", line);
+            };
+            text.push_str(line.get(4..).unwrap_or_default());
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok((code, text));
+            }
+            text.push('\n');
+        }
+    }
+
+    // Send a command and fail unless the reply has the `expected` code
+    fn command(&mut self, command: &str, expected: &[u16]) -> Result<String> {
+        self.write_all(command.as_bytes())?;
+        self.write_all(b"\r\n")?;
+        self.flush()?;
+        self.expect(command.split(' ').next().unwrap_or(command), expected)
+    }
+
+    fn expect(&mut self, step: &str, expected: &[u16]) -> Result<String> {
+        let (code, text) = self.reply()?;
+        if !expected.contains(&code) {
+            bail!("the mail server refused {}: {} {}", step, code, text);
+        }
+        Ok(text)
+    }
+}
+
+fn smtp(config: &Email, message: &str) -> Result<()> {
+    let smtp = &config.smtp;
+    let host = smtp.host.trim();
+    if config.from.trim().is_empty() {
+        bail!("set email.from to send through {}", host);
+    }
+    let address = (host, smtp.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| anyhow!("could not find {}", host))?;
+    let tcp = TcpStream::connect_timeout(&address, TIMEOUT)
+        .with_context(|| format!("Could not connect to {}:{}", host, smtp.port))?;
+    tcp.set_read_timeout(Some(TIMEOUT))?;
+    tcp.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut stream = Stream::Plain(tcp);
+    if smtp.security == SmtpSecurity::Tls {
+        stream = stream.into_tls(host)?;
+    }
+    stream.expect("the connection", &[220])?;
+    stream.command("EHLO slothtime", &[250])?;
+    if smtp.security == SmtpSecurity::StartTls {
+        stream.command("STARTTLS", &[220])?;
+        stream = stream.into_tls(host)?;
+        stream.command("EHLO slothtime", &[250])?;
+    }
+
+    if !smtp.username.is_empty() {
+        let password = if smtp.password.is_empty() {
+            std::env::var("SLOTHTIME_SMTP_PASSWORD").unwrap_or_default()
+        } else {
+            smtp.password.clone()
+        };
+        let credentials = STANDARD.encode(format!("\0{}\0{}", smtp.username, password));
+        stream.command(&format!("AUTH PLAIN {}", credentials), &[235])?;
+    }
+
+    stream.command(&format!("MAIL FROM:<{}>", address_of(&config.from)), &[250])?;
+    for to in &config.to {
+        stream.command(&format!("RCPT TO:<{}>", address_of(to)), &[250, 251])?;
+    }
+    stream.command("DATA", &[354])?;
+    // A line starting with a dot gets another, so it isn't read as the end of the message
+    for line in message.split_inclusive("\r\n") {
+        if line.starts_with('.') {
+            stream.write_all(b".")?;
+        }
+        stream.write_all(line.as_bytes())?;
+    }
+    stream.write_all(b".\r\n")?;
+    stream.flush()?;
+    stream.expect("the message", &[250])?;
+    let _ = stream.command("QUIT", &[221]);
+    Ok(())
+}
+
+// `Name <me@example.com>` or plain `me@example.com` to the bare address
+fn address_of(mailbox: &str) -> &str {
+    match (mailbox.find('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        _ => mailbox.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(name: &str, content: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("slothtime-email-{}-{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn message_attaches_the_export() {
+        let config = Email {
+            to: vec![
+                "Boss <boss@example.com>".to_string(),
+                "hr@example.com".to_string(),
+            ],
+            from: "me@example.com".to_string(),
+            ..Email::default()
+        };
+        let path = export("day.csv", "Task,Hours\nPROJ-1,1.5\n");
+        let message = message(&config, "2025-10-01", &path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(message.contains("From: me@example.com\r\n"));
+        assert!(message.contains("To: Boss <boss@example.com>, hr@example.com\r\n"));
+        assert!(message.contains("Subject: Timesheet 2025-10-01\r\n"));
+        assert!(message.contains("Content-Type: text/csv; name=\""));
+        assert!(message.contains(&STANDARD.encode("Task,Hours\nPROJ-1,1.5\n")));
+        assert!(!message.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn non_ascii_subjects_are_encoded() {
+        assert_eq!(header_text("Timesheet 2025-10-01"), "Timesheet 2025-10-01");
+        let encoded = header_text("Stundenzettel für 2025-10-01");
+        assert_eq!(
+            encoded,
+            format!(
+                "=?utf-8?B?{}?=",
+                STANDARD.encode("Stundenzettel für 2025-10-01")
+            )
+        );
+
+        let long = "é".repeat(40);
+        let encoded = header_text(&long);
+        let words: Vec<&str> = encoded.split("\r\n ").collect();
+        assert_eq!(words.len(), 2);
+        assert!(words.iter().all(|word| word.len() <= 75));
+    }
+
+    #[test]
+    fn addresses_are_taken_from_mailboxes() {
+        assert_eq!(address_of("Me <me@example.com>"), "me@example.com");
+        assert_eq!(address_of("  me@example.com "), "me@example.com");
+        assert_eq!(
+            address_of("\"Doe, Jane\" <jane@example.com>"),
+            "jane@example.com"
+        );
+        assert_eq!(
+            address_of("broken <me@example.com"),
+            "broken <me@example.com"
+        );
+    }
+}
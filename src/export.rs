@@ -1,53 +1,392 @@
 use anyhow::Result;
-use chrono::{Datelike, Local};
-use csv::Writer;
+use chrono::{Datelike, Local, NaiveDate};
 use std::fs;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 
-use crate::config::Config;
-use crate::time_entry::TimeEntry;
+use crate::archive::Day;
+use crate::config::{Config, InvoiceFormat};
+use crate::invoice::Invoice;
+use crate::mapping;
+use crate::paths;
+use crate::plaintext::PlainFormat;
+use crate::sheet::{self, Cell, Saved, Sheet};
+use crate::time_entry::{format_duration, TimeEntry};
+use crate::totals::{self, TaskTotal};
 
-pub fn export_csv(entries: &[TimeEntry], config: &Config) -> Result<()> {
-    let export_dir = shellexpand::tilde(&config.export.path).to_string();
+// Which rows an export should contain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportScope {
+    All,
+    Changed, // Rows never exported or edited since their last export
+    Checked, // Rows ticked with space for review
+}
+
+impl ExportScope {
+    pub fn includes(&self, entry: &TimeEntry) -> bool {
+        if entry.is_entirely_empty() {
+            return false;
+        }
+        match self {
+            ExportScope::All => true,
+            ExportScope::Changed => !entry.is_exported_unchanged(),
+            ExportScope::Checked => entry.checked,
+        }
+    }
+}
+
+// An export of the day written on its own thread, so a slow export folder (e.g. a
+// network share) doesn't freeze the UI. It works on a copy of the entries taken when
+// it started, which edits made in the meantime can't reach.
+pub struct ExportJob {
+    pub scope: ExportScope,
+    pub date: NaiveDate,
+    pub snapshot: Vec<TimeEntry>,
+    pub email: bool, // Email the file once it's written
+    result: Receiver<Result<Saved, String>>,
+}
+
+impl ExportJob {
+    pub fn start(
+        entries: &[TimeEntry],
+        note: &str,
+        date: NaiveDate,
+        config: &Config,
+        scope: ExportScope,
+        passphrase: Option<String>,
+        email: bool,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let snapshot = entries.to_vec();
+        let (entries, note, config) = (snapshot.clone(), note.to_string(), config.clone());
+        std::thread::spawn(move || {
+            let result =
+                export_day(&entries, &note, date, &config, scope).and_then(
+                    |saved| match passphrase {
+                        Some(passphrase) => Ok(Saved {
+                            path: encrypt_to_zip(&saved.path, &passphrase)?,
+                            ..saved
+                        }),
+                        None => Ok(saved),
+                    },
+                );
+            let _ = tx.send(result.map_err(|e| e.to_string()));
+        });
+        Self {
+            scope,
+            date,
+            snapshot,
+            email,
+            result: rx,
+        }
+    }
+
+    // Where the export was written once it's done, None while it's still running
+    pub fn finished(&self) -> Option<Result<Saved, String>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err("the export stopped unexpectedly".to_string()))
+            }
+        }
+    }
+
+    pub fn wait(&self) -> Result<Saved, String> {
+        self.result
+            .recv()
+            .unwrap_or_else(|_| Err("the export stopped unexpectedly".to_string()))
+    }
+}
+
+fn export_dir(config: &Config) -> Result<PathBuf> {
+    let export_dir = paths::expand(&config.export.path);
     fs::create_dir_all(&export_dir)?;
+    Ok(export_dir)
+}
+
+// Write a sheet with its header renamed by the export format's [mappings] table
+fn save(mut sheet: Sheet, config: &Config, stem: &str) -> Result<Saved> {
+    let mapping = mapping::for_destination(config, mapping::export_destination(config));
+    sheet.header = sheet
+        .header
+        .iter()
+        .map(|name| mapping::header(&mapping, name))
+        .collect();
+    sheet::save(&sheet, &export_dir(config)?, stem, &config.export)
+}
 
+const ENTRY_COLUMNS: [&str; 5] = [
+    "Task Number",
+    "Work Code",
+    "Time Entry",
+    "Start Time",
+    "End Time",
+];
+
+// The columns every entry export shares, ending with its duration as HH:MM
+fn entry_cells(entry: &TimeEntry) -> Vec<Cell> {
+    let task_time = entry
+        .calculate_task_time()
+        .unwrap_or_else(|| "00:00".to_string());
+    vec![
+        entry.task_number.as_str().into(),
+        entry.work_code.as_str().into(),
+        entry.time_entry.as_str().into(),
+        entry.start_time.as_str().into(),
+        entry.end_time.as_str().into(),
+        task_time.into(),
+    ]
+}
+
+// The day's entries in `config.export.format`, with the day note above the header
+pub fn export_day(
+    entries: &[TimeEntry],
+    note: &str,
+    date: NaiveDate,
+    config: &Config,
+    scope: ExportScope,
+) -> Result<Saved> {
+    let mapping = mapping::for_destination(config, mapping::export_destination(config));
+    let entries = mapping::map_entries(&mapping, entries);
     let now = Local::now();
-    let month = now.format("%B").to_string(); // Full month name (e.g., "September")
-    let day = now.day().to_string();          // Day without zero padding (e.g., "5")
-    let year = now.format("%Y").to_string();  // 4-digit year (e.g., "2025")
-    let filename = format!("{}_{}_{}_slothtime.csv", month, day, year);
-    let filepath = Path::new(&export_dir).join(filename);
-
-    let mut wtr = Writer::from_path(filepath)?;
-
-    wtr.write_record(&[
-        "Row",
-        "Task Number",
-        "Work Code",
-        "Time Entry",
-        "Start Time",
-        "End Time",
-        "Task Time",
-    ])?;
+    let month = date.format("%B").to_string(); // Full month name (e.g., "September")
+    let day = date.day().to_string(); // Day without zero padding (e.g., "5")
+    let year = date.format("%Y").to_string(); // 4-digit year (e.g., "2025")
+    let stem = match scope {
+        ExportScope::All => format!("{}_{}_{}_slothtime", month, day, year),
+        // Partial exports get their own file so the full day export is not overwritten
+        ExportScope::Changed => format!(
+            "{}_{}_{}_slothtime_changes_{}",
+            month,
+            day,
+            year,
+            now.format("%H%M%S")
+        ),
+        ExportScope::Checked => format!(
+            "{}_{}_{}_slothtime_checked_{}",
+            month,
+            day,
+            year,
+            now.format("%H%M%S")
+        ),
+    };
 
+    let mut header = vec!["Row"];
+    header.extend(ENTRY_COLUMNS);
+    header.push("Task Time");
+    let mut sheet = Sheet::new(&date.format("%Y-%m-%d").to_string(), &header);
+    sheet.title = date.format("Timesheet for %A %-d %B %Y").to_string();
+    sheet.notes.push(note.to_string());
+    let mut minutes = 0;
     for (i, entry) in entries.iter().enumerate() {
-        // Export all rows except entirely empty ones
-        if !entry.is_entirely_empty() {
-            let task_time = entry
-                .calculate_task_time()
-                .unwrap_or_else(|| "00:00".to_string());
-            wtr.write_record(&[
-                (i + 1).to_string(),
-                entry.task_number.clone(),
-                entry.work_code.clone(),
-                entry.time_entry.clone(),
-                entry.start_time.clone(),
-                entry.end_time.clone(),
-                task_time,
-            ])?;
+        // Export all rows in scope, never entirely empty ones
+        if scope.includes(entry) {
+            let mut row = vec![Cell::Count(i + 1)];
+            row.extend(entry_cells(entry));
+            sheet.rows.push(row);
+            minutes += entry.duration_minutes().unwrap_or(0);
+        }
+    }
+    sheet.total = Some(format_duration(minutes));
+    save(sheet, config, &stem)
+}
+
+fn range_stem(prefix: &str, from: NaiveDate, to: NaiveDate) -> String {
+    format!(
+        "{}_{}_to_{}",
+        prefix,
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d")
+    )
+}
+
+fn range_title(from: NaiveDate, to: NaiveDate) -> String {
+    format!("{} to {}", from.format("%-d %B %Y"), to.format("%-d %B %Y"))
+}
+
+// Per-task totals for a date range, named after the range so monthly exports don't collide
+pub fn export_task_totals(
+    totals: &[TaskTotal],
+    config: &Config,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Saved> {
+    let mut sheet = task_totals_sheet(totals);
+    sheet.title = format!("Hours per task, {}", range_title(from, to));
+    sheet.total = Some(format_duration(
+        totals.iter().map(|total| total.minutes).sum(),
+    ));
+    let stem = range_stem("slothtime_totals", from, to);
+    save(sheet, config, &stem)
+}
+
+pub fn task_totals_sheet(totals: &[TaskTotal]) -> Sheet {
+    let mut sheet = Sheet::new("Totals", &["Task Number", "Hours", "Time", "Days"]);
+    for total in totals {
+        sheet.rows.push(vec![
+            total.task_number.as_str().into(),
+            Cell::Hours(total.hours()),
+            format_duration(total.minutes).into(),
+            Cell::Count(total.days),
+        ]);
+    }
+    sheet
+}
+
+// Hours logged in each hour of the day, for `report --by-hour`
+pub fn hourly_sheet(hours: &[i64; 24]) -> Sheet {
+    let mut sheet = Sheet::new("Hours by hour", &["Hour", "Hours", "Time"]);
+    for (hour, minutes) in hours.iter().enumerate() {
+        sheet.rows.push(vec![
+            format!("{:02}:00", hour).into(),
+            Cell::Hours(*minutes as f64 / 60.0),
+            format_duration(*minutes).into(),
+        ]);
+    }
+    sheet
+}
+
+// Hours per task/work code and day with row and column totals, e.g. a weekly timesheet
+pub fn export_grid(days: &[Day], config: &Config, from: NaiveDate, to: NaiveDate) -> Result<Saved> {
+    let mapping = mapping::for_destination(config, mapping::export_destination(config));
+    let (dates, rows) = totals::timesheet_grid(&mapping::map_days(&mapping, days), from, to);
+    let hours = |minutes: i64| Cell::Hours(minutes as f64 / 60.0);
+
+    let dates_header: Vec<String> = dates
+        .iter()
+        .map(|date| date.format("%a %Y-%m-%d").to_string())
+        .collect();
+    let mut header = vec!["Task Number", "Work Code"];
+    header.extend(dates_header.iter().map(String::as_str));
+    header.push("Total");
+    let mut sheet = Sheet::new("Timesheet", &header);
+    sheet.title = format!("Timesheet, {}", range_title(from, to));
+
+    let mut day_totals = vec![0; dates.len()];
+    for row in &rows {
+        let mut record = vec![
+            row.task_number.as_str().into(),
+            row.work_code.as_str().into(),
+        ];
+        for (total, minutes) in day_totals.iter_mut().zip(&row.minutes) {
+            *total += minutes;
+            record.push(hours(*minutes));
         }
+        record.push(hours(row.total()));
+        sheet.rows.push(record);
     }
 
-    wtr.flush()?;
-    Ok(())
+    let mut record = vec!["Total".into(), Cell::from("")];
+    record.extend(day_totals.iter().map(|minutes| hours(*minutes)));
+    record.push(hours(day_totals.iter().sum()));
+    sheet.rows.push(record);
+
+    let stem = range_stem("slothtime_timesheet", from, to);
+    save(sheet, config, &stem)
+}
+
+// Every entry of several days in one file, with a Date column in front
+pub fn export_range(
+    days: &[Day],
+    config: &Config,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Saved> {
+    let mapping = mapping::for_destination(config, mapping::export_destination(config));
+    let days = mapping::map_days(&mapping, days);
+    let mut header = vec!["Date"];
+    header.extend(ENTRY_COLUMNS);
+    header.push("Task Time");
+    let mut sheet = Sheet::new("Entries", &header);
+    sheet.title = format!("Time entries, {}", range_title(from, to));
+    sheet.notes = days
+        .iter()
+        .filter(|day| !day.note.is_empty())
+        .map(|day| format!("{}: {}", day.date.format("%Y-%m-%d"), day.note))
+        .collect();
+    for day in &days {
+        for entry in day.entries.iter().filter(|e| !e.is_entirely_empty()) {
+            let mut row = vec![Cell::from(day.date.format("%Y-%m-%d").to_string())];
+            row.extend(entry_cells(entry));
+            sheet.rows.push(row);
+        }
+    }
+    sheet.total = Some(format_duration(
+        days.iter()
+            .flat_map(|day| &day.entries)
+            .filter_map(TimeEntry::duration_minutes)
+            .sum(),
+    ));
+
+    let stem = range_stem("slothtime", from, to);
+    save(sheet, config, &stem)
+}
+
+// Every entry of several days for a plain text time tracker, e.g. `timew import`
+pub fn export_plaintext(
+    days: &[Day],
+    config: &Config,
+    from: NaiveDate,
+    to: NaiveDate,
+    format: PlainFormat,
+) -> Result<PathBuf> {
+    let path = export_dir(config)?
+        .join(range_stem("slothtime", from, to))
+        .with_extension(format.extension());
+    let days = mapping::map_days(&mapping::for_destination(config, format.name()), days);
+    fs::write(&path, format.write(&days, Local::now().date_naive())?)?;
+    Ok(path)
+}
+
+// The invoice as `invoice_<number>`, characters a file name can't hold replaced. An
+// invoice already written under that name is only replaced with `overwrite`, as it may
+// have been sent.
+pub fn export_invoice(
+    invoice: &Invoice,
+    config: &Config,
+    format: InvoiceFormat,
+    overwrite: bool,
+) -> Result<PathBuf> {
+    let number: String = invoice
+        .number
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path = export_dir(config)?.join(format!("invoice_{}.{}", number, format.extension()));
+    if path.exists() && !overwrite {
+        anyhow::bail!(
+            "{} already exists, pass --number for a new invoice or --force to replace it",
+            path.display()
+        );
+    }
+    fs::write(&path, invoice.write(format))?;
+    Ok(path)
+}
+
+// Replace an exported file with an AES-256 encrypted zip holding it, for mailing
+// timesheets with client details. The plain file is removed afterwards.
+pub fn encrypt_to_zip(path: &Path, passphrase: &str) -> Result<PathBuf> {
+    let zip_path = path.with_extension("zip");
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("slothtime.csv");
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .with_aes_encryption(zip::AesMode::Aes256, passphrase);
+
+    let mut zip = zip::ZipWriter::new(fs::File::create(&zip_path)?);
+    zip.start_file(name, options)?;
+    zip.write_all(&fs::read(path)?)?;
+    zip.finish()?;
+    fs::remove_file(path)?;
+    Ok(zip_path)
 }
@@ -0,0 +1,42 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::process::Command;
+
+// The editor to open descriptions in: $VISUAL, then $EDITOR, then the system's
+// basic one. The value may carry arguments, e.g. `code --wait`.
+fn command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+// Open `text` in the editor through a temporary file and wait for it to close. The
+// newline editors add at the end of the file is dropped again.
+pub fn edit(text: &str, name: &str) -> Result<String> {
+    let path = std::env::temp_dir().join(format!("slothtime-{}-{}.txt", std::process::id(), name));
+    fs::write(&path, text)?;
+
+    let command = command();
+    let mut parts = command.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Could not start {}", program));
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    let status = status?;
+    if !status.success() {
+        bail!(
+            "{} exited with {}, the description is unchanged",
+            program,
+            status
+        );
+    }
+    let edited = edited?.replace("\r\n", "\n");
+    Ok(edited.trim_end_matches('\n').to_string())
+}
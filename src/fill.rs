@@ -0,0 +1,183 @@
+use chrono::NaiveTime;
+
+use crate::time_entry::{self, TimeEntry};
+
+pub const PLACEHOLDER: &str = "Unallocated";
+
+// Part of the working day no entry covers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gap {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Gap {
+    pub fn minutes(&self) -> i64 {
+        (self.end - self.start).num_minutes()
+    }
+}
+
+// Stretches of `day_start` to `day_end` not covered by any entry. Overlapping entries
+// count once, and a running entry covers everything from its start on.
+pub fn gaps(entries: &[TimeEntry], day_start: NaiveTime, day_end: NaiveTime) -> Vec<Gap> {
+    let mut day_end = day_end;
+    if let Some(row) = time_entry::running_entry(entries) {
+        if let Some(start) = TimeEntry::parse_time(&entries[row].start_time) {
+            day_end = day_end.min(start);
+        }
+    }
+    let mut covered: Vec<(NaiveTime, NaiveTime)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let start = TimeEntry::parse_time(&entry.start_time)?;
+            let end = TimeEntry::parse_time(&entry.end_time)?;
+            (end > start).then_some((start, end))
+        })
+        .collect();
+    covered.sort();
+
+    let mut gaps = Vec::new();
+    let mut from = day_start;
+    for (start, end) in covered {
+        if start > from && from < day_end {
+            gaps.push(Gap {
+                start: from,
+                end: start.min(day_end),
+            });
+        }
+        from = from.max(end);
+    }
+    if from < day_end {
+        gaps.push(Gap {
+            start: from,
+            end: day_end,
+        });
+    }
+    gaps
+}
+
+// Add an entry for each gap, placed after the rows that start before it. The task
+// number and work code are left empty so the rows are flagged until filled in.
+// Returns the row of the first placeholder.
+pub fn add_placeholders(entries: &mut Vec<TimeEntry>, gaps: &[Gap]) -> Option<usize> {
+    let mut first = None;
+    for gap in gaps {
+        let row = entries
+            .iter()
+            .rposition(|entry| {
+                TimeEntry::parse_time(&entry.start_time).is_some_and(|start| start <= gap.start)
+            })
+            .map_or(0, |row| row + 1);
+        entries.insert(
+            row,
+            TimeEntry {
+                time_entry: PLACEHOLDER.to_string(),
+                start_time: gap.start.format("%H:%M").to_string(),
+                end_time: gap.end.format("%H:%M").to_string(),
+                ..TimeEntry::new()
+            },
+        );
+        first.get_or_insert(row);
+    }
+    first
+}
+
+// Move the entry's start back over a gap ending where it starts, and its end over a
+// gap starting where it ends. Returns the minutes added.
+pub fn stretch(entry: &mut TimeEntry, gaps: &[Gap]) -> i64 {
+    let mut added = 0;
+    let start = TimeEntry::parse_time(&entry.start_time);
+    let end = TimeEntry::parse_time(&entry.end_time);
+    for gap in gaps {
+        if Some(gap.end) == start {
+            entry.start_time = gap.start.format("%H:%M").to_string();
+            added += gap.minutes();
+        } else if Some(gap.start) == end {
+            entry.end_time = gap.end.format("%H:%M").to_string();
+            added += gap.minutes();
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    fn time(value: &str) -> NaiveTime {
+        TimeEntry::parse_time(value).unwrap()
+    }
+
+    fn gap(start: &str, end: &str) -> Gap {
+        Gap {
+            start: time(start),
+            end: time(end),
+        }
+    }
+
+    #[test]
+    fn gaps_skip_overlapping_entries() {
+        let entries = vec![
+            entry("", "", "10:00", "11:00"),
+            entry("", "", "08:00", "09:30"),
+            entry("", "", "10:30", "12:00"),
+            entry("", "", "13:00", "13:00"),
+            entry("", "", "", ""),
+        ];
+        assert_eq!(
+            gaps(&entries, time("09:00"), time("17:00")),
+            vec![gap("09:30", "10:00"), gap("12:00", "17:00")]
+        );
+        assert_eq!(
+            gaps(&[], time("09:00"), time("17:00")),
+            vec![gap("09:00", "17:00")]
+        );
+    }
+
+    #[test]
+    fn running_entry_covers_the_rest_of_the_day() {
+        let entries = vec![entry("", "", "09:00", "10:00"), entry("", "", "11:00", "")];
+        assert_eq!(
+            gaps(&entries, time("09:00"), time("17:00")),
+            vec![gap("10:00", "11:00")]
+        );
+        let entries = vec![entry("", "", "09:00", "18:00")];
+        assert!(gaps(&entries, time("09:00"), time("17:00")).is_empty());
+    }
+
+    #[test]
+    fn placeholders_go_in_time_order() {
+        let mut entries = vec![
+            entry("", "", "09:00", "10:00"),
+            entry("", "", "11:00", "12:00"),
+        ];
+        let first = add_placeholders(
+            &mut entries,
+            &[gap("08:00", "09:00"), gap("10:00", "11:00")],
+        );
+        assert_eq!(first, Some(0));
+        let starts: Vec<&str> = entries.iter().map(|e| e.start_time.as_str()).collect();
+        assert_eq!(starts, vec!["08:00", "09:00", "10:00", "11:00"]);
+        assert_eq!(entries[2].time_entry, PLACEHOLDER);
+        assert_eq!(entries[2].end_time, "11:00");
+        assert!(entries[2].task_number.is_empty());
+        assert_eq!(add_placeholders(&mut entries, &[]), None);
+    }
+
+    #[test]
+    fn stretch_covers_adjacent_gaps() {
+        let gaps = [
+            gap("09:00", "09:30"),
+            gap("11:00", "11:15"),
+            gap("13:00", "14:00"),
+        ];
+        let mut stretched = entry("", "", "09:30", "11:00");
+        assert_eq!(stretch(&mut stretched, &gaps), 45);
+        assert_eq!(
+            (stretched.start_time.as_str(), stretched.end_time.as_str()),
+            ("09:00", "11:15")
+        );
+        assert_eq!(stretch(&mut entry("", "", "12:00", "12:30"), &gaps), 0);
+    }
+}
@@ -0,0 +1,56 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::archive;
+
+// Looking twice a second is plenty for a mirror and costs a few stats
+const CHECK_EVERY: Duration = Duration::from_millis(500);
+
+// Watches the files the editing Slothtime writes for `--follow`: today's entries, the
+// day's note and lock, and the archive a finished day is moved to
+pub struct FileWatch {
+    files: Vec<PathBuf>,
+    seen: Vec<Option<SystemTime>>,
+    checked: Instant,
+}
+
+impl FileWatch {
+    pub fn new(data_dir: &Path) -> Self {
+        let mut files = vec![data_dir.join("entries.json")];
+        files.extend(archive::day_files(data_dir));
+        let seen = modified(&files);
+        Self {
+            files,
+            seen,
+            checked: Instant::now(),
+        }
+    }
+
+    // True when any of the files was written, created or removed since the last look
+    pub fn changed(&mut self) -> bool {
+        if self.checked.elapsed() < CHECK_EVERY {
+            return false;
+        }
+        self.checked = Instant::now();
+        let now = modified(&self.files);
+        if now == self.seen {
+            return false;
+        }
+        self.seen = now;
+        true
+    }
+
+    // Look again next time even if nothing else changes, after reading a file that
+    // was caught half written
+    pub fn retry(&mut self) {
+        self.seen.clear();
+    }
+}
+
+fn modified(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|file| fs::metadata(file).and_then(|meta| meta.modified()).ok())
+        .collect()
+}
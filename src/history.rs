@@ -0,0 +1,220 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+use crate::archive::{self, Day};
+use crate::time_entry::TimeEntry;
+
+// Archived days are read a page at a time, newest first
+const PAGE_DAYS: usize = 60;
+// Older pages are read until the query has this many more matches than the selection
+const LOOKAHEAD: usize = 50;
+
+// Search over the archived days, for finding what was written about a task before.
+// Days are only read as far back as the query needs, so years of archive open quickly.
+#[derive(Debug, Default)]
+pub struct HistorySearch {
+    query: String,
+    pub selected: usize,
+    data_dir: PathBuf,
+    unread: Vec<NaiveDate>,       // Archived dates not read yet, oldest first
+    days: Vec<Day>,               // Newest first
+    matches: Vec<(usize, usize)>, // Day and row of each entry matching the query, in order
+}
+
+impl HistorySearch {
+    // Archived days up to `until`, with the newest read straight away
+    pub fn open(data_dir: &Path, until: NaiveDate) -> Result<Self> {
+        let mut unread = archive::dates(data_dir)?;
+        unread.retain(|date| *date <= until);
+        let mut search = Self {
+            data_dir: data_dir.to_path_buf(),
+            unread,
+            ..Self::default()
+        };
+        search.read_enough()?;
+        Ok(search)
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    // Entries matching the query, newest day first and in row order within a day
+    pub fn results(&self) -> Vec<(NaiveDate, &TimeEntry)> {
+        self.matches
+            .iter()
+            .map(|&(day, row)| (self.days[day].date, &self.days[day].entries[row]))
+            .collect()
+    }
+
+    pub fn selected_entry(&self) -> Option<(NaiveDate, &TimeEntry)> {
+        let &(day, row) = self.matches.get(self.selected)?;
+        Some((self.days[day].date, &self.days[day].entries[row]))
+    }
+
+    pub fn set_query(&mut self, query: String) -> Result<()> {
+        self.query = query;
+        self.selected = 0;
+        self.matches.clear();
+        self.find_matches(0);
+        self.read_enough()
+    }
+
+    pub fn select_next(&mut self) -> Result<()> {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+        self.read_enough()
+    }
+
+    // Read older pages until there are matches to scroll to, or the archive runs out
+    fn read_enough(&mut self) -> Result<()> {
+        while !self.unread.is_empty() && self.matches.len() < self.selected + LOOKAHEAD {
+            let read = self.days.len();
+            let page = self.unread.len().saturating_sub(PAGE_DAYS);
+            for date in self.unread.drain(page..).rev() {
+                self.days.extend(archive::load_day(&self.data_dir, date)?);
+            }
+            self.find_matches(read);
+        }
+        Ok(())
+    }
+
+    // Add the matches in the days read from index `from` on, so a new page only has its
+    // own days searched
+    fn find_matches(&mut self, from: usize) {
+        for (day, archived) in self.days.iter().enumerate().skip(from) {
+            for (row, entry) in archived.entries.iter().enumerate() {
+                if entry.matches(&self.query) {
+                    self.matches.push((day, row));
+                }
+            }
+        }
+    }
+
+    pub fn day_count(&self) -> usize {
+        self.days.len()
+    }
+
+    // Archived days the query hasn't needed to read yet
+    pub fn unread_count(&self) -> usize {
+        self.unread.len()
+    }
+}
+
+// Descriptions written for each task number, newest first without repeats, so the
+// same phrasing can be offered when the task number comes up again
+#[derive(Debug, Default)]
+pub struct TaskDescriptions {
+    by_task: HashMap<String, Vec<String>>,
+}
+
+impl TaskDescriptions {
+    // Read on a background thread, newest day first and one day at a time, so a long
+    // archive neither holds up starting nor sits in memory. The receiver yields them
+    // once every day up to `until` is read.
+    pub fn load(data_dir: &Path, until: NaiveDate) -> Receiver<Result<TaskDescriptions, String>> {
+        let (tx, rx) = mpsc::channel();
+        let data_dir = data_dir.to_path_buf();
+        std::thread::spawn(move || {
+            let result = Self::read(&data_dir, until).map_err(|e| format!("{:#}", e));
+            let _ = tx.send(result);
+        });
+        rx
+    }
+
+    fn read(data_dir: &Path, until: NaiveDate) -> Result<Self> {
+        let mut descriptions = Self::default();
+        for date in archive::dates(data_dir)?.into_iter().rev() {
+            if date > until {
+                continue;
+            }
+            if let Some(day) = archive::load_day(data_dir, date)? {
+                descriptions.add(&day);
+            }
+        }
+        Ok(descriptions)
+    }
+
+    // A day older than the ones added before it
+    fn add(&mut self, day: &Day) {
+        for entry in day.entries.iter().rev() {
+            let description = entry.time_entry.trim();
+            if description.is_empty() || entry.task_number.trim().is_empty() {
+                continue;
+            }
+            let descriptions = self.by_task.entry(key(&entry.task_number)).or_default();
+            if !descriptions.iter().any(|d| d == description) {
+                descriptions.push(description.to_string());
+            }
+        }
+    }
+
+    // Up to `limit` descriptions for `task`, today's rows (`today`) ahead of the archive's
+    pub fn for_task(&self, task: &str, today: &[&TimeEntry], limit: usize) -> Vec<String> {
+        let mut descriptions: Vec<String> = Vec::new();
+        let today = today
+            .iter()
+            .rev()
+            .filter(|entry| key(&entry.task_number) == key(task))
+            .map(|entry| entry.time_entry.trim().to_string());
+        let archived = self.by_task.get(&key(task)).into_iter().flatten().cloned();
+        for description in today.chain(archived) {
+            if descriptions.len() == limit {
+                break;
+            }
+            if !description.is_empty() && !descriptions.contains(&description) {
+                descriptions.push(description);
+            }
+        }
+        descriptions
+    }
+}
+
+// Task numbers match regardless of case and surrounding spaces
+fn key(task: &str) -> String {
+    task.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    #[test]
+    fn results_follow_the_query_newest_day_first() {
+        let dir = std::env::temp_dir().join(format!("slothtime-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let first = NaiveDate::from_ymd_opt(2025, 9, 29).unwrap();
+        for (days, description) in [(0, "Review login"), (1, "Fix login"), (2, "Standup")] {
+            let day = Day {
+                date: first + chrono::Duration::days(days),
+                entries: vec![
+                    entry("PROJ-1", "", "", "").described(description),
+                    TimeEntry::new(),
+                ],
+                note: String::new(),
+            };
+            archive::save_day(&dir, &day).unwrap();
+        }
+
+        let mut search = HistorySearch::open(&dir, first + chrono::Duration::days(1)).unwrap();
+        assert_eq!(search.day_count(), 2);
+        assert_eq!(search.results().len(), 2);
+        search.set_query("LOGIN".to_string()).unwrap();
+        let dates: Vec<NaiveDate> = search.results().iter().map(|(date, _)| *date).collect();
+        assert_eq!(dates, [first + chrono::Duration::days(1), first]);
+        search.select_next().unwrap();
+        assert_eq!(
+            search.selected_entry().unwrap().1.time_entry,
+            "Review login"
+        );
+        search.set_query("standup".to_string()).unwrap();
+        assert!(search.results().is_empty());
+        assert_eq!(search.selected_entry().map(|(date, _)| date), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
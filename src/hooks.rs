@@ -0,0 +1,47 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use crate::time_entry::TimeEntry;
+
+// JSON body posted to hooks.webhook_url after an export
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    pub date: NaiveDate,
+    pub entry_count: usize,
+    pub total_hours: f64,
+    pub path: String,
+}
+
+impl ExportSummary {
+    pub fn new(date: NaiveDate, entries: &[&TimeEntry], path: &Path) -> Self {
+        let minutes: i64 = entries.iter().filter_map(|e| e.duration_minutes()).sum();
+        Self {
+            date,
+            entry_count: entries.len(),
+            total_hours: (minutes as f64 / 60.0 * 100.0).round() / 100.0,
+            path: path.display().to_string(),
+        }
+    }
+}
+
+// POST the summary on a background thread so a slow endpoint doesn't freeze the UI.
+// The receiver yields the outcome once the request finishes.
+pub fn notify_export(url: &str, summary: ExportSummary) -> Receiver<Result<(), String>> {
+    let (tx, rx) = mpsc::channel();
+    let url = url.to_string();
+    std::thread::spawn(move || {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(10))
+            .build();
+        let result = agent
+            .post(&url)
+            .send_json(&summary)
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+        let _ = tx.send(result);
+    });
+    rx
+}
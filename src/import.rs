@@ -0,0 +1,412 @@
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use std::collections::HashMap;
+use std::fs;
+
+use crate::paths;
+use crate::time_entry::TimeEntry;
+
+struct Meeting {
+    summary: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+}
+
+// Read meetings scheduled on `date` from an .ics file and turn them into entries.
+// Recurring events (RRULE) are not expanded, only their first occurrence is seen.
+pub fn import_ics(path: &str, date: NaiveDate, work_code: &str) -> Result<Vec<TimeEntry>> {
+    let content = fs::read_to_string(paths::expand(path))?;
+
+    let mut meetings: Vec<Meeting> = parse_events(&content)
+        .into_iter()
+        .filter(|m| m.start.date() == date)
+        .collect();
+    meetings.sort_by_key(|m| m.start);
+
+    let entries = meetings
+        .into_iter()
+        .map(|m| {
+            let mut entry = TimeEntry::new();
+            entry.work_code = work_code.to_string();
+            entry.time_entry = m.summary;
+            entry.start_time = m.start.format("%H:%M").to_string();
+            entry.end_time = m.end.format("%H:%M").to_string();
+            entry
+        })
+        .collect();
+    Ok(entries)
+}
+
+fn parse_events(content: &str) -> Vec<Meeting> {
+    let lines = unfold_lines(content);
+    let timezones = parse_timezones(&lines);
+    let mut meetings = Vec::new();
+    let mut in_event = false;
+    let mut summary = String::new();
+    let mut start = None;
+    let mut end = None;
+
+    for line in &lines {
+        let Some((name, params, value)) = split_property(line) else {
+            continue;
+        };
+        match name.as_str() {
+            "BEGIN" if value == "VEVENT" => {
+                in_event = true;
+                summary.clear();
+                start = None;
+                end = None;
+            }
+            "END" if value == "VEVENT" => {
+                in_event = false;
+                // All-day events have no time range and are skipped
+                if let (Some(s), Some(e)) = (start, end) {
+                    meetings.push(Meeting {
+                        summary: summary.clone(),
+                        start: s,
+                        end: e,
+                    });
+                }
+            }
+            "SUMMARY" if in_event => summary = unescape(value),
+            "DTSTART" if in_event => start = parse_datetime(params, value, &timezones),
+            "DTEND" if in_event => end = parse_datetime(params, value, &timezones),
+            _ => {}
+        }
+    }
+    meetings
+}
+
+// One STANDARD or DAYLIGHT part of a VTIMEZONE: from `start`, and again every year
+// when it has a `rule`, the zone's times are `offset` seconds ahead of UTC
+struct Observance {
+    start: NaiveDateTime,
+    offset: i64,
+    rule: Option<YearlyRule>,
+}
+
+// `RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU`, the last Sunday of March
+struct YearlyRule {
+    month: u32,
+    week: i32, // 1 for the first, -1 for the last
+    weekday: Weekday,
+    until: Option<NaiveDateTime>,
+}
+
+impl Observance {
+    // When this observance last began at or before `time`, in the zone's own time
+    fn last_onset(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let Some(rule) = &self.rule else {
+            return (self.start <= time).then_some(self.start);
+        };
+        [time.year(), time.year() - 1]
+            .into_iter()
+            .filter_map(|year| {
+                let day = nth_weekday(year, rule.month, rule.week, rule.weekday)?;
+                Some(day.and_time(self.start.time()))
+            })
+            .find(|onset| *onset <= time && *onset >= self.start)
+            .filter(|onset| rule.until.is_none_or(|until| *onset <= until))
+    }
+}
+
+// The `week`th `weekday` of a month, counting from its end when `week` is negative
+fn nth_weekday(year: i32, month: u32, week: i32, weekday: Weekday) -> Option<NaiveDate> {
+    if week > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, week as u8)
+    } else {
+        let next_month = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        let last = next_month.pred_opt()?;
+        let back = (last.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        let day = last - Duration::days(i64::from(back) + 7 * i64::from(-week - 1));
+        (day.month() == month).then_some(day)
+    }
+}
+
+// The VTIMEZONE definitions in the file by TZID, which is how Outlook and Google
+// Calendar say what `DTSTART;TZID=...` times mean
+fn parse_timezones(lines: &[String]) -> HashMap<String, Vec<Observance>> {
+    let mut timezones: HashMap<String, Vec<Observance>> = HashMap::new();
+    let mut tzid = None;
+    let mut observance: Option<(Option<NaiveDateTime>, Option<i64>, Option<YearlyRule>)> = None;
+    for line in lines {
+        let Some((name, _, value)) = split_property(line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", "VTIMEZONE") => tzid = None,
+            ("TZID", _) if observance.is_none() => tzid = Some(value.to_string()),
+            ("BEGIN", "STANDARD" | "DAYLIGHT") => observance = Some((None, None, None)),
+            ("DTSTART", _) => {
+                if let Some(observance) = &mut observance {
+                    observance.0 = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok();
+                }
+            }
+            ("TZOFFSETTO", _) => {
+                if let Some(observance) = &mut observance {
+                    observance.1 = parse_offset(value);
+                }
+            }
+            ("RRULE", _) => {
+                if let Some(observance) = &mut observance {
+                    observance.2 = parse_yearly_rule(value);
+                }
+            }
+            ("END", "STANDARD" | "DAYLIGHT") => {
+                if let (Some(tzid), Some((Some(start), Some(offset), rule))) =
+                    (&tzid, observance.take())
+                {
+                    timezones.entry(tzid.clone()).or_default().push(Observance {
+                        start,
+                        offset,
+                        rule,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    timezones
+}
+
+// `+0200`, `-0500` or `+053000` to seconds
+fn parse_offset(value: &str) -> Option<i64> {
+    let sign = match value.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits = &value[1..];
+    let hours: i64 = digits.get(..2)?.parse().ok()?;
+    let minutes: i64 = digits.get(2..4)?.parse().ok()?;
+    let seconds: i64 = digits.get(4..6).map_or(Some(0), |s| s.parse().ok())?;
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+fn parse_yearly_rule(value: &str) -> Option<YearlyRule> {
+    let parts: HashMap<&str, &str> = value
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .collect();
+    if parts.get("FREQ") != Some(&"YEARLY") {
+        return None;
+    }
+    let month = parts.get("BYMONTH")?.parse().ok()?;
+    let byday = parts.get("BYDAY")?;
+    let split = byday.len().checked_sub(2)?;
+    let (week, day) = byday.split_at(split);
+    let weekday = match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let until = parts.get("UNTIL").and_then(|until| {
+        NaiveDateTime::parse_from_str(until.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()
+    });
+    Some(YearlyRule {
+        month,
+        week: week.trim_start_matches('+').parse().ok()?,
+        weekday,
+        until,
+    })
+}
+
+// Long lines are folded onto continuation lines starting with a space or tab
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if raw.starts_with(' ') || raw.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&raw[1..]);
+                continue;
+            }
+        }
+        lines.push(raw.to_string());
+    }
+    lines
+}
+
+// Split `NAME;PARAM=x:VALUE` into its name, parameters and value
+fn split_property(line: &str) -> Option<(String, &str, &str)> {
+    let colon = line.find(':')?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let (name, params) = match head.find(';') {
+        Some(i) => (&head[..i], &head[i + 1..]),
+        None => (head, ""),
+    };
+    Some((name.to_ascii_uppercase(), params, value))
+}
+
+fn parse_datetime(
+    params: &str,
+    value: &str,
+    timezones: &HashMap<String, Vec<Observance>>,
+) -> Option<NaiveDateTime> {
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        return None;
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(
+            Utc.from_utc_datetime(&naive)
+                .with_timezone(&Local)
+                .naive_local(),
+        );
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let tzid = params
+        .split(';')
+        .find_map(|param| param.strip_prefix("TZID="))
+        .map(|tzid| tzid.trim_matches('"'));
+    // The observance that began last is the one in effect. Floating times, and zones the
+    // file doesn't define, are read as local time.
+    let offset = tzid
+        .and_then(|tzid| timezones.get(tzid))
+        .and_then(|observances| {
+            observances
+                .iter()
+                .filter_map(|observance| Some((observance.last_onset(time)?, observance.offset)))
+                .max_by_key(|(onset, _)| *onset)
+                .map(|(_, offset)| offset)
+        });
+    match offset {
+        Some(offset) => {
+            let utc = time - Duration::seconds(offset);
+            Some(
+                Utc.from_utc_datetime(&utc)
+                    .with_timezone(&Local)
+                    .naive_local(),
+            )
+        }
+        None => Some(time),
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push(' '),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    // What a UTC time reads as on this machine's clock
+    fn local(utc: &str) -> NaiveDateTime {
+        Utc.from_utc_datetime(&at(utc))
+            .with_timezone(&Local)
+            .naive_local()
+    }
+
+    const NEW_YORK: &str = "BEGIN:VTIMEZONE\r
+TZID:America/New_York\r
+BEGIN:DAYLIGHT\r
+DTSTART:20070311T020000\r
+TZOFFSETFROM:-0500\r
+TZOFFSETTO:-0400\r
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r
+END:DAYLIGHT\r
+BEGIN:STANDARD\r
+DTSTART:20071104T020000\r
+TZOFFSETFROM:-0400\r
+TZOFFSETTO:-0500\r
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r
+END:STANDARD\r
+END:VTIMEZONE\r
+";
+
+    fn event(start: &str, end: &str) -> String {
+        format!(
+            "BEGIN:VEVENT\r\nSUMMARY:Standup\r\n{}\r\n{}\r\nEND:VEVENT\r\n",
+            start, end
+        )
+    }
+
+    #[test]
+    fn utc_times_are_read_as_local() {
+        let meetings = parse_events(&event("DTSTART:20251001T130000Z", "DTEND:20251001T133000Z"));
+        assert_eq!(meetings[0].start, local("2025-10-01 13:00"));
+        assert_eq!(meetings[0].end, local("2025-10-01 13:30"));
+    }
+
+    #[test]
+    fn tzid_times_are_converted_with_the_files_timezone() {
+        let content = format!(
+            "BEGIN:VCALENDAR\r\n{}{}{}END:VCALENDAR\r\n",
+            NEW_YORK,
+            event(
+                "DTSTART;TZID=America/New_York:20250701T090000",
+                "DTEND;TZID=\"America/New_York\":20250701T093000"
+            ),
+            event(
+                "DTSTART;TZID=America/New_York:20250115T090000",
+                "DTEND;TZID=America/New_York:20250115T100000"
+            ),
+        );
+        let meetings = parse_events(&content);
+        assert_eq!(meetings[0].start, local("2025-07-01 13:00"));
+        assert_eq!(meetings[0].end, local("2025-07-01 13:30"));
+        assert_eq!(meetings[1].start, local("2025-01-15 14:00"));
+    }
+
+    #[test]
+    fn floating_and_unknown_zones_are_local_time() {
+        let content = event(
+            "DTSTART;TZID=Nowhere/Else:20251001T090000",
+            "DTEND:20251001T100000",
+        );
+        let meetings = parse_events(&content);
+        assert_eq!(meetings[0].start, at("2025-10-01 09:00"));
+        assert_eq!(meetings[0].end, at("2025-10-01 10:00"));
+    }
+
+    #[test]
+    fn all_day_events_are_skipped() {
+        let content = event("DTSTART;VALUE=DATE:20251001", "DTEND;VALUE=DATE:20251002");
+        assert!(parse_events(&content).is_empty());
+    }
+
+    #[test]
+    fn folded_and_escaped_summaries_are_read_whole() {
+        let content = "BEGIN:VEVENT\r\nSUMMARY:Planning\\, Q4\\nro\r\n admap\r\nDTSTART:20251001T090000\r\nDTEND:20251001T100000\r\nEND:VEVENT\r\n";
+        assert_eq!(parse_events(content)[0].summary, "Planning, Q4 roadmap");
+    }
+
+    #[test]
+    fn yearly_rules_pick_the_right_day() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        assert_eq!(nth_weekday(2025, 3, 2, Weekday::Sun), day(2025, 3, 9));
+        assert_eq!(nth_weekday(2025, 3, -1, Weekday::Sun), day(2025, 3, 30));
+        assert_eq!(nth_weekday(2025, 10, -1, Weekday::Sun), day(2025, 10, 26));
+        assert_eq!(nth_weekday(2025, 12, -1, Weekday::Wed), day(2025, 12, 31));
+        assert_eq!(parse_offset("+0530"), Some(19800));
+        assert_eq!(parse_offset("-0500"), Some(-18000));
+        assert_eq!(parse_offset("0500"), None);
+    }
+}
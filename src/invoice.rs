@@ -0,0 +1,487 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+use crate::archive::Day;
+use crate::config::{self, Config, InvoiceFormat};
+use crate::sheet::{self, escape};
+
+// The time on one task number and work code over the invoiced range
+#[derive(Debug)]
+pub struct LineItem {
+    pub task_number: String,
+    pub work_code: String,
+    pub descriptions: Vec<String>, // Each distinct one once, in the order logged
+    pub minutes: i64,
+    pub rate: i64, // Cents per hour
+}
+
+impl LineItem {
+    pub fn hours(&self) -> f64 {
+        self.minutes as f64 / 60.0
+    }
+
+    // In cents, rounded once per line so the lines add up to the subtotal shown
+    pub fn amount(&self) -> i64 {
+        divide_rounded(self.minutes * self.rate, 60)
+    }
+
+    pub fn description(&self) -> String {
+        self.descriptions.join("; ")
+    }
+}
+
+pub struct Invoice<'a> {
+    pub number: String,
+    pub date: NaiveDate,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub items: Vec<LineItem>,
+    config: &'a Config,
+}
+
+impl<'a> Invoice<'a> {
+    pub fn new(
+        days: &[Day],
+        config: &'a Config,
+        number: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Self> {
+        let items = line_items(days, &config.invoice)?;
+        if items.is_empty() {
+            anyhow::bail!("no finished entries to invoice from {} to {}", from, to);
+        }
+        Ok(Self {
+            number: number.to_string(),
+            date: chrono::Local::now().date_naive(),
+            from,
+            to,
+            items,
+            config,
+        })
+    }
+
+    // Cents, like the tax and total
+    pub fn subtotal(&self) -> i64 {
+        self.items.iter().map(LineItem::amount).sum()
+    }
+
+    pub fn tax(&self) -> i64 {
+        tax_on(self.subtotal(), self.config.invoice.tax_rate)
+    }
+
+    pub fn total(&self) -> i64 {
+        self.subtotal() + self.tax()
+    }
+
+    fn due(&self) -> Option<NaiveDate> {
+        let days = self.config.invoice.due_days;
+        (days > 0).then(|| self.date + chrono::Duration::days(days.into()))
+    }
+
+    pub fn money(&self, cents: i64) -> String {
+        format!("{}{}", self.config.invoice.currency, thousands(cents))
+    }
+
+    fn period(&self) -> String {
+        if self.from == self.to {
+            self.from.format("%-d %B %Y").to_string()
+        } else {
+            format!(
+                "{} to {}",
+                self.from.format("%-d %B %Y"),
+                self.to.format("%-d %B %Y")
+            )
+        }
+    }
+
+    // Label and amount of the lines under the table, tax left out when there is none
+    fn sums(&self) -> Vec<(String, String)> {
+        let settings = &self.config.invoice;
+        let mut sums = vec![("Subtotal".to_string(), self.money(self.subtotal()))];
+        if settings.tax_rate > 0.0 {
+            sums.push((
+                format!("{} {}%", settings.tax_name, settings.tax_rate),
+                self.money(self.tax()),
+            ));
+        }
+        sums.push(("Total".to_string(), self.money(self.total())));
+        sums
+    }
+
+    pub fn write(&self, format: InvoiceFormat) -> String {
+        match format {
+            InvoiceFormat::Html => self.html(),
+            InvoiceFormat::Markdown => self.markdown(),
+        }
+    }
+
+    fn markdown(&self) -> String {
+        let settings = &self.config.invoice;
+        let export = &self.config.export;
+        let mut out = format!("# Invoice {}\n\n", self.number);
+        let mut sender: Vec<String> = Vec::new();
+        if !export.company.is_empty() {
+            sender.push(format!("**{}**", export.company));
+        }
+        if !export.name.is_empty() {
+            sender.push(export.name.clone());
+        }
+        sender.extend(settings.address.lines().map(str::to_string));
+        if !sender.is_empty() {
+            // Two spaces end a line without starting a paragraph
+            out.push_str(&format!("{}\n\n", sender.join("  \n")));
+        }
+        if !settings.client.trim().is_empty() {
+            let client: Vec<&str> = settings.client.lines().collect();
+            out.push_str(&format!("**Bill to**  \n{}\n\n", client.join("  \n")));
+        }
+        out.push_str(&format!(
+            "Invoice date: {}  \n",
+            self.date.format("%Y-%m-%d")
+        ));
+        if let Some(due) = self.due() {
+            out.push_str(&format!("Due date: {}  \n", due.format("%Y-%m-%d")));
+        }
+        out.push_str(&format!("Period: {}\n\n", self.period()));
+        out.push_str("| Task Number | Work Code | Description | Hours | Rate | Amount |\n");
+        out.push_str("|---|---|---|---:|---:|---:|\n");
+        for item in &self.items {
+            out.push_str(&format!(
+                "| {} | {} | {} | {:.2} | {} | {} |\n",
+                item.task_number.replace('|', "\\|"),
+                item.work_code.replace('|', "\\|"),
+                item.description().replace('|', "\\|"),
+                item.hours(),
+                self.money(item.rate),
+                self.money(item.amount())
+            ));
+        }
+        out.push('\n');
+        let sums: Vec<String> = self
+            .sums()
+            .into_iter()
+            .map(|(label, amount)| format!("**{}:** {}", label, amount))
+            .collect();
+        out.push_str(&format!("{}\n", sums.join("  \n")));
+        if !settings.notes.trim().is_empty() {
+            out.push_str(&format!("\n{}\n", settings.notes.trim_end()));
+        }
+        out
+    }
+
+    fn html(&self) -> String {
+        let settings = &self.config.invoice;
+        let lines = |text: &str| text.lines().map(escape).collect::<Vec<_>>().join("<br>");
+        let title = format!("Invoice {}", self.number);
+        let mut page =
+            String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        page.push_str(&format!("<title>{}</title>\n", escape(&title)));
+        page.push_str(INVOICE_STYLE);
+        page.push_str("</head>\n<body>\n<header>\n");
+        page.push_str(&sheet::letterhead(&self.config.export));
+        if !settings.address.trim().is_empty() {
+            page.push_str(&format!(
+                "<div class=\"address\">{}</div>\n",
+                lines(&settings.address)
+            ));
+        }
+        page.push_str(&format!(
+            "<h1>{}</h1>\n</header>\n<div class=\"parties\">\n",
+            escape(&title)
+        ));
+        if !settings.client.trim().is_empty() {
+            page.push_str(&format!(
+                "<div class=\"client\"><strong>Bill to</strong><br>{}</div>\n",
+                lines(&settings.client)
+            ));
+        }
+        page.push_str("<table class=\"dates\">\n");
+        page.push_str(&format!(
+            "<tr><th>Invoice date</th><td>{}</td></tr>\n",
+            self.date.format("%Y-%m-%d")
+        ));
+        if let Some(due) = self.due() {
+            page.push_str(&format!(
+                "<tr><th>Due date</th><td>{}</td></tr>\n",
+                due.format("%Y-%m-%d")
+            ));
+        }
+        page.push_str(&format!(
+            "<tr><th>Period</th><td>{}</td></tr>\n",
+            escape(&self.period())
+        ));
+        page.push_str("</table>\n</div>\n<table class=\"items\">\n<thead><tr>");
+        page.push_str("<th>Task Number</th><th>Work Code</th><th>Description</th>");
+        page.push_str(
+            "<th class=\"num\">Hours</th><th class=\"num\">Rate</th><th class=\"num\">Amount</th>",
+        );
+        page.push_str("</tr></thead>\n<tbody>\n");
+        for item in &self.items {
+            page.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{:.2}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+                escape(&item.task_number),
+                escape(&item.work_code),
+                escape(&item.description()),
+                item.hours(),
+                escape(&self.money(item.rate)),
+                escape(&self.money(item.amount()))
+            ));
+        }
+        page.push_str("</tbody>\n</table>\n<table class=\"sums\">\n");
+        for (label, amount) in self.sums() {
+            page.push_str(&format!(
+                "<tr><th>{}</th><td class=\"num\">{}</td></tr>\n",
+                escape(&label),
+                escape(&amount)
+            ));
+        }
+        page.push_str("</table>\n");
+        if !settings.notes.trim().is_empty() {
+            page.push_str(&format!(
+                "<p class=\"notes\">{}</p>\n",
+                lines(&settings.notes)
+            ));
+        }
+        page.push_str("</body>\n</html>\n");
+        page
+    }
+}
+
+// Line items of the days' finished entries, by task number and work code. Refuses
+// when a work code has no rate, rather than billing its time at nothing.
+pub fn line_items(days: &[Day], settings: &config::Invoice) -> Result<Vec<LineItem>> {
+    let mut items: BTreeMap<(String, String), LineItem> = BTreeMap::new();
+    let mut unrated: Vec<String> = Vec::new();
+    for entry in days.iter().flat_map(|day| &day.entries) {
+        let Some(minutes) = entry.duration_minutes() else {
+            continue;
+        };
+        let task_number = entry.task_number.trim().to_string();
+        let work_code = entry.work_code.trim().to_string();
+        let rate = to_cents(
+            settings
+                .rates
+                .get(&work_code)
+                .copied()
+                .unwrap_or(settings.rate),
+        );
+        if rate <= 0 && !unrated.contains(&work_code) {
+            unrated.push(work_code.clone());
+        }
+        let item = items
+            .entry((task_number.clone(), work_code.clone()))
+            .or_insert_with(|| LineItem {
+                task_number,
+                work_code,
+                descriptions: Vec::new(),
+                minutes: 0,
+                rate,
+            });
+        item.minutes += minutes;
+        let description = one_line(&entry.time_entry);
+        if !description.is_empty() && !item.descriptions.contains(&description) {
+            item.descriptions.push(description);
+        }
+    }
+    if !unrated.is_empty() {
+        unrated.sort();
+        let codes: Vec<&str> = unrated
+            .iter()
+            .map(|code| {
+                if code.is_empty() {
+                    "(no work code)"
+                } else {
+                    code.as_str()
+                }
+            })
+            .collect();
+        anyhow::bail!(
+            "no invoice was written, set invoice.rate or add these work codes to [invoice.rates]: {}",
+            codes.join(", ")
+        );
+    }
+    Ok(items.into_values().collect())
+}
+
+// A rate from the config in whole cents
+fn to_cents(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
+
+// The rate is taken to hundredths of a percent, e.g. 8.25
+fn tax_on(subtotal: i64, tax_rate: f64) -> i64 {
+    let basis_points = (tax_rate * 100.0).round() as i64;
+    divide_rounded(subtotal * basis_points, 10_000)
+}
+
+// `numerator / denominator` with halves rounded away from zero, as on a receipt
+fn divide_rounded(numerator: i64, denominator: i64) -> i64 {
+    let half = denominator / 2;
+    if numerator < 0 {
+        (numerator - half) / denominator
+    } else {
+        (numerator + half) / denominator
+    }
+}
+
+// e.g. 123456750 cents as "1,234,567.50"
+fn thousands(cents: i64) -> String {
+    let digits = (cents.unsigned_abs() / 100).to_string();
+    let mut grouped = String::new();
+    if cents < 0 {
+        grouped.push('-');
+    }
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}.{:02}", grouped, cents.unsigned_abs() % 100)
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+const INVOICE_STYLE: &str = "<style>
+body { font-family: system-ui, sans-serif; color: #222; margin: 2rem; }
+header { margin-bottom: 1.5rem; }
+.logo { max-height: 4rem; float: right; }
+.company { font-size: 1.2rem; font-weight: bold; }
+.address { color: #555; margin-top: 0.3rem; }
+h1 { font-size: 1.6rem; margin: 1.2rem 0 0.4rem; }
+.parties { display: flex; justify-content: space-between; margin-bottom: 1.5rem; }
+.dates th { text-align: left; padding-right: 1rem; font-weight: normal; color: #555; }
+table.items { border-collapse: collapse; width: 100%; font-size: 0.9rem; }
+.items th, .items td { border-bottom: 1px solid #bbb; padding: 0.4rem 0.5rem; text-align: left; vertical-align: top; }
+.items th { background: #eee; }
+.num { text-align: right !important; font-variant-numeric: tabular-nums; white-space: nowrap; }
+table.sums { margin: 1rem 0 0 auto; }
+.sums th { text-align: right; padding: 0.2rem 1rem; font-weight: normal; }
+.sums tr:last-child { font-weight: bold; font-size: 1.1rem; }
+.notes { margin-top: 2rem; color: #444; }
+@page { margin: 1.5cm; }
+@media print {
+  body { margin: 0; }
+  thead { display: table-header-group; }
+  tr { break-inside: avoid; }
+  .items th { -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+}
+</style>
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::{entry, TimeEntry};
+
+    fn day(date: u32, entries: Vec<TimeEntry>) -> Day {
+        Day {
+            date: NaiveDate::from_ymd_opt(2025, 9, date).unwrap(),
+            entries,
+            note: String::new(),
+        }
+    }
+
+    fn settings() -> config::Invoice {
+        config::Invoice {
+            rate: 100.0,
+            rates: [("MEET".to_string(), 50.0)].into_iter().collect(),
+            ..config::Invoice::default()
+        }
+    }
+
+    #[test]
+    fn items_are_summed_by_task_and_work_code() {
+        let days = [
+            day(
+                1,
+                vec![
+                    entry("T-1", "DEV", "09:00", "10:30").described("Login form"),
+                    entry("T-1", "MEET", "10:30", "11:00").described("Planning"),
+                ],
+            ),
+            day(
+                2,
+                vec![
+                    entry(" T-1 ", "DEV", "09:00", "09:45").described("Login  form"),
+                    entry("T-1", "DEV", "10:00", "10:20").described("Tests"),
+                ],
+            ),
+        ];
+        let items = line_items(&days, &settings()).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            (items[0].work_code.as_str(), items[0].minutes),
+            ("DEV", 155)
+        );
+        assert_eq!(items[0].description(), "Login form; Tests");
+        assert_eq!(items[0].amount(), 25833);
+        assert_eq!((items[1].work_code.as_str(), items[1].rate), ("MEET", 5000));
+        assert_eq!(items[1].amount(), 2500);
+    }
+
+    #[test]
+    fn unfinished_entries_are_not_billed() {
+        let days = [day(
+            1,
+            vec![entry("T-1", "DEV", "09:00", ""), TimeEntry::new()],
+        )];
+        assert!(line_items(&days, &settings()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn work_codes_without_a_rate_are_refused() {
+        let settings = config::Invoice {
+            rate: 0.0,
+            ..settings()
+        };
+        let days = [day(
+            1,
+            vec![
+                entry("T-1", "DEV", "09:00", "10:00"),
+                entry("T-2", "", "10:00", "11:00"),
+                entry("T-3", "MEET", "11:00", "12:00"),
+            ],
+        )];
+        let error = line_items(&days, &settings).unwrap_err().to_string();
+        assert!(error.ends_with(": (no work code), DEV"), "{}", error);
+    }
+
+    #[test]
+    fn halves_round_away_from_zero() {
+        assert_eq!(divide_rounded(155 * 10000, 60), 25833);
+        assert_eq!(divide_rounded(30, 60), 1);
+        assert_eq!(divide_rounded(29, 60), 0);
+        assert_eq!(divide_rounded(-30, 60), -1);
+        assert_eq!(to_cents(99.995), 10000);
+        assert_eq!(to_cents(12.34), 1234);
+    }
+
+    #[test]
+    fn totals_add_up_to_the_cent() {
+        // Three lines of 10 minutes at $100 are $16.67 each, not a third of $50.00
+        let entries = ["T-1", "T-2", "T-3"]
+            .iter()
+            .map(|task| entry(task, "DEV", "09:00", "09:10"))
+            .collect();
+        let items = line_items(&[day(1, entries)], &settings()).unwrap();
+        let subtotal: i64 = items.iter().map(LineItem::amount).sum();
+        assert_eq!(subtotal, 5001);
+        assert_eq!(tax_on(subtotal, 8.25), 413);
+        assert_eq!(tax_on(subtotal, 0.0), 0);
+        assert_eq!(tax_on(200, 7.5), 15);
+    }
+
+    #[test]
+    fn thousands_are_grouped() {
+        assert_eq!(thousands(0), "0.00");
+        assert_eq!(thousands(99999), "999.99");
+        assert_eq!(thousands(123456750), "1,234,567.50");
+        assert_eq!(thousands(10000000), "100,000.00");
+        assert_eq!(thousands(-105), "-1.05");
+    }
+}
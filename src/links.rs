@@ -0,0 +1,103 @@
+use anyhow::{Context, Result};
+use std::process::{Command, Stdio};
+
+use crate::config::Links;
+use crate::time_entry::TimeEntry;
+
+// Punctuation around a URL in prose, e.g. "(see https://example.com/pr/12)."
+const WRAPPING: &[char] = &[
+    '(', ')', '[', ']', '<', '>', '"', '\'', ',', '.', ';', ':', '!', '?',
+];
+
+// An entry's links: the task number's page from links.task_url first, then every
+// http(s) URL in the description, without repeats
+pub fn for_entry(entry: &TimeEntry, links: &Links) -> Vec<String> {
+    let mut found = Vec::new();
+    let task = entry.task_number.trim();
+    if !links.task_url.is_empty() && !task.is_empty() {
+        found.push(links.task_url.replace("{task}", &percent_encode(task)));
+    }
+    for word in entry.time_entry.split_whitespace() {
+        let url = word.trim_matches(WRAPPING);
+        let is_url = url.starts_with("https://") || url.starts_with("http://");
+        if is_url && !found.iter().any(|seen| seen == url) {
+            found.push(url.to_string());
+        }
+    }
+    found
+}
+
+// A task number as a URL path or query part: everything but letters, digits and
+// `-._~` as %XX bytes, so `&`, `#` or spaces can't change the URL
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// Hand the URL to the system's browser without waiting for it
+pub fn open(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        // Not `cmd /C start`, which would run `&` or `|` in the URL as commands
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let mut child = command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Could not open {}", url))?;
+    // Reap it once the browser has the URL, so no zombie is left behind
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    #[test]
+    fn task_number_is_percent_encoded() {
+        let links = Links {
+            task_url: "https://jira.example.com/browse/{task}".to_string(),
+        };
+        let found = for_entry(&entry("A&B calc|x", "", "", ""), &links);
+        assert_eq!(
+            found,
+            vec!["https://jira.example.com/browse/A%26B%20calc%7Cx"]
+        );
+    }
+
+    #[test]
+    fn description_urls_are_unwrapped_and_not_repeated() {
+        let links = Links::default();
+        let found = for_entry(
+            &entry("", "", "", "").described(
+                "see (https://example.com/pr/12). and https://example.com/pr/12, http://a.b",
+            ),
+            &links,
+        );
+        assert_eq!(found, vec!["https://example.com/pr/12", "http://a.b"]);
+    }
+
+    #[test]
+    fn unreserved_characters_are_kept() {
+        assert_eq!(percent_encode("PROJ-1.2_a~"), "PROJ-1.2_a~");
+        assert_eq!(percent_encode("é"), "%C3%A9");
+    }
+}
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+use tracing::Level;
+use tracing_appender::rolling::{self, Rotation};
+
+use crate::config;
+
+// Days of logs kept, one file each
+const LOG_DAYS: usize = 7;
+
+// Logs are shared by every project, under ~/.slothtime/logs
+pub fn log_dir() -> PathBuf {
+    config::data_dir().join("logs")
+}
+
+// Log saves, exports and integrations to a daily file, at debug level with
+// `verbose`. Nothing is printed to the terminal, which the TUI owns.
+pub fn init(verbose: bool) -> Result<()> {
+    fs::create_dir_all(log_dir())?;
+    let appender = rolling::Builder::new()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("slothtime")
+        .filename_suffix("log")
+        .max_log_files(LOG_DAYS)
+        .build(log_dir())?;
+    tracing_subscriber::fmt()
+        .with_writer(appender)
+        .with_max_level(if verbose { Level::DEBUG } else { Level::INFO })
+        .with_target(false)
+        .with_timer(tracing_subscriber::fmt::time::ChronoLocal::new(
+            "%Y-%m-%d %H:%M:%S".to_string(),
+        ))
+        .try_init()
+        .map_err(|e| anyhow::anyhow!(e))?;
+    Ok(())
+}
+
+// The newest log file's last `max` lines, oldest first
+pub fn recent_lines(max: usize) -> Result<(PathBuf, Vec<String>)> {
+    let mut files: Vec<PathBuf> = fs::read_dir(log_dir())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "log"))
+        .collect();
+    // Dated names sort by day
+    files.sort();
+    let Some(newest) = files.pop() else {
+        anyhow::bail!("nothing has been logged yet");
+    };
+    let content = fs::read_to_string(&newest)?;
+    let lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let skip = lines.len().saturating_sub(max);
+    Ok((newest, lines.into_iter().skip(skip).collect()))
+}
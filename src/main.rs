@@ -1,39 +1,431 @@
 use std::io;
 
-use ratatui::backend::CrosstermBackend;
-use ratatui::Terminal;
+use chrono::NaiveDate;
+use clap::{CommandFactory, Parser};
 use crossterm::{
+    event::{DisableBracketedPaste, EnableBracketedPaste},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
 
+mod activitywatch;
 mod app;
-mod ui;
+mod archive;
+mod backup;
+mod budget;
+mod changelog;
+mod checks;
+mod cli;
+mod clock;
+mod coalesce;
+mod columns;
 mod config;
-mod time_entry;
+mod day_summary;
+mod dedupe;
+mod demo;
+mod editor;
+mod email;
 mod export;
+mod external_editor;
+mod fill;
+mod follow;
+mod history;
+mod hooks;
+mod import;
+mod invoice;
+mod links;
+mod logging;
+mod mapping;
+mod markdown;
+mod paths;
+mod plaintext;
+mod repair;
+mod replace;
+mod schema;
+mod serve;
+mod sheet;
+mod snippets;
+mod stats;
+mod status_file;
+mod text;
+mod theme;
+mod time_entry;
+mod totals;
+mod trash;
+mod ui;
+mod upload;
+mod validate;
 
 use app::App;
+use cli::{Cli, Command};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    // Logging is only for diagnosing problems, a read-only home shouldn't stop the app
+    let _ = logging::init(cli.verbose);
+    tracing::debug!("started with {:?}", cli);
+    match cli.command {
+        Some(Command::Projects) => {
+            for project in config::list_projects()? {
+                println!("{}", project);
+            }
+            return Ok(());
+        }
+        Some(Command::Report {
+            from,
+            to,
+            csv,
+            by_hour,
+        }) => {
+            print_report(cli.project.as_deref(), from, to, csv, by_hour)?;
+            return Ok(());
+        }
+        Some(Command::Export {
+            from,
+            to,
+            grid,
+            format,
+            plaintext,
+            email,
+        }) => {
+            export_range(
+                cli.project.as_deref(),
+                from,
+                to,
+                grid,
+                format,
+                plaintext,
+                email,
+            )?;
+            return Ok(());
+        }
+        Some(Command::Invoice {
+            from,
+            to,
+            number,
+            format,
+            force,
+        }) => {
+            write_invoice(cli.project.as_deref(), from, to, number, format, force)?;
+            return Ok(());
+        }
+        Some(Command::Validate { from, to }) => {
+            let problems = print_issues(cli.project.as_deref(), from, to)?;
+            // A pre-submit script can stop on the exit code
+            if problems > 0 {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Serve { addr }) => {
+            let data_dir = match cli.project.as_deref() {
+                Some(name) => config::project_dir(name)?,
+                None => config::data_dir(),
+            };
+            serve::run(&addr, &data_dir, cli.project.as_deref())?;
+            return Ok(());
+        }
+        Some(Command::Completions { shell }) => {
+            let mut command = Cli::command();
+            clap_complete::generate(
+                shell,
+                &mut command,
+                env!("CARGO_PKG_NAME"),
+                &mut io::stdout(),
+            );
+            return Ok(());
+        }
+        Some(Command::Man) => {
+            let command = Cli::command().name(env!("CARGO_PKG_NAME"));
+            clap_mangen::Man::new(command).render(&mut io::stdout())?;
+            return Ok(());
+        }
+        None => {}
+    }
+
+    // create the app before the terminal is taken over, so a problem opening it is
+    // printed normally
+    let mut app = if cli.demo {
+        App::demo()
+    } else if cli.follow {
+        App::follower(cli.project)
+    } else if let Some(date) = cli.date {
+        App::past_day(cli.project, date)
+    } else {
+        App::new(cli.project)
+    }?;
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // create app and run
-    let mut app = App::new()?;
     let res = app.run(&mut terminal);
 
     // cleanup
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableBracketedPaste
+    )?;
 
     if let Err(err) = res {
         println!("{:?}", err);
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// Hours per hour of the day, with a bar scaled to the busiest hour
+fn print_hourly(hours: &[i64; 24], csv: bool) -> anyhow::Result<()> {
+    if csv {
+        return sheet::write_csv(&export::hourly_sheet(hours), io::stdout());
+    }
+    let busiest = hours.iter().copied().max().unwrap_or(0).max(1);
+    println!("{:<6} {:>8} {:>10}", "Hour", "Hours", "Time");
+    for (hour, minutes) in hours.iter().enumerate() {
+        let line = format!(
+            "{:02}:00  {:>8.2} {:>10}  {}",
+            hour,
+            *minutes as f64 / 60.0,
+            time_entry::format_duration(*minutes),
+            "#".repeat((minutes * 40 / busiest) as usize)
+        );
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+// Archived days in the range plus today's current entries, the range defaulting to this month
+fn load_days(
+    project: Option<&str>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> anyhow::Result<(NaiveDate, NaiveDate, Vec<archive::Day>)> {
+    let today = chrono::Local::now().date_naive();
+    let from = from.unwrap_or_else(|| budget::month_start(today));
+    let to = to.unwrap_or(today);
+    let data_dir = match project {
+        Some(name) => config::project_dir(name)?,
+        None => config::data_dir(),
+    };
+    let current = archive::Day {
+        date: today,
+        entries: App::load_entries(&data_dir).unwrap_or_default(),
+        note: archive::load_note(&data_dir),
+    };
+    let (archived, unreadable) = archive::load_range(&data_dir, from, to)?;
+    if let Some(message) = archive::unreadable_message(&unreadable) {
+        eprintln!("{}", message);
+    }
+    Ok((from, to, totals::days_in_range(archived, current, from, to)))
+}
+
+fn export_range(
+    project: Option<&str>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    grid: bool,
+    format: Option<config::ExportFormat>,
+    plaintext: Option<plaintext::PlainFormat>,
+    email: bool,
+) -> anyhow::Result<()> {
+    let mut config = config::Config::load(project)?;
+    if let Some(format) = format {
+        config.export.format = format;
+    }
+    if grid {
+        // A timesheet covers a week unless told otherwise
+        let from = from.unwrap_or_else(|| budget::week_start(chrono::Local::now().date_naive()));
+        let to = to.unwrap_or(from + chrono::Duration::days(6));
+        let (from, to, days) = load_days(project, Some(from), Some(to))?;
+        check_mapping(&config, mapping::export_destination(&config), &days)?;
+        let saved = export::export_grid(&days, &config, from, to)?;
+        report_saved(&saved);
+        let path = saved.path;
+        upload_export(&config, &path)?;
+        return email_export(&config, email, from, to, &path);
+    }
+    let (from, to, days) = load_days(project, from, to)?;
+    let destination =
+        plaintext.map_or(mapping::export_destination(&config), |format| format.name());
+    check_mapping(&config, destination, &days)?;
+    let saved = match plaintext {
+        Some(format) => sheet::Saved {
+            path: export::export_plaintext(&days, &config, from, to, format)?,
+            unmapped: 0,
+        },
+        None => export::export_range(&days, &config, from, to)?,
+    };
+    report_saved(&saved);
+    let path = saved.path;
+    upload_export(&config, &path)?;
+    email_export(&config, email, from, to, &path)
+}
+
+// The path for scripts on stdout, and any characters the encoding lost on stderr
+fn report_saved(saved: &sheet::Saved) {
+    println!("{}", saved.path.display());
+    if saved.unmapped > 0 {
+        eprintln!(
+            "{} character(s) Windows-1252 has no byte for were written as ?",
+            saved.unmapped
+        );
+    }
+}
+
+fn write_invoice(
+    project: Option<&str>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    number: Option<String>,
+    format: Option<config::InvoiceFormat>,
+    force: bool,
+) -> anyhow::Result<()> {
+    let config = config::Config::load(project)?;
+    let (from, to, days) = load_days(project, from, to)?;
+    // A number picked by hand is meant, the month's default may belong to an earlier invoice
+    let overwrite = force || number.is_some();
+    let number = number.unwrap_or_else(|| to.format("%Y-%m").to_string());
+    let invoice = invoice::Invoice::new(&days, &config, &number, from, to)?;
+    let format = format.unwrap_or(config.invoice.format);
+    let path = export::export_invoice(&invoice, &config, format, overwrite)?;
+    println!("{}", path.display());
+    eprintln!(
+        "{} line(s), {:.2} hours, total {}",
+        invoice.items.len(),
+        invoice.items.iter().map(|item| item.hours()).sum::<f64>(),
+        invoice.money(invoice.total())
+    );
+    Ok(())
+}
+
+// Refuse to write IDs the destination wouldn't know
+fn check_mapping(
+    config: &config::Config,
+    destination: &str,
+    days: &[archive::Day],
+) -> anyhow::Result<()> {
+    let unmapped = mapping::unmapped_codes(&mapping::for_destination(config, destination), days);
+    if !unmapped.is_empty() {
+        anyhow::bail!(
+            "nothing was exported, add these work codes to [mappings.{}.work_codes]: {}",
+            destination,
+            unmapped.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn upload_export(config: &config::Config, path: &std::path::Path) -> anyhow::Result<()> {
+    if config.upload.url.trim().is_empty() {
+        return Ok(());
+    }
+    let url = upload::upload(&config.upload, path)?;
+    eprintln!("Uploaded to {}", url);
+    Ok(())
+}
+
+fn email_export(
+    config: &config::Config,
+    email: bool,
+    from: NaiveDate,
+    to: NaiveDate,
+    path: &std::path::Path,
+) -> anyhow::Result<()> {
+    if !email {
+        return Ok(());
+    }
+    let period = if from == to {
+        from.format("%Y-%m-%d").to_string()
+    } else {
+        format!("{} to {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d"))
+    };
+    email::send(&config.email, &period, path)?;
+    eprintln!("Emailed to {}", config.email.to.join(", "));
+    Ok(())
+}
+
+// Print each day's problems and return how many there were. Days default to today.
+fn print_issues(
+    project: Option<&str>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> anyhow::Result<usize> {
+    let config = config::Config::load(project)?;
+    let mut rules = columns::InputRules::new(&config.columns, &config::data_dir())?;
+    for error in rules.fetch_now() {
+        eprintln!("Could not load the {}", error);
+    }
+    let today = chrono::Local::now().date_naive();
+    let from = from.unwrap_or(today);
+    let (_, _, days) = load_days(project, Some(from), Some(to.unwrap_or(from.max(today))))?;
+    let mut problems = 0;
+    let mut rows = 0;
+    for day in &days {
+        rows += day
+            .entries
+            .iter()
+            .filter(|e| !e.is_entirely_empty())
+            .count();
+        for issue in validate::issues(
+            &day.entries,
+            &config.checks,
+            mapping::for_export(&config),
+            &rules,
+        ) {
+            println!("{} row {}: {}", day.date, issue.row + 1, issue.message);
+            problems += 1;
+        }
+    }
+    if problems == 0 {
+        eprintln!("No problems found in {} row(s)", rows);
+    } else {
+        eprintln!("{} problem(s) found", problems);
+    }
+    Ok(problems)
+}
+
+fn print_report(
+    project: Option<&str>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    csv: bool,
+    by_hour: bool,
+) -> anyhow::Result<()> {
+    let (_, _, days) = load_days(project, from, to)?;
+    if by_hour {
+        return print_hourly(&totals::hourly_minutes(&days), csv);
+    }
+    let task_totals = totals::task_totals(&days);
+
+    if csv {
+        return sheet::write_csv(&export::task_totals_sheet(&task_totals), io::stdout());
+    }
+    println!(
+        "{:<20} {:>8} {:>10} {:>5}",
+        "Task Number", "Hours", "Time", "Days"
+    );
+    for total in &task_totals {
+        println!(
+            "{} {:>8.2} {:>10} {:>5}",
+            text::pad(&total.task_number, 20),
+            total.hours(),
+            time_entry::format_duration(total.minutes),
+            total.days
+        );
+    }
+    let minutes: i64 = task_totals.iter().map(|t| t.minutes).sum();
+    println!(
+        "{:<20} {:>8.2} {:>10}",
+        "Total",
+        minutes as f64 / 60.0,
+        time_entry::format_duration(minutes)
+    );
+    Ok(())
+}
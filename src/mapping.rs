@@ -0,0 +1,135 @@
+use std::collections::BTreeSet;
+
+use crate::archive::Day;
+use crate::config::{Config, Mapping};
+use crate::time_entry::TimeEntry;
+
+// The [mappings.<destination>] table, or one that changes nothing
+pub fn for_destination(config: &Config, destination: &str) -> Mapping {
+    config
+        .mappings
+        .get(destination)
+        .cloned()
+        .unwrap_or_default()
+}
+
+// The destination of the day exports, named after the export format
+pub fn export_destination(config: &Config) -> &'static str {
+    config.export.format.extension()
+}
+
+// The day exports' destination and its [mappings] table, when it has one
+pub fn for_export(config: &Config) -> Option<(&'static str, &Mapping)> {
+    let destination = export_destination(config);
+    config
+        .mappings
+        .get(destination)
+        .map(|mapping| (destination, mapping))
+}
+
+// A header renamed by `columns`, keyed by the snake_case of Slothtime's name, e.g.
+// `task_number` for "Task Number"
+pub fn header(mapping: &Mapping, name: &str) -> String {
+    let key = name.to_lowercase().replace(' ', "_");
+    mapping
+        .columns
+        .get(&key)
+        .cloned()
+        .unwrap_or_else(|| name.to_string())
+}
+
+// A work code as the destination knows it, unchanged when it isn't mapped
+pub fn work_code(mapping: &Mapping, code: &str) -> String {
+    mapping
+        .work_codes
+        .get(code.trim())
+        .cloned()
+        .unwrap_or_else(|| code.to_string())
+}
+
+// Once any work codes are mapped every one has to be, so an ID the destination would
+// reject is caught before exporting. Empty work codes are left to the other checks.
+pub fn unmapped(mapping: &Mapping, entry: &TimeEntry) -> bool {
+    let code = entry.work_code.trim();
+    !mapping.work_codes.is_empty() && !code.is_empty() && !mapping.work_codes.contains_key(code)
+}
+
+// The unmapped work codes of the days' entries, sorted, for refusing an export
+pub fn unmapped_codes(mapping: &Mapping, days: &[Day]) -> Vec<String> {
+    days.iter()
+        .flat_map(|day| &day.entries)
+        .filter(|entry| unmapped(mapping, entry))
+        .map(|entry| entry.work_code.trim().to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+// Copies of the entries with their work codes mapped
+pub fn map_entries(mapping: &Mapping, entries: &[TimeEntry]) -> Vec<TimeEntry> {
+    entries
+        .iter()
+        .map(|entry| TimeEntry {
+            work_code: work_code(mapping, &entry.work_code),
+            ..entry.clone()
+        })
+        .collect()
+}
+
+pub fn map_days(mapping: &Mapping, days: &[Day]) -> Vec<Day> {
+    days.iter()
+        .map(|day| Day {
+            entries: map_entries(mapping, &day.entries),
+            ..day.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    fn mapping() -> Mapping {
+        Mapping {
+            columns: HashMap::from([("work_code".to_string(), "Activity".to_string())]),
+            work_codes: HashMap::from([
+                ("DEV".to_string(), "1001".to_string()),
+                ("OPS".to_string(), "1002".to_string()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn headers_and_work_codes_are_renamed() {
+        let mapping = mapping();
+        assert_eq!(header(&mapping, "Work Code"), "Activity");
+        assert_eq!(header(&mapping, "Task Number"), "Task Number");
+        assert_eq!(work_code(&mapping, " DEV "), "1001");
+        assert_eq!(work_code(&mapping, "QA"), "QA");
+        let mapped = map_entries(&mapping, &[entry("", "OPS", "", ""), entry("", "", "", "")]);
+        assert_eq!(
+            (mapped[0].work_code.as_str(), mapped[1].work_code.as_str()),
+            ("1002", "")
+        );
+    }
+
+    #[test]
+    fn every_code_must_be_mapped_once_any_are() {
+        let days = [Day {
+            date: NaiveDate::from_ymd_opt(2025, 10, 8).unwrap(),
+            entries: vec![
+                entry("", "QA", "", ""),
+                entry("", "DEV", "", ""),
+                entry("", " ADMIN", "", ""),
+                entry("", "QA", "", ""),
+                entry("", "", "", ""),
+            ],
+            note: String::new(),
+        }];
+        assert_eq!(unmapped_codes(&mapping(), &days), vec!["ADMIN", "QA"]);
+        assert!(unmapped_codes(&Mapping::default(), &days).is_empty());
+    }
+}
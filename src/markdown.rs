@@ -0,0 +1,168 @@
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::theme::Theme;
+
+// A description as lightweight Markdown for the popup's view mode: `#` headings, `-`,
+// `*` and `+` bullets, `**bold**`, `*italic*` or `_italic_`, and `code` spans.
+// Anything else shows as typed, and editing always shows the raw text.
+pub fn lines(text: &str, theme: &Theme) -> Vec<Line<'static>> {
+    text.lines().map(|line| line_spans(line, theme)).collect()
+}
+
+fn line_spans(line: &str, theme: &Theme) -> Line<'static> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+        let heading = trimmed[hashes..].trim().to_string();
+        return Line::from(Span::styled(
+            heading,
+            theme.heading().add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let mut spans = Vec::new();
+    let bullet = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| trimmed.strip_prefix(bullet));
+    let rest = match bullet {
+        Some(rest) => {
+            spans.push(Span::raw(format!("{}{} ", indent, theme.symbol("•", "*"))));
+            rest
+        }
+        None => {
+            spans.push(Span::raw(indent.to_string()));
+            trimmed
+        }
+    };
+    spans.extend(inline(rest, theme));
+    Line::from(spans)
+}
+
+// Bold, italic and code spans within a line. Markers without a partner later in the
+// line are kept as text, and `_` inside a word (snake_case) isn't a marker.
+fn inline(text: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let (mut bold, mut italic) = (false, false);
+    let mut italic_marker = '*';
+    let mut prev: Option<char> = None;
+    let mut rest = text;
+
+    let style = |bold: bool, italic: bool| {
+        let mut style = Style::default();
+        if bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        if italic {
+            style = style.add_modifier(Modifier::ITALIC);
+        }
+        style
+    };
+
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        let next = after.chars().next();
+        let mut toggled = None;
+        if c == '`' {
+            if let Some(end) = after.find('`') {
+                if !run.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut run), style(bold, italic)));
+                }
+                spans.push(Span::styled(after[..end].to_string(), theme.accent()));
+                prev = Some('`');
+                rest = &after[end + 1..];
+                continue;
+            }
+        } else if rest.starts_with("**") && (bold || rest[2..].contains("**")) {
+            toggled = Some((2, !bold, italic));
+        } else if c == '*' || c == '_' {
+            let word_inside = |ch: Option<char>| ch.is_some_and(char::is_alphanumeric);
+            // An unpartnered `**` is text, not an italic that closes straight away
+            let opens = !italic
+                && !rest.starts_with("**")
+                && next.is_some_and(|n| !n.is_whitespace())
+                && after.contains(c)
+                && (c == '*' || !word_inside(prev));
+            let closes = italic && c == italic_marker && (c == '*' || !word_inside(next));
+            if opens || closes {
+                italic_marker = c;
+                toggled = Some((1, bold, !italic));
+            }
+        }
+        match toggled {
+            Some((len, new_bold, new_italic)) => {
+                if !run.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut run), style(bold, italic)));
+                }
+                bold = new_bold;
+                italic = new_italic;
+                rest = &rest[len..];
+            }
+            None => {
+                run.push(c);
+                rest = after;
+            }
+        }
+        prev = Some(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(run, style(bold, italic)));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Palette;
+
+    // Each span's text, marked ** when bold and _ when italic
+    fn marked(text: &str) -> Vec<String> {
+        let theme = Theme::new(Palette::Default, true);
+        lines(text, &theme)
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| {
+                        let modifier = span.style.add_modifier;
+                        let mut text = span.content.to_string();
+                        if modifier.contains(Modifier::ITALIC) {
+                            text = format!("_{}_", text);
+                        }
+                        if modifier.contains(Modifier::BOLD) {
+                            text = format!("**{}**", text);
+                        }
+                        text
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn headings_and_bullets() {
+        assert_eq!(
+            marked("## Notes \n  - first\n+ second\n#hashtag"),
+            vec!["**Notes**", "  * first", "* second", "#hashtag"]
+        );
+    }
+
+    #[test]
+    fn inline_markers_need_a_partner() {
+        assert_eq!(
+            marked("a **bold** and *italic* or _this_"),
+            vec!["a **bold** and _italic_ or _this_"]
+        );
+        assert_eq!(
+            marked("2 * 3 = 6, **unclosed"),
+            vec!["2 * 3 = 6, **unclosed"]
+        );
+        assert_eq!(marked("***both***"), vec!["**_both_**"]);
+        assert_eq!(marked("fix snake_case_name"), vec!["fix snake_case_name"]);
+        assert_eq!(marked("run `a*b*c` now"), vec!["run a*b*c now"]);
+    }
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// Turn a path from the config into a real one. `~` is the home folder, and
+// `~/Documents` is the system's documents folder, which on Windows can be moved
+// (e.g. into OneDrive) so it isn't always under the home folder. Both `/` and `\`
+// separate folders after a leading `~`.
+pub fn expand(path: &str) -> PathBuf {
+    expand_with(
+        path,
+        dirs::home_dir().as_deref(),
+        dirs::document_dir().as_deref(),
+    )
+}
+
+fn expand_with(path: &str, home: Option<&Path>, documents: Option<&Path>) -> PathBuf {
+    let Some(rest) = path.strip_prefix('~') else {
+        return PathBuf::from(path);
+    };
+    // `~user` forms are left alone
+    if !rest.is_empty() && !rest.starts_with(['/', '\\']) {
+        return PathBuf::from(path);
+    }
+    let mut parts = rest
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty())
+        .peekable();
+    let base = match (parts.peek(), documents) {
+        (Some(first), Some(documents)) if first.eq_ignore_ascii_case("documents") => {
+            parts.next();
+            documents.to_path_buf()
+        }
+        _ => match home {
+            Some(home) => home.to_path_buf(),
+            None => return PathBuf::from(path),
+        },
+    };
+    parts.fold(base, |path, part| path.join(part))
+}
+
+// Write `content` to a temporary file next to `path` and rename it over `path`, so a
+// crash part way through leaves the old file whole instead of cut short
+pub fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp = path.with_file_name(name);
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(content.as_ref())?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atomic_writes_replace_the_file() {
+        let dir = std::env::temp_dir().join(format!("slothtime-paths-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("entries.json");
+        write_atomic(&path, "[1]").unwrap();
+        write_atomic(&path, "[2]").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[2]");
+        assert!(!dir.join("entries.json.tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn tilde_is_home() {
+        let home = Path::new("/home/me");
+        assert_eq!(expand_with("~", Some(home), None), home);
+        assert_eq!(
+            expand_with("~/.slothtime/status", Some(home), None),
+            home.join(".slothtime").join("status")
+        );
+    }
+
+    #[test]
+    fn documents_follow_the_documents_folder() {
+        let home = Path::new("C:\\Users\\me");
+        let documents = Path::new("C:\\Users\\me\\OneDrive\\Documents");
+        assert_eq!(
+            expand_with("~/Documents/slothtime_exports", Some(home), Some(documents)),
+            documents.join("slothtime_exports")
+        );
+    }
+
+    #[test]
+    fn backslashes_separate_folders() {
+        let home = Path::new("/home/me");
+        let documents = Path::new("/home/me/Docs");
+        assert_eq!(
+            expand_with(
+                "~\\Documents\\slothtime_exports",
+                Some(home),
+                Some(documents)
+            ),
+            documents.join("slothtime_exports")
+        );
+        assert_eq!(
+            expand_with("~\\tickets.txt", Some(home), Some(documents)),
+            home.join("tickets.txt")
+        );
+    }
+
+    #[test]
+    fn documents_match_any_case() {
+        let documents = Path::new("/docs");
+        assert_eq!(
+            expand_with("~/documents", Some(Path::new("/home/me")), Some(documents)),
+            documents
+        );
+    }
+
+    #[test]
+    fn other_paths_are_unchanged() {
+        let home = Some(Path::new("/home/me"));
+        assert_eq!(
+            expand_with("D:\\exports", home, None),
+            PathBuf::from("D:\\exports")
+        );
+        assert_eq!(
+            expand_with("/srv/exports", home, None),
+            PathBuf::from("/srv/exports")
+        );
+        assert_eq!(
+            expand_with("~other/x", home, None),
+            PathBuf::from("~other/x")
+        );
+        assert_eq!(expand_with("~/x", None, None), PathBuf::from("~/x"));
+    }
+
+    #[test]
+    fn without_a_documents_folder_home_is_used() {
+        let home = Path::new("/home/me");
+        assert_eq!(
+            expand_with("~/Documents/out", Some(home), None),
+            home.join("Documents").join("out")
+        );
+    }
+}
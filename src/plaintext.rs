@@ -0,0 +1,215 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+
+use crate::archive::Day;
+use crate::time_entry::{self, TimeEntry};
+
+// Formats of plain text time tracking tools that can read Slothtime's entries
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum PlainFormat {
+    Timew,     // JSON for timewarrior's `timew import`
+    Timeclock, // timeclock.el's log, which hledger and ledger read too
+}
+
+impl PlainFormat {
+    // As typed after --plaintext, and the key of its [mappings] table
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlainFormat::Timew => "timew",
+            PlainFormat::Timeclock => "timeclock",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PlainFormat::Timew => "json",
+            PlainFormat::Timeclock => "timeclock",
+        }
+    }
+
+    pub fn write(&self, days: &[Day], today: NaiveDate) -> Result<String> {
+        let intervals = intervals(days, today);
+        match self {
+            PlainFormat::Timew => timew(&intervals),
+            PlainFormat::Timeclock => Ok(timeclock(&intervals)),
+        }
+    }
+}
+
+// An entry placed on the calendar, without an end while it's still running
+struct Interval<'a> {
+    entry: &'a TimeEntry,
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+}
+
+// Entries with valid times, in order. The only one left open is today's running entry;
+// other rows missing an end time, or ending before they start, are left out.
+fn intervals(days: &[Day], today: NaiveDate) -> Vec<Interval<'_>> {
+    let at = |date: NaiveDate, time: &str| {
+        let time = TimeEntry::parse_time(time)?;
+        Local
+            .from_local_datetime(&NaiveDateTime::new(date, time))
+            .earliest()
+    };
+    let mut intervals = Vec::new();
+    for day in days {
+        let running = (day.date == today)
+            .then(|| time_entry::running_entry(&day.entries))
+            .flatten();
+        for (row, entry) in day.entries.iter().enumerate() {
+            let Some(start) = at(day.date, &entry.start_time) else {
+                continue;
+            };
+            let end = match entry.end_time.trim() {
+                "" if running == Some(row) => None,
+                "" => continue,
+                end => match at(day.date, end) {
+                    Some(end) if end >= start => Some(end),
+                    _ => continue,
+                },
+            };
+            intervals.push(Interval { entry, start, end });
+        }
+    }
+    intervals.sort_by_key(|interval| interval.start);
+    intervals
+}
+
+// Descriptions on one line, as both formats need
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Serialize)]
+struct TimewInterval {
+    start: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    annotation: String,
+}
+
+// Tagged with the task number and work code, with the description as the annotation
+fn timew(intervals: &[Interval]) -> Result<String> {
+    let utc = |time: DateTime<Local>| {
+        time.with_timezone(&Utc)
+            .format("%Y%m%dT%H%M%SZ")
+            .to_string()
+    };
+    let intervals: Vec<TimewInterval> = intervals
+        .iter()
+        .map(|interval| TimewInterval {
+            start: utc(interval.start),
+            end: interval.end.map(utc),
+            tags: [&interval.entry.task_number, &interval.entry.work_code]
+                .iter()
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+            annotation: one_line(&interval.entry.time_entry),
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&intervals)? + "\n")
+}
+
+// `i` and `o` lines on the account WORKCODE:TASK, the description after two spaces
+fn timeclock(intervals: &[Interval]) -> String {
+    let mut out = String::new();
+    let stamp = |time: DateTime<Local>| time.format("%Y/%m/%d %H:%M:%S").to_string();
+    for interval in intervals {
+        let entry = interval.entry;
+        let parts: Vec<&str> = [entry.work_code.trim(), entry.task_number.trim()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect();
+        let account = if parts.is_empty() {
+            "unassigned".to_string()
+        } else {
+            // Two spaces end an account name, so one has to do inside it
+            one_line(&parts.join(":"))
+        };
+        let description = one_line(&entry.time_entry);
+        out.push_str(&format!("i {} {}", stamp(interval.start), account));
+        if !description.is_empty() {
+            out.push_str(&format!("  {}", description));
+        }
+        out.push('\n');
+        if let Some(end) = interval.end {
+            out.push_str(&format!("o {}\n", stamp(end)));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 10, day).unwrap()
+    }
+
+    fn days() -> Vec<Day> {
+        vec![
+            Day {
+                date: date(7),
+                entries: vec![
+                    entry("T-1", "DEV", "13:00", "14:00"),
+                    entry("T-2", "", "15:00", "").described("Left\nopen"), // Not today, so not running
+                    entry("", "", "17:00", "08:00").described("Typo"),
+                    entry("", "", "09:00", "10:30").described("Review  the\tplan"),
+                ],
+                note: String::new(),
+            },
+            Day {
+                date: date(8),
+                entries: vec![entry("T 3", "OPS", "08:00", "").described("On call")],
+                note: String::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn timeclock_lists_valid_entries_in_order() {
+        let out = PlainFormat::Timeclock.write(&days(), date(8)).unwrap();
+        assert_eq!(
+            out,
+            "i 2025/10/07 09:00:00 unassigned  Review the plan\n\
+             o 2025/10/07 10:30:00\n\
+             i 2025/10/07 13:00:00 DEV:T-1\n\
+             o 2025/10/07 14:00:00\n\
+             i 2025/10/08 08:00:00 OPS:T 3  On call\n"
+        );
+    }
+
+    #[test]
+    fn timew_tags_the_task_and_work_code() {
+        let out = PlainFormat::Timew.write(&days(), date(8)).unwrap();
+        let intervals: serde_json::Value = serde_json::from_str(&out).unwrap();
+        let intervals = intervals.as_array().unwrap();
+        assert_eq!(intervals.len(), 3);
+
+        let utc = |day: u32, time: &str| {
+            let time = TimeEntry::parse_time(time).unwrap();
+            let local = Local
+                .from_local_datetime(&date(day).and_time(time))
+                .earliest()
+                .unwrap();
+            local
+                .with_timezone(&Utc)
+                .format("%Y%m%dT%H%M%SZ")
+                .to_string()
+        };
+        assert_eq!(intervals[0]["start"], utc(7, "09:00"));
+        assert_eq!(intervals[0]["end"], utc(7, "10:30"));
+        assert_eq!(intervals[0]["tags"], serde_json::json!([]));
+        assert_eq!(intervals[0]["annotation"], "Review the plan");
+        assert_eq!(intervals[1]["tags"], serde_json::json!(["T-1", "DEV"]));
+        assert!(intervals[1].get("annotation").is_none());
+        assert!(intervals[2].get("end").is_none());
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup;
+use crate::time_entry::TimeEntry;
+
+// entries.json exists but couldn't be read, and what could be recovered from it
+#[derive(Debug)]
+pub struct LoadProblem {
+    pub error: String,
+    pub salvaged: Vec<TimeEntry>, // Entries that parse before the damage
+    pub backup: Option<(PathBuf, Vec<TimeEntry>)>, // Newest backup that still loads
+}
+
+impl LoadProblem {
+    pub fn new(data_dir: &Path, content: &str, error: String) -> Self {
+        Self {
+            error,
+            salvaged: salvage(content),
+            backup: backup::newest_readable(data_dir),
+        }
+    }
+}
+
+// Read the entries array entry by entry and keep every entry up to the first one
+// that doesn't parse, e.g. where a crash cut the file short
+pub fn salvage(content: &str) -> Vec<TimeEntry> {
+    let mut entries = Vec::new();
+    // Current files hold the array under "entries", version 1 files are only the array
+    let content = content.trim_start();
+    let array = if content.starts_with('[') {
+        Some(content)
+    } else {
+        content.find("\"entries\"").and_then(|key| {
+            content[key..]
+                .find('[')
+                .map(|start| &content[key + start..])
+        })
+    };
+    let Some(mut rest) = array.and_then(|array| array.strip_prefix('[')) else {
+        return entries;
+    };
+    loop {
+        rest = rest.trim_start().trim_start_matches(',').trim_start();
+        let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<TimeEntry>();
+        match stream.next() {
+            Some(Ok(entry)) => {
+                entries.push(entry);
+                rest = &rest[stream.byte_offset()..];
+            }
+            _ => return entries,
+        }
+    }
+}
+
+// Move the damaged file out of the way before anything is saved over it
+pub fn set_aside(data_dir: &Path) -> Result<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = data_dir.join(format!("entries.corrupt-{}.json", stamp));
+    fs::rename(data_dir.join("entries.json"), &path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST: &str = r#"{"task_number":"T-1","work_code":"DEV","time_entry":"a","start_time":"09:00","end_time":"10:00"}"#;
+    const SECOND: &str = r#"{"task_number":"T-2","work_code":"OPS","time_entry":"b","start_time":"10:00","end_time":"11:00"}"#;
+
+    fn tasks(entries: &[TimeEntry]) -> Vec<&str> {
+        entries
+            .iter()
+            .map(|entry| entry.task_number.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn version_1_arrays_keep_the_entries_before_the_cut() {
+        let content = format!("[{}, {}, {{\"task_number\":\"T-3\",\"wor", FIRST, SECOND);
+        assert_eq!(tasks(&salvage(&content)), vec!["T-1", "T-2"]);
+        let content = format!("  [{},\n{}", FIRST, &SECOND[..40]);
+        assert_eq!(tasks(&salvage(&content)), vec!["T-1"]);
+    }
+
+    #[test]
+    fn current_files_are_read_from_the_entries_key() {
+        let content = format!("{{\"version\":2,\"entries\":[{},{}", FIRST, SECOND);
+        assert_eq!(tasks(&salvage(&content)), vec!["T-1", "T-2"]);
+        assert!(salvage("{\"version\":2,\"entr").is_empty());
+    }
+
+    #[test]
+    fn garbage_salvages_nothing() {
+        for content in ["", "not json at all", "\0\0\0\0", "{[}", "[[[", "[1, 2]"] {
+            assert!(salvage(content).is_empty(), "{:?}", content);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::{bail, Result};
+use regex::{NoExpand, Regex};
+
+use crate::time_entry::TimeEntry;
+
+// A `:%s/old/new/flags` command. `old` is a regular expression and `new` can use
+// its groups as `$1`. Without the `a` flag only one column is searched.
+#[derive(Debug, Clone)]
+pub struct Replace {
+    pattern: Regex,
+    replacement: String,
+    literal: bool, // `new` has no `$` groups to expand
+    pub columns: Vec<usize>,
+}
+
+// One cell the command would change
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub row: usize,
+    pub col: usize,
+    pub before: String,
+    pub after: String,
+}
+
+impl Replace {
+    // Parse what follows `%s`, e.g. `/DEV/DEVELOP/` or `|a/b|c|ai`. Flags: `a` searches
+    // every column instead of `col`, `i` ignores case.
+    pub fn parse(command: &str, col: usize) -> Result<Self> {
+        let mut chars = command.chars();
+        let Some(separator) = chars
+            .next()
+            .filter(|c| !c.is_alphanumeric() && !c.is_whitespace())
+        else {
+            bail!("Usage: :%s/old/new/ (add a to search every column, i to ignore case)");
+        };
+        let parts = split_unescaped(chars.as_str(), separator);
+        let (old, new, flags) = match parts.as_slice() {
+            [old, new] => (old, new, ""),
+            [old, new, flags] => (old, new, flags.as_str()),
+            _ => bail!("Usage: :%s/old/new/ (add a to search every column, i to ignore case)"),
+        };
+        if old.is_empty() {
+            bail!("Nothing to search for");
+        }
+        if let Some(flag) = flags.chars().find(|c| !matches!(c, 'a' | 'i' | 'g')) {
+            bail!(
+                "Unknown flag '{}', use a (every column) or i (ignore case)",
+                flag
+            );
+        }
+        let pattern = if flags.contains('i') {
+            format!("(?i){}", old)
+        } else {
+            old.clone()
+        };
+        // The regex error spans several lines pointing at the problem, the last one says what it is
+        let pattern = Regex::new(&pattern).map_err(|e| {
+            let error = e.to_string();
+            anyhow::anyhow!(
+                "Invalid pattern: {}",
+                error
+                    .lines()
+                    .last()
+                    .unwrap_or_default()
+                    .trim_start_matches("error: ")
+            )
+        })?;
+        Ok(Self {
+            pattern,
+            literal: !new.contains('$'),
+            replacement: new.clone(),
+            columns: if flags.contains('a') {
+                (1..=5).collect()
+            } else {
+                vec![col]
+            },
+        })
+    }
+
+    // Every cell that would change, top to bottom
+    pub fn plan(&self, entries: &[TimeEntry]) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for (row, entry) in entries.iter().enumerate() {
+            for &col in &self.columns {
+                let Some(before) = entry.field(col) else {
+                    continue;
+                };
+                let after = if self.literal {
+                    self.pattern
+                        .replace_all(before, NoExpand(&self.replacement))
+                } else {
+                    self.pattern.replace_all(before, self.replacement.as_str())
+                };
+                if after != *before {
+                    changes.push(Change {
+                        row,
+                        col,
+                        before: before.clone(),
+                        after: after.into_owned(),
+                    });
+                }
+            }
+        }
+        changes
+    }
+}
+
+// Split on `separator` unless it is escaped with a backslash, dropping that backslash
+fn split_unescaped(text: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&separator) => {
+                parts.last_mut().unwrap().push(separator);
+                chars.next();
+            }
+            c if c == separator => parts.push(String::new()),
+            c => parts.last_mut().unwrap().push(c),
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    fn cells(changes: &[Change]) -> Vec<(usize, usize, &str)> {
+        changes
+            .iter()
+            .map(|change| (change.row, change.col, change.after.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn commands_are_parsed() {
+        let replace = Replace::parse("/DEV/DEVELOP/", 2).unwrap();
+        assert_eq!(replace.columns, vec![2]);
+        assert!(replace.literal);
+        let replace = Replace::parse("|a/b|c|ai", 2).unwrap();
+        assert_eq!(replace.columns, vec![1, 2, 3, 4, 5]);
+        assert_eq!(replace.pattern.as_str(), "(?i)a/b");
+        let replace = Replace::parse(r"/a\/b/c", 1).unwrap();
+        assert_eq!(replace.pattern.as_str(), "a/b");
+
+        for command in ["", "sDEV/DEVELOP/", "/DEV", "//x/", "/DEV/X/q", "/(/x/"] {
+            assert!(Replace::parse(command, 2).is_err(), "{}", command);
+        }
+        let error = Replace::parse("/(/x/", 2).unwrap_err().to_string();
+        assert!(error.starts_with("Invalid pattern: "), "{}", error);
+        assert!(!error.contains('\n'));
+    }
+
+    #[test]
+    fn plan_lists_changed_cells() {
+        let entries = vec![
+            entry("T-1", "DEV", "", "").described("dev work"),
+            entry("T-2", "OPS", "", "").described("Deploy"),
+            entry("T-3", "DEV", "", ""),
+        ];
+        let replace = Replace::parse("/DEV/DEVELOP/", 2).unwrap();
+        assert_eq!(
+            cells(&replace.plan(&entries)),
+            vec![(0, 2, "DEVELOP"), (2, 2, "DEVELOP")]
+        );
+
+        let replace = Replace::parse("/dev/Build/ai", 2).unwrap();
+        assert_eq!(
+            cells(&replace.plan(&entries)),
+            vec![(0, 2, "Build"), (0, 3, "Build work"), (2, 2, "Build")]
+        );
+    }
+
+    #[test]
+    fn groups_expand_only_when_asked() {
+        let entries = vec![entry("T-12", "", "", "")];
+        let replace = Replace::parse(r"/T-(\d+)/TASK-$1/", 1).unwrap();
+        assert_eq!(cells(&replace.plan(&entries)), vec![(0, 1, "TASK-12")]);
+        let replace = Replace::parse("/T-/T-100%/", 1).unwrap();
+        assert_eq!(cells(&replace.plan(&entries)), vec![(0, 1, "T-100%12")]);
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::time_entry::TimeEntry;
+
+// Format versions written into entries.json and slothtime.toml. Bump one and add a
+// migration below whenever a change would stop older files from loading.
+pub const ENTRIES_VERSION: u32 = 2;
+pub const CONFIG_VERSION: u32 = 1;
+
+// MIGRATIONS[n] upgrades a version n + 1 file to version n + 2
+type JsonMigration = fn(Value) -> Result<Value>;
+type TomlMigration = fn(toml::Value) -> Result<toml::Value>;
+
+const ENTRIES_MIGRATIONS: &[JsonMigration] = &[entries_v1_to_v2];
+const CONFIG_MIGRATIONS: &[TomlMigration] = &[];
+
+#[derive(Serialize)]
+struct EntriesFile<'a> {
+    version: u32,
+    entries: &'a [TimeEntry],
+}
+
+pub fn write_entries(entries: &[TimeEntry]) -> Result<String> {
+    Ok(serde_json::to_string(&EntriesFile {
+        version: ENTRIES_VERSION,
+        entries,
+    })?)
+}
+
+// Parse entries.json as written by any version of Slothtime up to this one
+pub fn read_entries(content: &str) -> Result<Vec<TimeEntry>> {
+    let value: Value = serde_json::from_str(content)?;
+    let version = match &value {
+        // Version 1 was a bare array, before files had a version
+        Value::Array(_) => 1,
+        _ => match value.get("version").and_then(Value::as_u64) {
+            Some(version) => version as u32,
+            None => bail!("entries file has no version"),
+        },
+    };
+    let value = upgrade(
+        value,
+        version,
+        ENTRIES_VERSION,
+        ENTRIES_MIGRATIONS,
+        "entries file",
+    )?;
+    match value.get("entries") {
+        Some(entries) => Ok(serde_json::from_value(entries.clone())?),
+        None => bail!("entries file has no entries"),
+    }
+}
+
+// Bring a parsed slothtime.toml up to date. Files without a version are version 1,
+// which also covers project overlays that only set a few keys.
+pub fn migrate_config(value: toml::Value) -> Result<toml::Value> {
+    let version = value
+        .get("version")
+        .and_then(toml::Value::as_integer)
+        .unwrap_or(1) as u32;
+    let mut value = upgrade(value, version, CONFIG_VERSION, CONFIG_MIGRATIONS, "config")?;
+    if let toml::Value::Table(table) = &mut value {
+        table.insert(
+            "version".to_string(),
+            toml::Value::Integer(CONFIG_VERSION as i64),
+        );
+    }
+    Ok(value)
+}
+
+fn upgrade<T>(
+    mut value: T,
+    version: u32,
+    current: u32,
+    migrations: &[fn(T) -> Result<T>],
+    what: &str,
+) -> Result<T> {
+    if version == 0 || version > current {
+        bail!(
+            "{} is version {}, this Slothtime reads up to version {}. Is it from a newer release?",
+            what,
+            version,
+            current
+        );
+    }
+    for migration in &migrations[version as usize - 1..] {
+        value = migration(value)?;
+    }
+    Ok(value)
+}
+
+// The bare array gets wrapped in an object that can carry the version
+fn entries_v1_to_v2(value: Value) -> Result<Value> {
+    Ok(serde_json::json!({ "version": 2, "entries": value }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    #[test]
+    fn written_entries_read_back() {
+        let content =
+            write_entries(&[entry("PROJ-1", "", "", ""), entry("PROJ-2", "", "", "")]).unwrap();
+        assert!(content.starts_with(r#"{"version":2,"#));
+        let entries = read_entries(&content).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].task_number, "PROJ-2");
+    }
+
+    #[test]
+    fn version_1_array_is_migrated() {
+        let content = r#"[{"task_number":"PROJ-1","work_code":"DEV","time_entry":"x","start_time":"09:00","end_time":"10:00"}]"#;
+        let entries = read_entries(content).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].work_code, "DEV");
+        assert!(entries[0].exported_hash.is_none());
+    }
+
+    #[test]
+    fn newer_entries_are_refused() {
+        let error = read_entries(r#"{"version":99,"entries":[]}"#).unwrap_err();
+        assert!(error.to_string().contains("version 99"));
+    }
+
+    #[test]
+    fn unversioned_object_is_refused() {
+        assert!(read_entries(r#"{"entries":[]}"#).is_err());
+    }
+
+    #[test]
+    fn config_without_version_is_stamped() {
+        let value: toml::Value = toml::from_str("[ui]\nauto_save = true\n").unwrap();
+        let value = migrate_config(value).unwrap();
+        assert_eq!(
+            value.get("version").and_then(toml::Value::as_integer),
+            Some(CONFIG_VERSION as i64)
+        );
+        assert!(value.get("ui").is_some());
+    }
+
+    #[test]
+    fn newer_config_is_refused() {
+        let value: toml::Value = toml::from_str("version = 99\n").unwrap();
+        assert!(migrate_config(value).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{bail, Result};
+use chrono::Local;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::app::App;
+use crate::status_file::TimerStatus;
+use crate::time_entry;
+
+// How long a client gets to send its whole request, and to take each write of the response
+const TIMEOUT: Duration = Duration::from_secs(5);
+// A GET with a few headers is far smaller, anything longer isn't a scrape
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+// Connections served at once, more are closed straight away
+const MAX_CONNECTIONS: usize = 16;
+
+// A tiny HTTP server for `slothtime-rs serve`. Entries are read from disk on every
+// request, so it reflects whatever the TUI last saved. Each connection gets its own
+// thread, so a client that never finishes its request can't hold up the scrapes.
+pub fn run(addr: &str, data_dir: &Path, project: Option<&str>) -> Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("Serving metrics on http://{}/metrics", addr);
+    let open = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            open.fetch_sub(1, Ordering::SeqCst);
+            continue;
+        }
+        let open = Arc::clone(&open);
+        let data_dir = data_dir.to_path_buf();
+        let project = project.map(str::to_string);
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, &data_dir, project.as_deref()) {
+                eprintln!("request failed: {}", e);
+            }
+            open.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+    Ok(())
+}
+
+// A connection that stops reading once the request's deadline has passed, so a client
+// trickling in a byte at a time can't keep it open
+struct Deadline {
+    stream: TcpStream,
+    until: Instant,
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "the request took too long",
+            ));
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+fn handle(mut stream: TcpStream, data_dir: &Path, project: Option<&str>) -> Result<()> {
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let deadline = Deadline {
+        stream: stream.try_clone()?,
+        until: Instant::now() + TIMEOUT,
+    };
+    let mut reader = BufReader::new(deadline.take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers, nothing in them matters here
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    if reader.get_ref().limit() == 0 {
+        bail!("the request is longer than {} bytes", MAX_REQUEST_BYTES);
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let (status, body) = match path {
+        "/metrics" => ("200 OK", metrics(data_dir, project)),
+        _ => ("404 Not Found", "Try /metrics\n".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+// Prometheus text format
+fn metrics(data_dir: &Path, project: Option<&str>) -> String {
+    let entries = App::load_entries(data_dir).unwrap_or_default();
+    let filled: Vec<_> = entries.iter().filter(|e| !e.is_entirely_empty()).collect();
+    let tracked_minutes: i64 = filled.iter().filter_map(|e| e.duration_minutes()).sum();
+    let running = time_entry::running_entry(&entries).map(|row| &entries[row]);
+    let timer = TimerStatus::new(running, Local::now());
+    let labels = match project {
+        Some(project) => format!("{{project=\"{}\"}}", project.replace(['"', '\\'], "")),
+        None => String::new(),
+    };
+
+    let metrics = [
+        (
+            "slothtime_entries_today",
+            "Entries with any content in today's sheet",
+            filled.len() as i64,
+        ),
+        (
+            "slothtime_tracked_seconds_today",
+            "Time covered by today's finished entries",
+            tracked_minutes * 60,
+        ),
+        (
+            "slothtime_timer_running",
+            "1 while an entry has a start time but no end time",
+            timer.running as i64,
+        ),
+        (
+            "slothtime_timer_elapsed_seconds",
+            "How long the running entry has been going",
+            timer.elapsed_minutes * 60,
+        ),
+    ];
+    let mut body = String::new();
+    for (name, help, value) in metrics {
+        let _ = writeln!(body, "# HELP {} {}", name, help);
+        let _ = writeln!(body, "# TYPE {} gauge", name);
+        let _ = writeln!(body, "{}{} {}", name, labels, value);
+    }
+    body
+}
@@ -0,0 +1,437 @@
+use anyhow::Result;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::{Encoding, Export, ExportFormat, Newlines};
+use crate::paths;
+
+// One value of an exported table. Numbers stay numbers in spreadsheet formats so
+// they can be summed without converting text first.
+#[derive(Debug, Clone)]
+pub enum Cell {
+    Text(String),
+    Count(usize),
+    Hours(f64),
+}
+
+impl Cell {
+    fn display(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Count(count) => count.to_string(),
+            Cell::Hours(hours) => format!("{:.2}", hours),
+        }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::Text(text.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+// A table as every export format writes it, so a column added to one export shows
+// up the same way in CSV and ODS
+#[derive(Debug, Clone, Default)]
+pub struct Sheet {
+    pub name: String,          // Sheet name in spreadsheets
+    pub title: String,         // Heading of printable formats, the name unless set
+    pub total: Option<String>, // Time logged, shown under the table by printable formats
+    pub notes: Vec<String>,    // Day notes above the header, empty ones are skipped
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Sheet {
+    pub fn new(name: &str, header: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            title: name.to_string(),
+            header: header.iter().map(|column| column.to_string()).collect(),
+            ..Self::default()
+        }
+    }
+
+    fn notes(&self) -> impl Iterator<Item = &str> {
+        self.notes
+            .iter()
+            .map(String::as_str)
+            .filter(|note| !note.is_empty())
+    }
+}
+
+// Where a sheet was written
+#[derive(Debug, Clone)]
+pub struct Saved {
+    pub path: PathBuf,
+    pub unmapped: usize, // Characters written as `?` because the encoding has no byte for them
+}
+
+impl Saved {
+    // Added to the export message when characters were replaced, empty otherwise
+    pub fn unmapped_note(&self) -> String {
+        if self.unmapped == 0 {
+            return String::new();
+        }
+        format!(
+            ", {} character(s) Windows-1252 has no byte for were written as ?",
+            self.unmapped
+        )
+    }
+}
+
+// Write the sheet into `dir` as `<stem>.<extension>` in `export.format`
+pub fn save(sheet: &Sheet, dir: &Path, stem: &str, export: &Export) -> Result<Saved> {
+    let path = dir.join(format!("{}.{}", stem, export.format.extension()));
+    let mut unmapped = 0;
+    match export.format {
+        ExportFormat::Csv => {
+            let mut csv = Vec::new();
+            write_csv_as(sheet, &mut csv, export.newlines, export.crlf)?;
+            let (bytes, replaced) = encode(&csv, export.encoding);
+            fs::write(&path, bytes)?;
+            unmapped = replaced;
+        }
+        ExportFormat::Ods => fs::write(&path, ods(sheet)?)?,
+        ExportFormat::Html => fs::write(&path, html(sheet, export))?,
+    }
+    Ok(Saved { path, unmapped })
+}
+
+// CSV as printed by the CLI, line breaks kept
+pub fn write_csv<W: io::Write>(sheet: &Sheet, out: W) -> Result<()> {
+    write_csv_as(sheet, out, Newlines::Keep, false)
+}
+
+// Notes become `# ` comment lines before the header, which most CSV readers can skip.
+// Line breaks within a cell follow `newlines`, and with `crlf` every line ends in \r\n.
+fn write_csv_as<W: io::Write>(
+    sheet: &Sheet,
+    mut out: W,
+    newlines: Newlines,
+    crlf: bool,
+) -> Result<()> {
+    let eol = if crlf { "\r\n" } else { "\n" };
+    for note in sheet.notes() {
+        write!(out, "# {}{}", note, eol)?;
+    }
+    let mut wtr = csv::WriterBuilder::new()
+        .terminator(if crlf {
+            csv::Terminator::CRLF
+        } else {
+            csv::Terminator::Any(b'\n')
+        })
+        .from_writer(out);
+    wtr.write_record(&sheet.header)?;
+    for row in &sheet.rows {
+        wtr.write_record(
+            row.iter()
+                .map(|cell| line_breaks(&cell.display(), newlines, eol)),
+        )?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// `\r\n` and `\n` alike, as a description pasted from Windows may have either
+fn line_breaks(text: &str, newlines: Newlines, eol: &str) -> String {
+    if !text.contains('\n') {
+        return text.to_string();
+    }
+    match newlines {
+        Newlines::Keep => text.lines().collect::<Vec<_>>().join(eol),
+        Newlines::Semicolon => text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("; "),
+        Newlines::Escape => text.lines().collect::<Vec<_>>().join("\\n"),
+    }
+}
+
+// UTF-8 text in `encoding`, and how many characters were replaced. Characters
+// Windows-1252 has no byte for, like emoji or arrows, become `?` rather than failing
+// the export.
+fn encode(utf8: &[u8], encoding: Encoding) -> (Vec<u8>, usize) {
+    match encoding {
+        Encoding::Utf8 => (utf8.to_vec(), 0),
+        Encoding::Utf8Bom => ([b"\xEF\xBB\xBF".as_slice(), utf8].concat(), 0),
+        Encoding::Windows1252 => {
+            let text = String::from_utf8_lossy(utf8);
+            let mut out = Vec::with_capacity(text.len());
+            let mut unmapped = 0;
+            let mut buf = [0; 4];
+            for c in text.chars() {
+                let (bytes, _, failed) = encoding_rs::WINDOWS_1252.encode(c.encode_utf8(&mut buf));
+                if failed {
+                    unmapped += 1;
+                    out.push(b'?');
+                } else {
+                    out.extend_from_slice(&bytes);
+                }
+            }
+            if unmapped > 0 {
+                tracing::warn!(
+                    "{} characters have no Windows-1252 byte and were written as ?",
+                    unmapped
+                );
+            }
+            (out, unmapped)
+        }
+    }
+}
+
+// An OpenDocument spreadsheet is a zip holding the table as content.xml, with an
+// uncompressed mimetype entry first so it can be recognised without unzipping
+fn ods(sheet: &Sheet) -> Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+    let stored =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    let deflated = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/vnd.oasis.opendocument.spreadsheet")?;
+    zip.start_file("META-INF/manifest.xml", deflated)?;
+    zip.write_all(ODS_MANIFEST.as_bytes())?;
+    zip.start_file("content.xml", deflated)?;
+    zip.write_all(ods_content(sheet).as_bytes())?;
+    Ok(zip.finish()?.into_inner())
+}
+
+const ODS_MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2">
+ <manifest:file-entry manifest:full-path="/" manifest:version="1.2" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>
+ <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>
+"#;
+
+fn ods_content(sheet: &Sheet) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+        "\n",
+        r#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" "#,
+        r#"xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" "#,
+        r#"xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" office:version="1.2">"#,
+        "<office:body><office:spreadsheet>",
+    ));
+    xml.push_str(&format!(
+        r#"<table:table table:name="{}">"#,
+        escape(&sheet.name)
+    ));
+    for note in sheet.notes() {
+        xml.push_str("<table:table-row>");
+        xml.push_str(&ods_cell(&Cell::from(note)));
+        xml.push_str("</table:table-row>");
+    }
+    let header = sheet
+        .header
+        .iter()
+        .map(|column| Cell::from(column.as_str()));
+    for row in std::iter::once(header.collect()).chain(sheet.rows.iter().cloned()) {
+        xml.push_str("<table:table-row>");
+        for cell in &row {
+            xml.push_str(&ods_cell(cell));
+        }
+        xml.push_str("</table:table-row>");
+    }
+    xml.push_str("</table:table></office:spreadsheet></office:body></office:document-content>\n");
+    xml
+}
+
+fn ods_cell(cell: &Cell) -> String {
+    let value = match cell {
+        Cell::Text(_) => r#"office:value-type="string""#.to_string(),
+        Cell::Count(count) => format!(r#"office:value-type="float" office:value="{}""#, count),
+        Cell::Hours(hours) => format!(r#"office:value-type="float" office:value="{}""#, hours),
+    };
+    // Each line of a multi-line description is its own paragraph in the cell
+    let paragraphs: String = cell
+        .display()
+        .lines()
+        .map(|line| format!("<text:p>{}</text:p>", escape(line)))
+        .collect();
+    format!(
+        "<table:table-cell {}>{}</table:table-cell>",
+        value, paragraphs
+    )
+}
+
+// A standalone page that prints as a timesheet: letterhead from the config, the
+// title and notes, the table and the total. Rows aren't split across pages and the
+// header repeats on each printed page.
+fn html(sheet: &Sheet, export: &Export) -> String {
+    let mut page =
+        String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    page.push_str(&format!("<title>{}</title>\n", escape(&sheet.title)));
+    page.push_str(HTML_STYLE);
+    page.push_str("</head>\n<body>\n<header>\n");
+    page.push_str(&letterhead(export));
+    page.push_str(&format!("<h1>{}</h1>\n", escape(&sheet.title)));
+    for note in sheet.notes() {
+        page.push_str(&format!("<p class=\"note\">{}</p>\n", escape(note)));
+    }
+    page.push_str("</header>\n<table>\n<thead><tr>");
+    for column in &sheet.header {
+        page.push_str(&format!("<th>{}</th>", escape(column)));
+    }
+    page.push_str("</tr></thead>\n<tbody>\n");
+    for row in &sheet.rows {
+        page.push_str("<tr>");
+        for cell in row {
+            let class = match cell {
+                Cell::Text(_) => "",
+                Cell::Count(_) | Cell::Hours(_) => " class=\"num\"",
+            };
+            let lines: Vec<String> = cell.display().lines().map(escape).collect();
+            page.push_str(&format!("<td{}>{}</td>", class, lines.join("<br>")));
+        }
+        page.push_str("</tr>\n");
+    }
+    page.push_str("</tbody>\n</table>\n");
+    if let Some(total) = &sheet.total {
+        page.push_str(&format!(
+            "<p class=\"total\">Total: {}</p>\n",
+            escape(total)
+        ));
+    }
+    page.push_str(&format!(
+        "<footer>Generated by Slothtime on {}</footer>\n</body>\n</html>\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    ));
+    page
+}
+
+// The logo, company and name of printable pages, each left out when empty
+pub fn letterhead(export: &Export) -> String {
+    let mut html = String::new();
+    if !export.logo.is_empty() {
+        // Local files are linked by absolute path so the page still finds them when moved
+        let logo = if export.logo.contains("://") {
+            export.logo.clone()
+        } else {
+            file_url(&paths::expand(&export.logo))
+        };
+        html.push_str(&format!(
+            "<img class=\"logo\" src=\"{}\" alt=\"\">\n",
+            escape(&logo)
+        ));
+    }
+    if !export.company.is_empty() {
+        html.push_str(&format!(
+            "<div class=\"company\">{}</div>\n",
+            escape(&export.company)
+        ));
+    }
+    if !export.name.is_empty() {
+        html.push_str(&format!(
+            "<div class=\"name\">{}</div>\n",
+            escape(&export.name)
+        ));
+    }
+    html
+}
+
+// A `file://` URL for a local path, so spaces, `#` and Windows drive paths still load
+fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let encoded: String = path
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    format!("file:///{}", encoded.trim_start_matches('/'))
+}
+
+const HTML_STYLE: &str = "<style>
+body { font-family: system-ui, sans-serif; color: #222; margin: 2rem; }
+header { margin-bottom: 1.5rem; }
+.logo { max-height: 4rem; float: right; }
+.company { font-size: 1.2rem; font-weight: bold; }
+h1 { font-size: 1.4rem; margin: 0.8rem 0 0.4rem; }
+.note { font-style: italic; margin: 0.2rem 0; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9rem; }
+th, td { border: 1px solid #bbb; padding: 0.3rem 0.5rem; text-align: left; vertical-align: top; }
+th { background: #eee; }
+tbody tr:nth-child(even) { background: #f7f7f7; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.total { font-weight: bold; text-align: right; margin-top: 0.8rem; }
+footer { color: #777; font-size: 0.75rem; margin-top: 2rem; }
+@page { margin: 1.5cm; }
+@media print {
+  body { margin: 0; }
+  thead { display: table-header-group; }
+  tr { break-inside: avoid; }
+  th, tbody tr:nth-child(even) { -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+}
+</style>
+";
+
+// XML and HTML text and attribute values, dropping control characters XML can't hold
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' => escaped.push(' '),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logos_are_linked_as_file_urls() {
+        assert_eq!(
+            file_url(Path::new("/home/me/logo.png")),
+            "file:///home/me/logo.png"
+        );
+        assert_eq!(
+            file_url(Path::new("/home/me/My Logos/#1 logo.png")),
+            "file:///home/me/My%20Logos/%231%20logo.png"
+        );
+        assert_eq!(
+            file_url(Path::new("C:\\Users\\me\\logo.png")),
+            "file:///C:/Users/me/logo.png"
+        );
+        assert_eq!(
+            file_url(Path::new("/tmp/\"><script>.png")),
+            "file:///tmp/%22%3E%3Cscript%3E.png"
+        );
+    }
+
+    #[test]
+    fn csv_is_encoded_byte_for_byte() {
+        let csv = "Café,€5,👍\n".as_bytes();
+        let (bytes, unmapped) = encode(csv, Encoding::Utf8Bom);
+        assert_eq!(&bytes[..3], &[0xEF, 0xBB, 0xBF]);
+        assert_eq!(&bytes[3..], csv);
+        assert_eq!(unmapped, 0);
+
+        let (bytes, unmapped) = encode(csv, Encoding::Windows1252);
+        assert_eq!(bytes, b"Caf\xE9,\x805,?\n");
+        assert_eq!(unmapped, 1);
+        assert_eq!(encode(csv, Encoding::Utf8), (csv.to_vec(), 0));
+    }
+}
@@ -0,0 +1,88 @@
+// Description snippets: ";name" followed by Tab expands to the template configured
+// under [snippets], and Tab then walks through its "{placeholder}"s
+
+// Snippet name typed right before the end of `before`, with the byte offset of its ';'
+pub fn trigger_before(before: &str) -> Option<(usize, &str)> {
+    let start = before.rfind(';')?;
+    let name = &before[start + 1..];
+    let is_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    let at_word_start = before[..start]
+        .chars()
+        .next_back()
+        .is_none_or(char::is_whitespace);
+    (is_name && at_word_start).then_some((start, name))
+}
+
+// The "{name}" placeholders of a template, in order. Only these are Tab stops, so
+// braces the description already had are left alone.
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut search = 0;
+    while let Some(open) = template[search..].find('{').map(|i| i + search) {
+        let Some(close) = template[open..].find('}').map(|i| i + open) else {
+            break;
+        };
+        let name = &template[open + 1..close];
+        if !name.is_empty() && !name.contains(['{', '\n']) {
+            found.push(template[open..=close].to_string());
+            search = close + 1;
+        } else {
+            search = open + 1;
+        }
+    }
+    found
+}
+
+// Byte range and index into `pending` of the first of those placeholders at or after
+// byte `from`
+pub fn next_placeholder(
+    text: &str,
+    from: usize,
+    pending: &[String],
+) -> Option<(usize, usize, usize)> {
+    pending
+        .iter()
+        .enumerate()
+        .filter_map(|(index, placeholder)| {
+            let start = text[from..].find(placeholder.as_str())? + from;
+            Some((start, start + placeholder.len(), index))
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triggers_are_names_at_the_start_of_a_word() {
+        assert_eq!(trigger_before("Notes ;meet"), Some((6, "meet")));
+        assert_eq!(trigger_before(";stand-up_2"), Some((0, "stand-up_2")));
+        assert_eq!(trigger_before("a;meet"), None);
+        assert_eq!(trigger_before("Notes ;"), None);
+        assert_eq!(trigger_before("Notes ;meet now"), None);
+    }
+
+    #[test]
+    fn placeholders_come_from_the_template() {
+        assert_eq!(
+            placeholders("Meeting with {who} about {topic}"),
+            ["{who}", "{topic}"]
+        );
+        assert!(placeholders("Fix {} and {\n}").is_empty());
+        assert_eq!(placeholders("{{who}"), ["{who}"]);
+    }
+
+    #[test]
+    fn braces_not_from_the_template_are_skipped() {
+        let pending = placeholders("Meeting with {who} about {topic}");
+        let text = "Parse {json}; Meeting with {who} about {topic}";
+        assert_eq!(next_placeholder(text, 0, &pending), Some((27, 32, 0)));
+        assert_eq!(next_placeholder(text, 32, &pending), Some((39, 46, 1)));
+        assert_eq!(next_placeholder(text, 0, &pending[1..]), Some((39, 46, 0)));
+        assert_eq!(next_placeholder("Parse {json}", 0, &pending), None);
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+// How many of the latest timings are kept for the average and slowest
+const SAMPLES: usize = 100;
+
+// The latest durations of something done over and over, e.g. saving, for :debug
+#[derive(Default)]
+pub struct Timings {
+    samples: VecDeque<Duration>,
+    count: usize,
+}
+
+impl Timings {
+    pub fn record(&mut self, took: Duration) {
+        if self.samples.len() == SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(took);
+        self.count += 1;
+    }
+
+    // e.g. "12, last 3.1 ms, average 2.4 ms, slowest 9.0 ms", the average and slowest
+    // of the latest 100
+    pub fn summary(&self) -> String {
+        let Some(last) = self.samples.back() else {
+            return "none yet".to_string();
+        };
+        let total: Duration = self.samples.iter().sum();
+        let slowest = self.samples.iter().max().copied().unwrap_or_default();
+        format!(
+            "{}, last {}, average {}, slowest {}",
+            self.count,
+            millis(*last),
+            millis(total / self.samples.len() as u32),
+            millis(slowest)
+        )
+    }
+}
+
+fn millis(took: Duration) -> String {
+    format!("{:.1} ms", took.as_secs_f64() * 1000.0)
+}
+
+// e.g. "14.2 KB", or why the file couldn't be looked at
+pub fn file_size(path: &Path) -> String {
+    match fs::metadata(path) {
+        Ok(metadata) => size(metadata.len()),
+        Err(e) => format!("unknown ({})", e),
+    }
+}
+
+pub fn size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} bytes", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
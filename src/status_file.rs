@@ -0,0 +1,73 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::time_entry::{format_duration, TimeEntry};
+
+// What external status bars read from status_file.path, and the same as JSON next to it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimerStatus {
+    pub running: bool,
+    pub task_number: String,
+    pub work_code: String,
+    pub description: String,                 // First line only
+    pub started_at: Option<DateTime<Local>>, // Lets scripts keep counting while Slothtime is closed
+    pub elapsed_minutes: i64,
+}
+
+impl TimerStatus {
+    pub fn new(running: Option<&TimeEntry>, now: DateTime<Local>) -> Self {
+        let started_at = running
+            .and_then(|entry| TimeEntry::parse_time(&entry.start_time))
+            .and_then(|time| {
+                now.date_naive()
+                    .and_time(time)
+                    .and_local_timezone(Local)
+                    .single()
+            });
+        let Some(entry) = running else {
+            return Self {
+                running: false,
+                task_number: String::new(),
+                work_code: String::new(),
+                description: String::new(),
+                started_at: None,
+                elapsed_minutes: 0,
+            };
+        };
+        Self {
+            running: true,
+            task_number: entry.task_number.clone(),
+            work_code: entry.work_code.clone(),
+            description: entry.time_entry.lines().next().unwrap_or("").to_string(),
+            started_at,
+            elapsed_minutes: started_at.map_or(0, |start| (now - start).num_minutes().max(0)),
+        }
+    }
+
+    // One line for i3bar, waybar or tmux, empty while nothing runs
+    pub fn line(&self) -> String {
+        if !self.running {
+            return String::new();
+        }
+        let task = if self.task_number.is_empty() {
+            "(no task)"
+        } else {
+            &self.task_number
+        };
+        format!("{} {}", task, format_duration(self.elapsed_minutes))
+    }
+}
+
+pub fn write(path: &Path, status: &TimerStatus) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, format!("{}\n", status.line()))?;
+    let mut json_path = path.as_os_str().to_owned();
+    json_path.push(".json");
+    fs::write(json_path, serde_json::to_string(status)?)?;
+    Ok(())
+}
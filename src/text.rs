@@ -0,0 +1,307 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// Text cursors count grapheme clusters, so "é", "👍🏽" or "日" are each one step
+
+pub fn grapheme_count(s: &str) -> usize {
+    s.graphemes(true).count()
+}
+
+// Byte offset of the grapheme at `index`, or the end of the string past the last one
+pub fn byte_offset(s: &str, index: usize) -> usize {
+    s.grapheme_indices(true)
+        .nth(index)
+        .map_or(s.len(), |(offset, _)| offset)
+}
+
+// Insert `insert` at grapheme `index` and return where the cursor ends up. A combining
+// mark joins the grapheme before it, so the cursor doesn't always move by one per char.
+pub fn insert_str(s: &mut String, index: usize, insert: &str) -> usize {
+    let offset = byte_offset(s, index);
+    s.insert_str(offset, insert);
+    grapheme_count(&s[..offset + insert.len()])
+}
+
+// Remove the grapheme before `index`
+pub fn remove_before(s: &mut String, index: usize) {
+    if index == 0 {
+        return;
+    }
+    let start = byte_offset(s, index - 1);
+    let end = byte_offset(s, index);
+    s.replace_range(start..end, "");
+}
+
+// Where each line of a text starts, so moving the cursor between lines only looks at
+// the lines involved instead of rescanning the whole text on every key press
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    lines: Vec<LineSpan>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LineSpan {
+    start: usize, // Byte offset of the first character
+    end: usize,   // Byte offset of the line break, or the end of the text
+    first_grapheme: usize,
+}
+
+impl LineIndex {
+    pub fn new(s: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut first_grapheme = 0;
+        for (i, (offset, grapheme)) in s.grapheme_indices(true).enumerate() {
+            if grapheme == "\n" || grapheme == "\r\n" {
+                lines.push(LineSpan {
+                    start,
+                    end: offset,
+                    first_grapheme,
+                });
+                start = offset + grapheme.len();
+                first_grapheme = i + 1;
+            }
+        }
+        lines.push(LineSpan {
+            start,
+            end: s.len(),
+            first_grapheme,
+        });
+        Self { lines }
+    }
+
+    // Line number and display column of the cursor at grapheme `index`
+    pub fn line_and_column(&self, s: &str, index: usize) -> (usize, usize) {
+        let line = self
+            .lines
+            .partition_point(|span| span.first_grapheme <= index)
+            .saturating_sub(1);
+        let span = self.lines[line];
+        let column = s[span.start..span.end]
+            .graphemes(true)
+            .take(index - span.first_grapheme)
+            .map(|grapheme| grapheme.width())
+            .sum();
+        (line, column)
+    }
+
+    // Grapheme index on `line` at display column `column`, or the end of that line
+    // if it is shorter. None when there is no such line.
+    pub fn index_at(&self, s: &str, line: usize, column: usize) -> Option<usize> {
+        let span = self.lines.get(line)?;
+        let mut index = span.first_grapheme;
+        let mut width = 0;
+        for grapheme in s[span.start..span.end].graphemes(true) {
+            width += grapheme.width();
+            if width > column {
+                break;
+            }
+            index += 1;
+        }
+        Some(index)
+    }
+}
+
+// Shorten `s` to at most `width` terminal columns, ending in `ellipsis` (e.g. "…") when
+// anything was cut. Widths are counted per grapheme, so "日本" takes four columns and an
+// emoji with a skin tone two, and a wide one that doesn't fit leaves a column unused.
+pub fn truncate(s: &str, width: usize, ellipsis: &str) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for grapheme in s.graphemes(true) {
+        let w = grapheme.width();
+        if used + w + ellipsis.width() > width {
+            break;
+        }
+        used += w;
+        out.push_str(grapheme);
+    }
+    out.push_str(ellipsis);
+    out
+}
+
+// Pad `s` with spaces to `width` terminal columns, for lining up plain text tables where
+// `{:<20}` would count a wide character as one column
+pub fn pad(s: &str, width: usize) -> String {
+    format!("{}{}", s, " ".repeat(width.saturating_sub(s.width())))
+}
+
+// Text for one table cell: line breaks, tabs and other control characters become
+// spaces, as the terminal would otherwise move the cursor and shift the columns after
+pub fn single_line(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+// Upper-case the first letter of each sentence: the start of the text, after `.`, `!`
+// or `?` and a space, and at the start of each line. A dot after an initial, a word with
+// dots of its own like "e.g." or a title like "Dr." doesn't end a sentence.
+pub fn capitalize_sentences(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut sentence_start = true;
+    let mut after_stop = false;
+    let mut word = String::new();
+    for c in s.chars() {
+        if sentence_start && c.is_alphanumeric() {
+            out.extend(c.to_uppercase());
+            sentence_start = false;
+            after_stop = false;
+        } else {
+            out.push(c);
+            if c == '\n' || (after_stop && c.is_whitespace()) {
+                sentence_start = true;
+            } else if !c.is_whitespace() && !sentence_start {
+                after_stop = match c {
+                    '.' => ends_sentence(&word),
+                    '!' | '?' => true,
+                    // A closing bracket or quote after the stop, like `(done.) Next`
+                    ')' | ']' | '"' | '\'' => after_stop,
+                    _ => false,
+                };
+            }
+        }
+        if c.is_whitespace() {
+            word.clear();
+        } else {
+            word.push(c);
+        }
+    }
+    out
+}
+
+// Abbreviations usually followed by more of the same sentence
+const ABBREVIATIONS: &[&str] = &[
+    "approx", "cf", "dr", "fig", "incl", "mr", "mrs", "ms", "no", "st", "vs",
+];
+
+// Whether a `.` right after `word` ends the sentence
+fn ends_sentence(word: &str) -> bool {
+    let word = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+    word.chars().count() > 1
+        && word.chars().all(char::is_alphanumeric)
+        && !ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+}
+
+// Lines joined with single spaces, blank lines dropped
+pub fn join_lines(s: &str) -> String {
+    s.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sentences_start_with_a_capital() {
+        assert_eq!(
+            capitalize_sentences("fixed it. tested it! done?  yes"),
+            "Fixed it. Tested it! Done?  Yes"
+        );
+        assert_eq!(
+            capitalize_sentences("first line\nsecond line"),
+            "First line\nSecond line"
+        );
+        assert_eq!(
+            capitalize_sentences("(done.) next \"ok.\" then"),
+            "(Done.) Next \"ok.\" Then"
+        );
+        assert_eq!(capitalize_sentences("v1.2 released"), "V1.2 released");
+    }
+
+    #[test]
+    fn abbreviations_and_initials_dont_end_a_sentence() {
+        assert_eq!(capitalize_sentences("e.g. foo"), "E.g. foo");
+        assert_eq!(
+            capitalize_sentences("tools, i.e. the cli"),
+            "Tools, i.e. the cli"
+        );
+        assert_eq!(
+            capitalize_sentences("options a. b. and c."),
+            "Options a. b. and c."
+        );
+        assert_eq!(
+            capitalize_sentences("call with dr. smith"),
+            "Call with dr. smith"
+        );
+        assert_eq!(
+            capitalize_sentences("see fig. 3 and no. 4"),
+            "See fig. 3 and no. 4"
+        );
+        assert_eq!(
+            capitalize_sentences("met J. Doe. then lunch"),
+            "Met J. Doe. Then lunch"
+        );
+    }
+
+    #[test]
+    fn lines_are_joined_without_blanks() {
+        assert_eq!(join_lines("  one\n\n two  \n\nthree"), "one two three");
+        assert_eq!(join_lines(""), "");
+    }
+
+    #[test]
+    fn cursors_step_over_whole_graphemes() {
+        let text = "e\u{301}👍🏽日x";
+        assert_eq!(grapheme_count(text), 4);
+        assert_eq!(byte_offset(text, 1), 3);
+        assert_eq!(byte_offset(text, 3), text.len() - 1);
+        assert_eq!(byte_offset(text, 9), text.len());
+
+        let mut text = text.to_string();
+        remove_before(&mut text, 2);
+        assert_eq!(text, "e\u{301}日x");
+        remove_before(&mut text, 0);
+        assert_eq!(text, "e\u{301}日x");
+    }
+
+    #[test]
+    fn inserting_a_combining_mark_keeps_the_cursor_on_its_letter() {
+        let mut text = "ab".to_string();
+        assert_eq!(insert_str(&mut text, 1, "日本"), 3);
+        assert_eq!(text, "a日本b");
+        assert_eq!(insert_str(&mut text, 1, "\u{301}"), 1);
+        assert_eq!(text, "a\u{301}日本b");
+        assert_eq!(insert_str(&mut text, 9, "!"), 5);
+    }
+
+    #[test]
+    fn line_index_maps_cursors_to_lines_and_columns() {
+        let text = "one\r\n日本 two\n\nend";
+        let index = LineIndex::new(text);
+        assert_eq!(index.line_and_column(text, 0), (0, 0));
+        assert_eq!(index.line_and_column(text, 3), (0, 3));
+        assert_eq!(index.line_and_column(text, 6), (1, 4));
+        assert_eq!(index.line_and_column(text, 11), (2, 0));
+        assert_eq!(index.line_and_column(text, 15), (3, 3));
+
+        assert_eq!(index.index_at(text, 1, 2), Some(5));
+        assert_eq!(index.index_at(text, 1, 3), Some(5));
+        assert_eq!(index.index_at(text, 0, 40), Some(3));
+        assert_eq!(index.index_at(text, 2, 5), Some(11));
+        assert_eq!(index.index_at(text, 4, 0), None);
+    }
+
+    #[test]
+    fn truncate_counts_terminal_columns() {
+        assert_eq!(truncate("short", 5, "…"), "short");
+        assert_eq!(truncate("too long", 5, "…"), "too …");
+        assert_eq!(truncate("日本語", 4, "…"), "日…");
+        assert_eq!(truncate("日本語", 5, "..."), "日...");
+        assert_eq!(truncate("ab", 0, ""), "");
+    }
+
+    #[test]
+    fn cells_are_padded_and_kept_on_one_line() {
+        assert_eq!(pad("日本", 6), "日本  ");
+        assert_eq!(pad("abc", 2), "abc");
+        assert_eq!(single_line("a\tb\r\nc"), "a b  c");
+    }
+}
@@ -0,0 +1,143 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols;
+
+use crate::config::Palette;
+
+// Styles for what things mean on screen, so each palette only decides how they look.
+// Selections and the active cell are bold as well as coloured, and the monochrome
+// palette (also used when NO_COLOR is set) relies on bold, reversed and dim alone.
+// With `ascii` the borders, bars and marks are drawn in plain ASCII for terminals and
+// fonts that mangle box drawing characters, e.g. over old SSH sessions.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    palette: Palette,
+    ascii: bool,
+}
+
+const ASCII_BORDER: symbols::border::Set = symbols::border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+const ASCII_BAR: symbols::bar::Set = symbols::bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "#",
+    five_eighths: "#",
+    half: "#",
+    three_eighths: "#",
+    one_quarter: "#",
+    one_eighth: "#",
+    empty: " ",
+};
+
+impl Theme {
+    pub fn new(palette: Palette, ascii: bool) -> Self {
+        // https://no-color.org: any non-empty value turns colour off
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        Self {
+            palette: if no_color {
+                Palette::Monochrome
+            } else {
+                palette
+            },
+            ascii,
+        }
+    }
+
+    // `unicode`, or `ascii` when only ASCII should be drawn
+    pub fn symbol(&self, unicode: &'static str, ascii: &'static str) -> &'static str {
+        if self.ascii {
+            ascii
+        } else {
+            unicode
+        }
+    }
+
+    pub fn border_set(&self) -> symbols::border::Set {
+        if self.ascii {
+            ASCII_BORDER
+        } else {
+            symbols::border::PLAIN
+        }
+    }
+
+    pub fn bar_set(&self) -> symbols::bar::Set {
+        if self.ascii {
+            ASCII_BAR
+        } else {
+            symbols::bar::NINE_LEVELS
+        }
+    }
+
+    // Marks the end of text cut short to fit its column
+    pub fn ellipsis(&self) -> &'static str {
+        self.symbol("…", ">")
+    }
+
+    fn fg(&self, default: Color, deuteranopia: Color, monochrome: Modifier) -> Style {
+        match self.palette {
+            Palette::Default => Style::default().fg(default),
+            Palette::Deuteranopia => Style::default().fg(deuteranopia),
+            Palette::Monochrome => Style::default().add_modifier(monochrome),
+        }
+    }
+
+    fn highlight(&self, default: Color, deuteranopia: Color) -> Style {
+        let style = match self.palette {
+            Palette::Default => Style::default().fg(Color::Black).bg(default),
+            Palette::Deuteranopia => Style::default().fg(Color::Black).bg(deuteranopia),
+            Palette::Monochrome => Style::default().add_modifier(Modifier::REVERSED),
+        };
+        style.add_modifier(Modifier::BOLD)
+    }
+
+    // Table headers and field labels
+    pub fn heading(&self) -> Style {
+        self.fg(Color::Yellow, Color::Yellow, Modifier::BOLD)
+    }
+
+    // The selected row or cell
+    pub fn selected(&self) -> Style {
+        self.highlight(Color::Cyan, Color::LightBlue)
+    }
+
+    // The cell being edited, or the part of a picker being changed
+    pub fn editing(&self) -> Style {
+        let style = self.highlight(Color::Yellow, Color::Yellow);
+        match self.palette {
+            Palette::Monochrome => style.add_modifier(Modifier::UNDERLINED),
+            _ => style,
+        }
+    }
+
+    // Failed saves, exceeded budgets and dialogs that throw data away
+    pub fn error(&self) -> Style {
+        self.fg(Color::Red, Color::Magenta, Modifier::BOLD)
+    }
+
+    // Things to double check, like entries flagged before exporting
+    pub fn warning(&self) -> Style {
+        self.fg(Color::Yellow, Color::Yellow, Modifier::empty())
+    }
+
+    pub fn success(&self) -> Style {
+        self.fg(Color::Green, Color::Blue, Modifier::empty())
+    }
+
+    // Informational dialogs and charts
+    pub fn accent(&self) -> Style {
+        self.fg(Color::Cyan, Color::LightBlue, Modifier::empty())
+    }
+
+    // Secondary text like the clock and activity hints
+    pub fn muted(&self) -> Style {
+        self.fg(Color::Gray, Color::Gray, Modifier::DIM)
+    }
+}
@@ -1,23 +1,105 @@
+use chrono::{DateTime, Local, NaiveTime, Timelike};
 use serde::{Deserialize, Serialize};
-use chrono::NaiveTime;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeEntry {
+    // Identifies the entry however rows move, for syncing and merging. Entries saved
+    // before IDs existed read as empty until `assign_ids` gives them one.
+    #[serde(default)]
+    pub id: String,
     pub task_number: String,
     pub work_code: String,
     pub time_entry: String,
     pub start_time: String,
     pub end_time: String,
+    // Content hash and time of the last export that included this entry
+    #[serde(default)]
+    pub exported_hash: Option<String>,
+    #[serde(default)]
+    pub exported_at: Option<DateTime<Local>>,
+    // Picked for the next "checked rows" export
+    #[serde(default)]
+    pub checked: bool,
+    // Columns kept from changing once approved, by key, e.g. "task_number"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected: Vec<String>,
+    // When the row was added, and when a change to its content was last saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<Local>>,
 }
 
 impl TimeEntry {
     pub fn new() -> Self {
         Self {
+            id: new_id(),
             task_number: String::new(),
             work_code: String::new(),
             time_entry: String::new(),
             start_time: String::new(),
             end_time: String::new(),
+            exported_hash: None,
+            exported_at: None,
+            checked: false,
+            protected: Vec::new(),
+            created: Some(Local::now()),
+            modified: None,
+        }
+    }
+
+    // The same content as a new entry with its own ID, e.g. to continue it in another row
+    pub fn copy_as_new(&self) -> Self {
+        Self {
+            id: new_id(),
+            created: Some(Local::now()),
+            modified: None,
+            ..self.clone()
+        }
+    }
+
+    // Text of a table column, 1 = Task Number through 5 = End Time
+    pub fn field(&self, col: usize) -> Option<&String> {
+        match col {
+            1 => Some(&self.task_number),
+            2 => Some(&self.work_code),
+            3 => Some(&self.time_entry),
+            4 => Some(&self.start_time),
+            5 => Some(&self.end_time),
+            _ => None,
+        }
+    }
+
+    pub fn field_name(col: usize) -> Option<&'static str> {
+        [
+            "Task Number",
+            "Work Code",
+            "Time Entry",
+            "Start Time",
+            "End Time",
+        ]
+        .get(col.checked_sub(1)?)
+        .copied()
+    }
+
+    // The snake_case key of a column for config and :protect, e.g. `task_number`
+    pub fn field_key(col: usize) -> Option<String> {
+        Self::field_name(col).map(|name| name.to_lowercase().replace(' ', "_"))
+    }
+
+    pub fn is_protected(&self, col: usize) -> bool {
+        Self::field_key(col).is_some_and(|key| self.protected.contains(&key))
+    }
+
+    pub fn field_mut(&mut self, col: usize) -> Option<&mut String> {
+        match col {
+            1 => Some(&mut self.task_number),
+            2 => Some(&mut self.work_code),
+            3 => Some(&mut self.time_entry),
+            4 => Some(&mut self.start_time),
+            5 => Some(&mut self.end_time),
+            _ => None,
         }
     }
 
@@ -37,7 +119,95 @@ impl TimeEntry {
             && self.end_time.is_empty()
     }
 
+    // Case-insensitive match of the task number, work code or description against a filter
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        !self.is_entirely_empty()
+            && [&self.task_number, &self.work_code, &self.time_entry]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&query))
+    }
+
+    // Stable FNV-1a hash of the exported fields, used to spot edits made after an export
+    pub fn content_hash(&self) -> String {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let fields = [
+            &self.task_number,
+            &self.work_code,
+            &self.time_entry,
+            &self.start_time,
+            &self.end_time,
+        ];
+        for field in fields {
+            for byte in field.bytes().chain(std::iter::once(0x1f)) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        format!("{:016x}", hash)
+    }
+
+    pub fn is_exported(&self) -> bool {
+        self.exported_hash.is_some()
+    }
+
+    // Exported before, and not edited since
+    pub fn is_exported_unchanged(&self) -> bool {
+        self.exported_hash.as_deref() == Some(self.content_hash().as_str())
+    }
+
+    // `hash` is the content_hash of the version that was exported, which differs from
+    // the current one when the entry was edited while the export was written
+    pub fn mark_exported(&mut self, hash: String) {
+        self.exported_hash = Some(hash);
+        self.exported_at = Some(Local::now());
+    }
+
+    // Problems that would make this entry unusable in an export
+    pub fn validation_issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        if self.is_entirely_empty() {
+            return issues;
+        }
+
+        let fields = [
+            (&self.task_number, "Task Number"),
+            (&self.work_code, "Work Code"),
+            (&self.time_entry, "Time Entry"),
+            (&self.start_time, "Start Time"),
+            (&self.end_time, "End Time"),
+        ];
+        for (value, name) in fields {
+            if value.is_empty() {
+                issues.push(format!("{} is missing", name));
+            }
+        }
+
+        let start = Self::parse_time(&self.start_time);
+        let end = Self::parse_time(&self.end_time);
+        if !self.start_time.is_empty() && start.is_none() {
+            issues.push(format!(
+                "Start Time '{}' is not a valid time",
+                self.start_time
+            ));
+        }
+        if !self.end_time.is_empty() && end.is_none() {
+            issues.push(format!("End Time '{}' is not a valid time", self.end_time));
+        }
+        if let (Some(start), Some(end)) = (start, end) {
+            if end < start {
+                issues.push("End Time is before Start Time".to_string());
+            }
+        }
+        issues
+    }
+
     pub fn calculate_task_time(&self) -> Option<String> {
+        let minutes = self.duration_minutes()?;
+        Some(format!("{:02}:{:02}", minutes / 60, minutes % 60))
+    }
+
+    pub fn duration_minutes(&self) -> Option<i64> {
         if self.start_time.is_empty() || self.end_time.is_empty() {
             return None;
         }
@@ -49,16 +219,13 @@ impl TimeEntry {
             return None; // invalid
         }
 
-        let duration = end - start;
-        let hours = duration.num_hours();
-        let minutes = duration.num_minutes() % 60;
-        Some(format!("{:02}:{:02}", hours, minutes))
+        Some((end - start).num_minutes())
     }
 
-    fn parse_time(time_str: &str) -> Option<NaiveTime> {
+    pub fn parse_time(time_str: &str) -> Option<NaiveTime> {
         // Support HH:MM or HHMM
         let time_str = time_str.replace(":", "");
-        if time_str.len() == 4 {
+        if time_str.len() == 4 && time_str.is_ascii() {
             let hour: u32 = time_str[0..2].parse().ok()?;
             let min: u32 = time_str[2..4].parse().ok()?;
             NaiveTime::from_hms_opt(hour, min, 0)
@@ -66,4 +233,253 @@ impl TimeEntry {
             None
         }
     }
-}
\ No newline at end of file
+}
+
+// An entry for tests, any other field set by struct update or `described`
+#[cfg(test)]
+pub fn entry(task_number: &str, work_code: &str, start: &str, end: &str) -> TimeEntry {
+    TimeEntry {
+        task_number: task_number.to_string(),
+        work_code: work_code.to_string(),
+        start_time: start.to_string(),
+        end_time: end.to_string(),
+        ..TimeEntry::new()
+    }
+}
+
+#[cfg(test)]
+impl TimeEntry {
+    pub fn described(self, description: &str) -> Self {
+        Self {
+            time_entry: description.to_string(),
+            ..self
+        }
+    }
+}
+
+// Relative times like `+15` or `-5` move `base` by that many minutes, and a time
+// followed by offsets (`09:00+15`) moves that time instead. The result stays within
+// the day. None when `value` isn't of that form or the offsets are too big to add up.
+pub fn apply_offset(value: &str, base: &str) -> Option<String> {
+    let value = value.trim();
+    let split = value.find(['+', '-'])?;
+    let (time, mut offsets) = value.split_at(split);
+    let time = if time.trim().is_empty() { base } else { time };
+    let time = TimeEntry::parse_time(time.trim())?;
+    let mut minutes = i64::from(time.hour() * 60 + time.minute());
+    while !offsets.is_empty() {
+        let sign = if offsets.starts_with('-') { -1 } else { 1 };
+        let rest = &offsets[1..];
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        let amount: i64 = rest[..end].trim().parse().ok()?;
+        minutes = minutes.checked_add(amount.checked_mul(sign)?)?;
+        offsets = &rest[end..];
+    }
+    let minutes = minutes.clamp(0, 23 * 60 + 59);
+    Some(format!("{:02}:{:02}", minutes / 60, minutes % 60))
+}
+
+// Trim the ends of a field, and for identifiers like task numbers also collapse
+// runs of whitespace to a single space
+pub fn normalize_whitespace(value: &str, collapse: bool) -> String {
+    if collapse {
+        value.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        value.trim().to_string()
+    }
+}
+
+// The last row with a start time but no end time yet, i.e. the one being timed
+pub fn running_entry(entries: &[TimeEntry]) -> Option<usize> {
+    entries
+        .iter()
+        .rposition(|e| !e.start_time.trim().is_empty() && e.end_time.trim().is_empty())
+}
+
+// How a row of today's sheet stands at `now`, for the When column: "in 20m" before it
+// starts, "running 45m" for the running entry, "ends in 10m" while it's under way and
+// "2h 5m ago" once it's over. Empty without usable times.
+pub fn relative(entry: &TimeEntry, running: bool, now: NaiveTime) -> String {
+    let start = TimeEntry::parse_time(&entry.start_time);
+    let end = TimeEntry::parse_time(&entry.end_time);
+    match (start, end) {
+        (Some(start), _) if now < start => {
+            format!("in {}", short_duration((start - now).num_minutes()))
+        }
+        (Some(start), None) if running => {
+            format!("running {}", short_duration((now - start).num_minutes()))
+        }
+        (_, Some(end)) if now < end => {
+            format!("ends in {}", short_duration((end - now).num_minutes()))
+        }
+        (_, Some(end)) => match (now - end).num_minutes() {
+            0 => "just now".to_string(),
+            minutes => format!("{} ago", short_duration(minutes)),
+        },
+        _ => String::new(),
+    }
+}
+
+// e.g. "45m", "2h" or "2h 5m", and "<1m" for under a minute
+fn short_duration(minutes: i64) -> String {
+    match minutes {
+        ..=0 => "<1m".to_string(),
+        1..=59 => format!("{}m", minutes),
+        _ if minutes % 60 == 0 => format!("{}h", minutes / 60),
+        _ => format!("{}h {}m", minutes / 60, minutes % 60),
+    }
+}
+
+// Human readable duration, e.g. "5h 20m"
+// A random (version 4) UUID, e.g. "3f2b8c1e-9a4d-4e7f-b1c2-5d6e7f8a9b0c"
+pub fn new_id() -> String {
+    use ring::rand::SecureRandom;
+    let mut bytes = [0u8; 16];
+    if ring::rand::SystemRandom::new().fill(&mut bytes).is_err() {
+        // Only unique rather than random then, which is all an ID needs
+        static COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let nanos = Local::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+        let count = COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        bytes[..8].copy_from_slice(&nanos.to_be_bytes());
+        bytes[8..].copy_from_slice(&(count ^ std::process::id() as u64).to_be_bytes());
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40; // Version 4
+    bytes[8] = (bytes[8] & 0x3f) | 0x80; // RFC 4122 variant
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+// Give entries saved before IDs existed their ID, true if any needed one so the caller
+// writes the file back and the IDs stay the same the next time it's read
+pub fn assign_ids(entries: &mut [TimeEntry]) -> bool {
+    let mut assigned = false;
+    for entry in entries.iter_mut().filter(|entry| entry.id.is_empty()) {
+        entry.id = new_id();
+        assigned = true;
+    }
+    assigned
+}
+
+// Content hashes by ID, as the entries were read or last saved
+pub fn saved_hashes(entries: &[TimeEntry]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .map(|entry| (entry.id.clone(), entry.content_hash()))
+        .collect()
+}
+
+// Before saving: a copy still carrying another row's ID gets its own, and entries
+// whose content differs from `saved` are stamped as modified `now`. Returns the hashes
+// the next save compares with.
+pub fn stamp(
+    entries: &mut [TimeEntry],
+    saved: &HashMap<String, String>,
+    now: DateTime<Local>,
+) -> HashMap<String, String> {
+    assign_ids(entries);
+    let mut seen = HashSet::new();
+    for entry in entries.iter_mut() {
+        if !seen.insert(entry.id.clone()) {
+            *entry = entry.copy_as_new();
+            seen.insert(entry.id.clone());
+        }
+        let hash = entry.content_hash();
+        if !entry.is_entirely_empty() && saved.get(&entry.id) != Some(&hash) {
+            entry.modified = Some(now);
+        }
+    }
+    saved_hashes(entries)
+}
+
+pub fn format_duration(minutes: i64) -> String {
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_version_4_uuids() {
+        let id = new_id();
+        let parts: Vec<&str> = id.split('-').collect();
+        assert_eq!(
+            parts.iter().map(|part| part.len()).collect::<Vec<_>>(),
+            [8, 4, 4, 4, 12]
+        );
+        assert!(parts[2].starts_with('4'));
+        assert!(matches!(
+            parts[3].chars().next(),
+            Some('8' | '9' | 'a' | 'b')
+        ));
+        assert_ne!(id, new_id());
+    }
+
+    #[test]
+    fn copied_rows_get_their_own_id() {
+        let first = entry("PROJ-1", "", "", "");
+        let copy = first.clone();
+        let mut entries = vec![first, copy];
+        stamp(&mut entries, &HashMap::new(), Local::now());
+        assert_ne!(entries[0].id, entries[1].id);
+        assert_eq!(entries[1].task_number, "PROJ-1");
+    }
+
+    #[test]
+    fn modified_is_only_set_when_content_changes() {
+        let mut entries = vec![entry("PROJ-1", "", "", ""), entry("PROJ-2", "", "", "")];
+        let saved = saved_hashes(&entries);
+        entries[1].time_entry = "Review".to_string();
+        let now = Local::now();
+        stamp(&mut entries, &saved, now);
+        assert_eq!(entries[0].modified, None);
+        assert_eq!(entries[1].modified, Some(now));
+    }
+
+    #[test]
+    fn offsets_move_the_base_time() {
+        assert_eq!(apply_offset("+15", "09:00").as_deref(), Some("09:15"));
+        assert_eq!(apply_offset("-5", "09:00").as_deref(), Some("08:55"));
+        assert_eq!(
+            apply_offset("10:00+15-5", "09:00").as_deref(),
+            Some("10:10")
+        );
+        assert_eq!(apply_offset("+90", "").as_deref(), None);
+    }
+
+    #[test]
+    fn offsets_stay_within_the_day() {
+        assert_eq!(apply_offset("+120", "23:00").as_deref(), Some("23:59"));
+        assert_eq!(apply_offset("-600", "09:00").as_deref(), Some("00:00"));
+    }
+
+    #[test]
+    fn offsets_that_overflow_are_refused() {
+        assert_eq!(apply_offset("+9223372036854775807+1", "09:00"), None);
+        assert_eq!(
+            apply_offset("-9223372036854775807-9223372036854775807", "09:00"),
+            None
+        );
+        assert_eq!(apply_offset("+99999999999999999999", "09:00"), None);
+        assert_eq!(apply_offset("09:00+x", "09:00"), None);
+    }
+
+    #[test]
+    fn entries_without_an_id_get_one() {
+        let mut entries: Vec<TimeEntry> = serde_json::from_str(
+            r#"[{"task_number":"PROJ-1","work_code":"","time_entry":"","start_time":"","end_time":""}]"#,
+        )
+        .unwrap();
+        assert!(entries[0].id.is_empty());
+        assert!(assign_ids(&mut entries));
+        assert!(!entries[0].id.is_empty());
+        assert!(!assign_ids(&mut entries));
+    }
+}
@@ -0,0 +1,285 @@
+use chrono::{NaiveDate, Timelike};
+use std::collections::BTreeMap;
+
+use crate::archive::Day;
+use crate::time_entry::TimeEntry;
+
+// Time logged against one task number over a date range
+#[derive(Debug, Clone)]
+pub struct TaskTotal {
+    pub task_number: String,
+    pub minutes: i64,
+    pub days: usize, // Number of days with time on this task
+}
+
+impl TaskTotal {
+    pub fn hours(&self) -> f64 {
+        self.minutes as f64 / 60.0
+    }
+}
+
+// Archived days in `from..=to`, with `today` (the current entries and note) standing
+// in for its archived copy so unexported work is counted too
+pub fn days_in_range(archived: Vec<Day>, today: Day, from: NaiveDate, to: NaiveDate) -> Vec<Day> {
+    let mut days: Vec<Day> = archived
+        .into_iter()
+        .filter(|day| day.date >= from && day.date <= to && day.date != today.date)
+        .collect();
+    if today.date >= from && today.date <= to {
+        days.push(today);
+    }
+    days
+}
+
+// Total minutes logged on each date from `from` to `to`, including days without any
+pub fn daily_minutes(days: &[Day], from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, i64)> {
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| {
+            let minutes = days
+                .iter()
+                .filter(|day| day.date == date)
+                .flat_map(|day| &day.entries)
+                .filter_map(|entry| entry.duration_minutes())
+                .sum();
+            (date, minutes)
+        })
+        .collect()
+}
+
+// Minutes logged in each hour of the day over the days, index 9 being 09:00-10:00. An
+// entry from 09:30 to 11:15 adds 30, 60 and 15 minutes to hours 9, 10 and 11.
+pub fn hourly_minutes(days: &[Day]) -> [i64; 24] {
+    let mut hours = [0; 24];
+    for entry in days.iter().flat_map(|day| &day.entries) {
+        let (Some(minutes), Some(start)) = (
+            entry.duration_minutes(),
+            TimeEntry::parse_time(&entry.start_time),
+        ) else {
+            continue;
+        };
+        let mut minute = i64::from(start.num_seconds_from_midnight() / 60);
+        let end = minute + minutes;
+        while minute < end {
+            let hour = minute / 60;
+            let next = ((hour + 1) * 60).min(end);
+            hours[hour as usize] += next - minute;
+            minute = next;
+        }
+    }
+    hours
+}
+
+// Cumulative time per task number, sorted by task number
+pub fn task_totals(days: &[Day]) -> Vec<TaskTotal> {
+    let mut totals: BTreeMap<String, TaskTotal> = BTreeMap::new();
+    for day in days {
+        let mut seen_today: Vec<&str> = Vec::new();
+        for entry in &day.entries {
+            let Some(minutes) = entry.duration_minutes() else {
+                continue;
+            };
+            let task_number = entry.task_number.trim();
+            let total = totals
+                .entry(task_number.to_string())
+                .or_insert_with(|| TaskTotal {
+                    task_number: task_number.to_string(),
+                    minutes: 0,
+                    days: 0,
+                });
+            total.minutes += minutes;
+            if !seen_today.contains(&task_number) {
+                seen_today.push(task_number);
+                total.days += 1;
+            }
+        }
+    }
+    totals.into_values().collect()
+}
+
+// One row of the timesheet grid: a task and work code with the minutes of each day
+#[derive(Debug, Clone)]
+pub struct GridRow {
+    pub task_number: String,
+    pub work_code: String,
+    pub minutes: Vec<i64>, // One per day of the grid
+}
+
+impl GridRow {
+    pub fn total(&self) -> i64 {
+        self.minutes.iter().sum()
+    }
+}
+
+// Minutes per task/work code combination and day, the timesheet layout many ERPs
+// take. Days without time still get a column.
+pub fn timesheet_grid(
+    days: &[Day],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> (Vec<NaiveDate>, Vec<GridRow>) {
+    let dates: Vec<NaiveDate> = from.iter_days().take_while(|date| *date <= to).collect();
+    let mut rows: BTreeMap<(String, String), GridRow> = BTreeMap::new();
+    for day in days {
+        let Some(column) = dates.iter().position(|date| *date == day.date) else {
+            continue;
+        };
+        for entry in &day.entries {
+            let Some(minutes) = entry.duration_minutes() else {
+                continue;
+            };
+            let key = (
+                entry.task_number.trim().to_string(),
+                entry.work_code.trim().to_string(),
+            );
+            let row = rows.entry(key.clone()).or_insert_with(|| GridRow {
+                task_number: key.0,
+                work_code: key.1,
+                minutes: vec![0; dates.len()],
+            });
+            row.minutes[column] += minutes;
+        }
+    }
+    (dates, rows.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_entry::entry;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 10, day).unwrap()
+    }
+
+    fn day(day: u32, entries: Vec<TimeEntry>) -> Day {
+        Day {
+            date: date(day),
+            entries,
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn today_replaces_its_archived_copy() {
+        let archived = vec![
+            day(1, vec![entry("T-1", "", "09:00", "10:00")]),
+            day(5, vec![entry("T-1", "", "09:00", "10:00")]),
+            day(8, vec![entry("T-1", "", "09:00", "10:00")]),
+        ];
+        let today = day(8, vec![entry("T-1", "", "09:00", "12:00")]);
+        let days = days_in_range(archived, today, date(2), date(8));
+        let dates: Vec<NaiveDate> = days.iter().map(|day| day.date).collect();
+        assert_eq!(dates, vec![date(5), date(8)]);
+        assert_eq!(days[1].entries[0].end_time, "12:00");
+
+        let today = day(9, Vec::new());
+        assert_eq!(days_in_range(Vec::new(), today, date(2), date(8)).len(), 0);
+    }
+
+    #[test]
+    fn task_totals_count_each_day_once() {
+        let days = vec![
+            day(
+                1,
+                vec![
+                    entry("T-1", "", "09:00", "10:00"),
+                    entry(" T-1 ", "", "11:00", "11:30"),
+                ],
+            ),
+            day(
+                2,
+                vec![
+                    entry("T-1", "", "09:00", "09:15"),
+                    entry("T-2", "", "10:00", ""),
+                ],
+            ),
+            day(3, vec![entry("T-2", "", "09:00", "10:00")]),
+        ];
+        let totals = task_totals(&days);
+        assert_eq!(totals.len(), 2);
+        assert_eq!(
+            (
+                totals[0].task_number.as_str(),
+                totals[0].minutes,
+                totals[0].days
+            ),
+            ("T-1", 105, 2)
+        );
+        assert_eq!(
+            (
+                totals[1].task_number.as_str(),
+                totals[1].minutes,
+                totals[1].days
+            ),
+            ("T-2", 60, 1)
+        );
+        assert_eq!(totals[0].hours(), 1.75);
+    }
+
+    #[test]
+    fn daily_minutes_include_empty_days() {
+        let days = vec![
+            day(
+                1,
+                vec![
+                    entry("T-1", "", "09:00", "10:00"),
+                    entry("T-2", "", "10:00", "10:45"),
+                ],
+            ),
+            day(3, vec![entry("T-1", "", "09:00", "09:30")]),
+        ];
+        assert_eq!(
+            daily_minutes(&days, date(1), date(4)),
+            vec![(date(1), 105), (date(2), 0), (date(3), 30), (date(4), 0)]
+        );
+    }
+
+    #[test]
+    fn hourly_minutes_split_entries_at_the_hour() {
+        let days = vec![
+            day(
+                1,
+                vec![
+                    entry("T-1", "", "09:30", "11:15"),
+                    entry("T-1", "", "23:00", "23:59"),
+                ],
+            ),
+            day(
+                2,
+                vec![entry("T-1", "", "09:00", "09:20"), entry("T-1", "", "", "")],
+            ),
+        ];
+        let hours = hourly_minutes(&days);
+        assert_eq!(&hours[9..12], &[50, 60, 15]);
+        assert_eq!(hours[23], 59);
+        assert_eq!(hours.iter().sum::<i64>(), 184);
+    }
+
+    #[test]
+    fn grid_has_a_row_per_task_and_work_code() {
+        let days = vec![
+            day(
+                1,
+                vec![
+                    entry("T-1", "DEV", "09:00", "10:00"),
+                    entry("T-1", "OPS", "10:00", "10:30"),
+                ],
+            ),
+            day(3, vec![entry("T-1", "DEV ", "09:00", "09:45")]),
+            day(9, vec![entry("T-1", "DEV", "09:00", "17:00")]), // Outside the grid
+        ];
+        let (dates, rows) = timesheet_grid(&days, date(1), date(3));
+        assert_eq!(dates, vec![date(1), date(2), date(3)]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            (rows[0].work_code.as_str(), rows[0].minutes.clone()),
+            ("DEV", vec![60, 0, 45])
+        );
+        assert_eq!(
+            (rows[1].work_code.as_str(), rows[1].minutes.clone()),
+            ("OPS", vec![30, 0, 0])
+        );
+        assert_eq!(rows[0].total(), 105);
+    }
+}
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::time_entry::{self, TimeEntry};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedEntry {
+    pub entry: TimeEntry,
+    pub deleted_at: DateTime<Local>,
+}
+
+pub fn load(data_dir: &Path) -> Result<Vec<TrashedEntry>> {
+    let file = data_dir.join("trash.json");
+    if !file.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(file)?;
+    let mut trash: Vec<TrashedEntry> = serde_json::from_str(&content)?;
+    let mut assigned = false;
+    for trashed in &mut trash {
+        assigned |= time_entry::assign_ids(std::slice::from_mut(&mut trashed.entry));
+    }
+    if assigned {
+        save(data_dir, &trash)?;
+    }
+    Ok(trash)
+}
+
+pub fn save(data_dir: &Path, trash: &[TrashedEntry]) -> Result<()> {
+    let content = serde_json::to_string(trash)?;
+    fs::write(data_dir.join("trash.json"), content)?;
+    Ok(())
+}
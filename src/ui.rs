@@ -1,19 +1,34 @@
+use chrono::Local;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    text::{Line, Text},
-    widgets::{Block, Borders, Clear, Paragraph, Table, TableState, Wrap},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::block::{Position, Title},
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Clear, Paragraph, Table, TableState, Wrap},
     Frame,
 };
-use chrono::Local;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 use crate::app::{App, InputMode};
+use crate::config::{Density, FirstColumn, Keymap, PaneLayout, Popup};
+use crate::editor::FieldEditor;
+use crate::fill;
+use crate::markdown;
+use crate::text;
+use crate::time_entry::{self, format_duration, TimeEntry};
 
 pub fn draw(f: &mut Frame, app: &App) {
     let size = f.size();
 
     match app.mode {
-        InputMode::Help => {
+        InputMode::Help
+        | InputMode::Report
+        | InputMode::Trash
+        | InputMode::History
+        | InputMode::Log
+        | InputMode::Debug
+        | InputMode::WhatsNew => {
             let constraints = if app.config.ui.time_bar.show {
                 [Constraint::Length(1), Constraint::Min(1)].as_ref()
             } else {
@@ -24,16 +39,30 @@ pub fn draw(f: &mut Frame, app: &App) {
                 .constraints(constraints)
                 .split(size);
 
-            if app.config.ui.time_bar.show {
+            let area = if app.config.ui.time_bar.show {
                 draw_time_bar(f, app, chunks[0]);
-                draw_help(f, app, chunks[1]);
+                chunks[1]
             } else {
-                draw_help(f, app, chunks[0]);
+                chunks[0]
+            };
+            match app.mode {
+                InputMode::Report => draw_report(f, app, area),
+                InputMode::Trash => draw_trash(f, app, area),
+                InputMode::History => draw_history(f, app, area),
+                InputMode::Log => draw_log(f, app, area),
+                InputMode::Debug => draw_debug(f, app, area),
+                InputMode::WhatsNew => draw_whats_new(f, app, area),
+                _ => draw_help(f, app, area),
             }
         }
         InputMode::EditingPopup | InputMode::ViewingPopup => {
             let constraints = if app.config.ui.time_bar.show {
-                [Constraint::Length(1), Constraint::Min(1), Constraint::Length(3)].as_ref()
+                [
+                    Constraint::Length(1),
+                    Constraint::Min(1),
+                    Constraint::Length(3),
+                ]
+                .as_ref()
             } else {
                 [Constraint::Min(1), Constraint::Length(3)].as_ref()
             };
@@ -44,17 +73,37 @@ pub fn draw(f: &mut Frame, app: &App) {
 
             if app.config.ui.time_bar.show {
                 draw_time_bar(f, app, chunks[0]);
-                draw_table(f, app, chunks[1]);
+                draw_main(f, app, chunks[1]);
                 draw_status(f, app, chunks[2]);
             } else {
-                draw_table(f, app, chunks[0]);
+                draw_main(f, app, chunks[0]);
                 draw_status(f, app, chunks[1]);
             }
             draw_popup(f, app, size);
         }
-        InputMode::ConfirmDeleteEntry => {
+        InputMode::ConfirmDeleteEntry
+        | InputMode::ConfirmClearEntries
+        | InputMode::ExportMenu
+        | InputMode::Prompt
+        | InputMode::ConfirmQuit
+        | InputMode::ConfirmExport
+        | InputMode::ConfirmCoalesce
+        | InputMode::ConfirmDedupe
+        | InputMode::ConfirmFill
+        | InputMode::ConfirmReplace
+        | InputMode::Rollover
+        | InputMode::ClockJump
+        | InputMode::Repair
+        | InputMode::PickDescription
+        | InputMode::Validation
+        | InputMode::TimePicker => {
             let constraints = if app.config.ui.time_bar.show {
-                [Constraint::Length(1), Constraint::Min(1), Constraint::Length(3)].as_ref()
+                [
+                    Constraint::Length(1),
+                    Constraint::Min(1),
+                    Constraint::Length(3),
+                ]
+                .as_ref()
             } else {
                 [Constraint::Min(1), Constraint::Length(3)].as_ref()
             };
@@ -65,17 +114,39 @@ pub fn draw(f: &mut Frame, app: &App) {
 
             if app.config.ui.time_bar.show {
                 draw_time_bar(f, app, chunks[0]);
-                draw_table(f, app, chunks[1]);
+                draw_main(f, app, chunks[1]);
                 draw_status(f, app, chunks[2]);
             } else {
-                draw_table(f, app, chunks[0]);
+                draw_main(f, app, chunks[0]);
                 draw_status(f, app, chunks[1]);
             }
-            draw_confirm_delete_dialog(f, app, size);
+            match app.mode {
+                InputMode::ConfirmDeleteEntry => draw_confirm_delete_dialog(f, app, size),
+                InputMode::ConfirmClearEntries => draw_confirm_clear_dialog(f, app, size),
+                InputMode::Prompt => draw_prompt(f, app, size),
+                InputMode::ConfirmQuit => draw_confirm_quit_dialog(f, app, size),
+                InputMode::ConfirmExport => draw_confirm_export_dialog(f, app, size),
+                InputMode::TimePicker => draw_time_picker(f, app, size),
+                InputMode::ConfirmCoalesce => draw_confirm_coalesce_dialog(f, app, size),
+                InputMode::ConfirmDedupe => draw_confirm_dedupe_dialog(f, app, size),
+                InputMode::ConfirmFill => draw_confirm_fill_dialog(f, app, size),
+                InputMode::ConfirmReplace => draw_confirm_replace_dialog(f, app, size),
+                InputMode::Rollover => draw_rollover_dialog(f, app, size),
+                InputMode::ClockJump => draw_clock_jump_dialog(f, app, size),
+                InputMode::Repair => draw_repair_dialog(f, app, size),
+                InputMode::PickDescription => draw_pick_description_dialog(f, app, size),
+                InputMode::Validation => draw_validation_dialog(f, app, size),
+                _ => draw_export_menu(f, app, size),
+            }
         }
-        InputMode::ConfirmClearEntries => {
+        _ => {
             let constraints = if app.config.ui.time_bar.show {
-                [Constraint::Length(1), Constraint::Min(1), Constraint::Length(3)].as_ref()
+                [
+                    Constraint::Length(1),
+                    Constraint::Min(1),
+                    Constraint::Length(3),
+                ]
+                .as_ref()
             } else {
                 [Constraint::Min(1), Constraint::Length(3)].as_ref()
             };
@@ -86,109 +157,373 @@ pub fn draw(f: &mut Frame, app: &App) {
 
             if app.config.ui.time_bar.show {
                 draw_time_bar(f, app, chunks[0]);
-                draw_table(f, app, chunks[1]);
+                draw_main(f, app, chunks[1]);
                 draw_status(f, app, chunks[2]);
             } else {
-                draw_table(f, app, chunks[0]);
+                draw_main(f, app, chunks[0]);
                 draw_status(f, app, chunks[1]);
             }
-            draw_confirm_clear_dialog(f, app, size);
         }
-        _ => {
-            let constraints = if app.config.ui.time_bar.show {
-                [Constraint::Length(1), Constraint::Min(1), Constraint::Length(3)].as_ref()
-            } else {
-                [Constraint::Min(1), Constraint::Length(3)].as_ref()
-            };
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints(constraints)
-                .split(size);
+    }
+    if let Some(step) = app.tutorial {
+        draw_tutorial(f, app, step, size);
+    }
+}
 
-            if app.config.ui.time_bar.show {
-                draw_time_bar(f, app, chunks[0]);
-                draw_table(f, app, chunks[1]);
-                draw_status(f, app, chunks[2]);
-            } else {
-                draw_table(f, app, chunks[0]);
-                draw_status(f, app, chunks[1]);
+// The current --demo tutorial hint, in the bottom right corner above the status bar
+fn draw_tutorial(f: &mut Frame, app: &App, step: usize, area: Rect) {
+    let width = area.width.min(50);
+    let height = 5.min(area.height);
+    let popup_area = Rect {
+        x: area.width.saturating_sub(width + 2),
+        y: area.height.saturating_sub(height + 4),
+        width,
+        height,
+    };
+    f.render_widget(Clear, popup_area);
+    let paragraph = Paragraph::new(crate::demo::STEPS[step].hint)
+        .block(
+            bordered(app)
+                .title(format!(
+                    "Tutorial {}/{}",
+                    step + 1,
+                    crate::demo::STEPS.len()
+                ))
+                .style(app.theme.success()),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, popup_area);
+}
+
+// The entry table, plus the detail panel when the split layout is configured
+fn draw_main(f: &mut Frame, app: &App, area: Rect) {
+    if app.config.ui.layout == PaneLayout::Split {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+        draw_table(f, app, chunks[0]);
+        draw_details(f, app, chunks[1]);
+    } else {
+        draw_table(f, app, area);
+    }
+}
+
+fn draw_details(f: &mut Frame, app: &App, area: Rect) {
+    let mut lines: Vec<Line> = Vec::new();
+    if let Some(entry) = app.entries.get(app.cursor.row) {
+        let label = app.theme.heading();
+        lines.push(Line::from(vec![
+            Span::styled("Task:      ", label),
+            Span::raw(entry.task_number.clone()),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("Work Code: ", label),
+            Span::raw(entry.work_code.clone()),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("Time:      ", label),
+            Span::raw(format!("{} - {}", entry.start_time, entry.end_time)),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("Duration:  ", label),
+            Span::raw(
+                entry
+                    .calculate_task_time()
+                    .unwrap_or_else(|| "--:--".to_string()),
+            ),
+        ]));
+        lines.push(Line::from(""));
+        lines.push(Line::styled("Description", label));
+        lines.extend(entry.time_entry.lines().map(|l| Line::from(l.to_string())));
+
+        let mut issues = entry.validation_issues();
+        for warning in app.row_warnings(entry) {
+            if !issues.contains(&warning) {
+                issues.push(warning);
             }
         }
+        if !issues.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::styled("Issues", app.theme.error()));
+            lines.extend(
+                issues
+                    .into_iter()
+                    .map(|issue| Line::styled(format!("- {}", issue), app.theme.error())),
+            );
+        }
     }
+
+    let title = format!("Row {}", app.cursor.row + 1);
+    let paragraph = Paragraph::new(lines)
+        .block(bordered(app).title(title))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(paragraph, area);
+}
+
+// Table column widths: row number, task number, work code, time entry, start and end time
+const COLUMN_WIDTHS: [u16; 6] = [5, 15, 15, 30, 12, 12];
+
+// "running 10h 59m" and a space
+const RELATIVE_WIDTH: u16 = 16;
+
+// Whether the selected cell in column `col` is too narrow for its value, brackets included
+fn is_truncated(col: usize, value: &str) -> bool {
+    text::single_line(value).width() > (COLUMN_WIDTHS[col] as usize).saturating_sub(2)
 }
 
 fn draw_table(f: &mut Frame, app: &App, area: Rect) {
-    let header = ["#", "Task Number", "Work Code", "Time Entry", "Start Time", "End Time"];
-
-    let rows: Vec<ratatui::widgets::Row> = app.entries.iter().enumerate().map(|(i, entry)| {
-        let row_num = if i == app.cursor.row { ">>".to_string() } else { (i + 1).to_string() };
-        
-        let is_current_row = i == app.cursor.row;
-        let active_cell_style = match app.mode {
-            InputMode::Editing | InputMode::EditingPopup => Style::default()
-                .fg(Color::Black)
-                .bg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-            _ => Style::default()
-                .fg(Color::Black)
-                .bg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        };
-        
-        // Create cells with conditional styling
-        let cell_data = vec![
-            row_num,
-            entry.task_number.clone(),
-            entry.work_code.clone(),
-            entry.time_entry.replace("\n", " "),
-            entry.start_time.clone(),
-            entry.end_time.clone(),
-        ];
-        
-        let cells: Vec<Text> = cell_data.into_iter().enumerate().map(|(col_idx, content)| {
-            // app.cursor.col is 1-indexed (1=Task Number, 2=Work Code, etc.)
-            // cell_data array is 0-indexed (0=row number, 1=Task Number, etc.)
-            // So app.cursor.col should equal col_idx for the active cell
-            if is_current_row && app.cursor.col == col_idx {
-                // Add text cursor when in editing mode, but NOT when popup is active
-                let display_content = if matches!(app.mode, InputMode::Editing) && !matches!(app.mode, InputMode::EditingPopup | InputMode::ViewingPopup) {
-                    // Insert cursor indicator at text_cursor position
-                    let mut chars: Vec<char> = content.chars().collect();
-                    if app.text_cursor <= chars.len() {
-                        chars.insert(app.text_cursor, '|');
+    let first_column = app.config.ui.first_column;
+    let mut column_widths = COLUMN_WIDTHS.to_vec();
+    let first_header = match first_column {
+        FirstColumn::Number | FirstColumn::None => "#",
+        FirstColumn::Status => "",
+        FirstColumn::Date => {
+            column_widths[0] = 6;
+            "Date"
+        }
+    };
+    // Columns after this one are drawn, `none` skips the first
+    let skip = usize::from(first_column == FirstColumn::None);
+    let mut header = vec![
+        first_header,
+        "Task Number",
+        "Work Code",
+        "Time Entry",
+        "Start Time",
+        "End Time",
+    ];
+    // The When column only means something on today's sheet
+    let now = Local::now();
+    let relative = app
+        .relative_times
+        .then(|| (app.running_entry(), now.time()));
+    let relative = relative.filter(|_| app.day == now.date_naive());
+    if app.relative_times {
+        header.push("When");
+        column_widths.push(RELATIVE_WIDTH);
+    }
+    let row_margin = match app.config.ui.density {
+        Density::Comfortable => 1,
+        Density::Compact => 0,
+    };
+
+    let listed: Vec<(usize, &TimeEntry)> = app
+        .entries
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| app.is_listed(*i))
+        .collect();
+    // Which of the exported, changed, checked, protected and warning marks are in view,
+    // for the legend
+    let mut shown = [false; 5];
+    let rows: Vec<ratatui::widgets::Row> = listed
+        .iter()
+        .map(|&(i, entry)| {
+            let is_current_row = i == app.cursor.row;
+            // Mark rows already exported, or edited since their last export
+            let mut marks = String::new();
+            if entry.is_exported_unchanged() {
+                marks.push_str(app.theme.symbol("✓", "+"));
+                shown[0] = true;
+            } else if entry.is_exported() {
+                marks.push('~');
+                shown[1] = true;
+            }
+            if entry.checked {
+                marks.push_str(app.theme.symbol("•", "*"));
+                shown[2] = true;
+            }
+            if !entry.protected.is_empty() {
+                marks.push('#');
+                shown[3] = true;
+            }
+            if !app.row_warnings(entry).is_empty() {
+                marks.push('!');
+                shown[4] = true;
+            }
+            let pointer = if is_current_row { ">" } else { " " };
+            let row_num = match first_column {
+                FirstColumn::Number | FirstColumn::None if is_current_row => format!(">>{}", marks),
+                FirstColumn::Number | FirstColumn::None => format!("{}{}", i + 1, marks),
+                FirstColumn::Status => format!("{}{}", pointer, marks),
+                FirstColumn::Date => match entry.exported_at {
+                    Some(at) => format!("{}{}", pointer, at.format("%m-%d")),
+                    None => format!("{}-", pointer),
+                },
+            };
+
+            let active_cell_style = match app.mode {
+                InputMode::Editing | InputMode::EditingPopup => app.theme.editing(),
+                _ => app.theme.selected(),
+            };
+
+            // Create cells with conditional styling
+            let mut cell_data = vec![
+                row_num,
+                text::single_line(&entry.task_number),
+                text::single_line(&entry.work_code),
+                text::single_line(&entry.time_entry),
+                text::single_line(&entry.start_time),
+                text::single_line(&entry.end_time),
+            ];
+            // A folded group shows its rows' count and total, from the first start to the last end
+            if let Some(folded) = app.folded_rows(i) {
+                let minutes: i64 = folded
+                    .iter()
+                    .filter_map(|&row| app.entries[row].duration_minutes())
+                    .sum();
+                cell_data[3] = format!(
+                    "{} {} rows, {} - {}",
+                    app.theme.symbol("▸", ">"),
+                    folded.len(),
+                    format_duration(minutes),
+                    cell_data[3]
+                );
+                if let Some(&last) = folded.last() {
+                    cell_data[5] = text::single_line(&app.entries[last].end_time);
+                }
+            }
+            if app.relative_times {
+                cell_data.push(match relative {
+                    Some((running, now)) => time_entry::relative(entry, running == Some(i), now),
+                    None => String::new(),
+                });
+            }
+
+            let cells: Vec<Text> = cell_data
+                .into_iter()
+                .enumerate()
+                .map(|(col_idx, content)| {
+                    // app.cursor.col is 1-indexed (1=Task Number, 2=Work Code, etc.)
+                    // cell_data array is 0-indexed (0=row number, 1=Task Number, etc.)
+                    // So app.cursor.col should equal col_idx for the active cell
+                    if is_current_row && app.cursor.col == col_idx {
+                        // Add text cursor when in editing mode, but NOT when popup is active
+                        if matches!(app.mode, InputMode::Editing) {
+                            // Show the editor's cursor and selection, descriptions on one line
+                            let mut spans = vec![Span::styled("[", active_cell_style)];
+                            for (i, line) in editor_lines(&app.editor, active_cell_style)
+                                .into_iter()
+                                .enumerate()
+                            {
+                                if i > 0 {
+                                    spans.push(Span::styled(" ", active_cell_style));
+                                }
+                                spans.extend(line.spans);
+                            }
+                            spans.push(Span::styled("]", active_cell_style));
+                            return Text::from(Line::from(spans));
+                        }
+                        let width = (COLUMN_WIDTHS[col_idx] as usize).saturating_sub(2);
+                        Text::styled(
+                            format!(
+                                "[{}]",
+                                text::truncate(&content, width, app.theme.ellipsis())
+                            ),
+                            active_cell_style,
+                        )
+                    } else if entry.is_protected(col_idx) {
+                        // Protected cells are dimmed like a locked day
+                        Text::styled(
+                            text::truncate(
+                                &content,
+                                column_widths[col_idx] as usize,
+                                app.theme.ellipsis(),
+                            ),
+                            app.theme.muted(),
+                        )
+                    } else {
+                        Text::raw(text::truncate(
+                            &content,
+                            column_widths[col_idx] as usize,
+                            app.theme.ellipsis(),
+                        ))
                     }
-                    format!("[{}]", chars.into_iter().collect::<String>())
-                } else {
-                    format!("[{}]", content)
-                };
-                Text::styled(display_content, active_cell_style)
+                })
+                .skip(skip)
+                .collect();
+
+            let row = ratatui::widgets::Row::new(cells).bottom_margin(row_margin);
+            if app.locked {
+                row.style(app.theme.muted())
             } else {
-                Text::raw(content)
+                row
             }
-        }).collect();
-        
-        ratatui::widgets::Row::new(cells).bottom_margin(1)
-    }).collect();
+        })
+        .collect();
 
-    let widths = [
-        Constraint::Length(3),
-        Constraint::Length(15),
-        Constraint::Length(15),
-        Constraint::Length(30),
-        Constraint::Length(12),
-        Constraint::Length(12),
-    ];
+    let widths: Vec<Constraint> = column_widths
+        .into_iter()
+        .skip(skip)
+        .map(Constraint::Length)
+        .collect();
 
-    let table = Table::new(rows).widths(&widths)
+    let mut title = match &app.project {
+        Some(project) => format!("Slothtime - {}", project),
+        None if app.demo.is_some() => {
+            "Slothtime demo - sample data, deleted when you quit".to_string()
+        }
+        None => "Slothtime".to_string(),
+    };
+    if let Some(profile) = &app.config.profile {
+        title.push_str(&format!(" ({} profile)", profile));
+    }
+    if !app.day_note.is_empty() {
+        title.push_str(&format!(" - {}", app.day_note));
+    }
+    if app.follow.is_some() {
+        title.push_str(" - following, read-only");
+    } else if app.past_day {
+        title.push_str(&format!(
+            " - {} from the archive, saved back to it",
+            app.day.format("%a %Y-%m-%d")
+        ));
+    } else if app.locked {
+        title.push_str(" - locked, :unlock to edit");
+    }
+    if let Some(filter) = &app.filter {
+        title.push_str(&format!(" - rows containing '{}'", filter));
+    }
+    if app.grouped.is_some() {
+        title.push_str(" - grouped by task number, Enter opens a group");
+    }
+    let mut block = bordered(app).title(title);
+    // The date column shows export dates instead of marks, and `none` hides the column
+    let marks_shown = matches!(first_column, FirstColumn::Number | FirstColumn::Status);
+    if app.legend && marks_shown && shown.contains(&true) {
+        let meanings = [
+            format!("{} exported", app.theme.symbol("✓", "+")),
+            "~ changed since export".to_string(),
+            format!("{} checked for export", app.theme.symbol("•", "*")),
+            "# protected, :unprotect".to_string(),
+            "! needs attention, V lists it".to_string(),
+        ];
+        let legend: Vec<String> = meanings
+            .into_iter()
+            .zip(shown)
+            .filter_map(|(meaning, shown)| shown.then_some(meaning))
+            .collect();
+        block = block.title(
+            Title::from(Line::styled(
+                format!(" {} ", legend.join("   ")),
+                app.theme.muted(),
+            ))
+            .position(Position::Bottom),
+        );
+    }
+    let table = Table::new(rows)
+        .widths(&widths)
         .header(
-            ratatui::widgets::Row::new(header)
-                .style(Style::default().fg(Color::Yellow))
-                .bottom_margin(1),
+            ratatui::widgets::Row::new(header.into_iter().skip(skip))
+                .style(app.theme.heading())
+                .bottom_margin(row_margin),
         )
-        .block(Block::default().borders(Borders::ALL).title("Slothtime"));
+        .block(block);
 
     let mut state = TableState::default();
-    state.select(Some(app.cursor.row));
+    state.select(listed.iter().position(|&(i, _)| i == app.cursor.row));
 
     f.render_stateful_widget(table, area, &mut state);
 }
@@ -202,6 +537,26 @@ fn draw_status(f: &mut Frame, app: &App, area: Rect) {
         InputMode::Help => "Help",
         InputMode::ConfirmDeleteEntry => "Confirm Delete",
         InputMode::ConfirmClearEntries => "Confirm Clear",
+        InputMode::Report => "Report",
+        InputMode::Trash => "Trash",
+        InputMode::Log => "Log",
+        InputMode::Debug => "Debug",
+        InputMode::ExportMenu => "Export",
+        InputMode::Prompt => "Prompt",
+        InputMode::ConfirmQuit => "Confirm Quit",
+        InputMode::ConfirmExport => "Confirm Export",
+        InputMode::TimePicker => "Time Picker",
+        InputMode::ConfirmCoalesce => "Confirm Merge",
+        InputMode::ConfirmDedupe => "Duplicates",
+        InputMode::ConfirmFill => "Fill Day",
+        InputMode::ConfirmReplace => "Confirm Replace",
+        InputMode::Rollover => "New Day",
+        InputMode::ClockJump => "Clock Jump",
+        InputMode::History => "History",
+        InputMode::Repair => "Repair",
+        InputMode::PickDescription => "Pick Description",
+        InputMode::WhatsNew => "What's New",
+        InputMode::Validation => "Validate",
     };
 
     let col_name = match app.cursor.col {
@@ -227,14 +582,93 @@ fn draw_status(f: &mut Frame, app: &App, area: Rect) {
         "".to_string()
     };
 
+    // Surface the most used budget once it gets close to its cap
+    let budget_warning = app
+        .budget_usage()
+        .into_iter()
+        .filter(|u| u.is_warning())
+        .max_by(|a, b| a.ratio().total_cmp(&b.ratio()));
+
+    let row_warnings = app
+        .entries
+        .get(app.cursor.row)
+        .map(|entry| app.row_warnings(entry))
+        .filter(|warnings| !warnings.is_empty());
+
     let status = if let Some(ref message) = app.status_message {
         // Show status message if available
         message.clone()
+    } else if app.pending_go {
+        "Go to column: t task number, w work code, d description, s start, e end (or 1-5)"
+            .to_string()
+    } else if !app.row_jump.is_empty() {
+        format!("Go to row {} (Enter to jump, Esc to cancel)", app.row_jump)
+    } else if app.degraded {
+        let last_error = app
+            .save_failures
+            .last()
+            .map(|(_, e)| e.as_str())
+            .unwrap_or("unknown error");
+        format!(
+            "WARNING: {} saves failed ({}). Entries are only in memory | W save elsewhere",
+            app.save_failures.len(),
+            last_error
+        )
+    } else if let Some((shown, total, minutes)) = app
+        .filter_summary()
+        .filter(|_| matches!(app.mode, InputMode::Navigation))
+    {
+        format!(
+            "Showing {}/{} entries, {} | / to change the filter, Esc to show all",
+            shown,
+            total,
+            format_duration(minutes)
+        )
+    } else if let Some(usage) = budget_warning {
+        format!(
+            "Budget {}: {} is at {} of {} this {} | r for report",
+            if usage.is_exceeded() {
+                "exceeded"
+            } else {
+                "warning"
+            },
+            usage.work_code,
+            format_duration(usage.used_minutes),
+            format_duration(usage.budget_minutes),
+            usage.period.label()
+        )
+    } else if let Some(warnings) =
+        row_warnings.filter(|_| matches!(app.mode, InputMode::Navigation))
+    {
+        format!("Row {}: {}", app.cursor.row + 1, warnings.join(", "))
+    } else if matches!(app.mode, InputMode::Navigation)
+        && is_truncated(app.cursor.col, &current_value)
+    {
+        // Full value of a cell the table had to cut short
+        format!("{}: {}", col_name, current_value.replace('\n', " "))
+    } else if let Some((code, today, week)) = app
+        .work_code_minutes()
+        .filter(|_| matches!(app.mode, InputMode::Navigation))
+    {
+        format!(
+            "{}: {} today, {} this week | i to edit, ? help",
+            code,
+            format_duration(today),
+            format_duration(week)
+        )
+    } else if app.config.ui.keymap == Keymap::Simple {
+        format!(
+            "Row: {} | {}{} | F1 help, F2 export, F3 export menu, F8 delete row, F10 quit",
+            app.cursor.row + 1,
+            col_name,
+            length_counter(app)
+        )
     } else if matches!(app.mode, InputMode::Editing) {
         format!(
-            "Mode: {} | Editing {}: '{}' | Esc to exit, Tab to next cell",
+            "Mode: {} | Editing {}{}: '{}' | Esc to exit, Tab to next cell",
             mode,
             col_name,
+            length_counter(app),
             current_value
         )
     } else {
@@ -247,15 +681,46 @@ fn draw_status(f: &mut Frame, app: &App, area: Rect) {
         )
     };
 
+    let save_state = if app.dirty {
+        format!("{} unsaved", app.theme.symbol("●", "*"))
+    } else if let Some(saved) = app.last_saved {
+        format!("saved {} ago", format_elapsed(saved.elapsed().as_secs()))
+    } else {
+        "saved".to_string()
+    };
+
+    let style = if app.degraded {
+        app.theme.error()
+    } else {
+        Style::default()
+    };
     let paragraph = Paragraph::new(status)
-        .block(Block::default().borders(Borders::ALL).title("Status"))
+        .block(bordered(app).title(format!("Status - {}", save_state)))
+        .style(style)
         .wrap(Wrap { trim: true });
 
     f.render_widget(paragraph, area);
 }
 
+// ` (12/20)` while editing a column with a max_length, empty otherwise
+fn length_counter(app: &App) -> String {
+    match app.length_counter() {
+        Some((length, max)) if length > max => format!(" ({}/{}, over the limit)", length, max),
+        Some((length, max)) => format!(" ({}/{})", length, max),
+        None => String::new(),
+    }
+}
+
+fn format_elapsed(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}
+
 fn draw_popup(f: &mut Frame, app: &App, area: Rect) {
-    let popup_area = centered_rect(80, 60, area);
+    let popup_area = placed_rect(app.config.ui.popup, area);
     f.render_widget(Clear, popup_area);
 
     // Get task number for the title
@@ -269,37 +734,136 @@ fn draw_popup(f: &mut Frame, app: &App, area: Rect) {
     } else {
         format!("{} - Edit Time Entry", task_number)
     };
+    let title = format!("{}{}", title, length_counter(app));
 
-    let block = Block::default()
-        .title(title)
-        .borders(Borders::ALL);
+    // Leave room for the ActivityWatch panel next to the description
+    let (popup_area, hints_area) = if app.config.activitywatch.enabled {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+            .split(popup_area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (popup_area, None)
+    };
+
+    let block = bordered(app).title(title);
     let inner_area = block.inner(popup_area);
     f.render_widget(block, popup_area);
 
+    if let Some(hints_area) = hints_area {
+        draw_activity_hints(f, app, hints_area);
+    }
+
     if app.cursor.row < app.entries.len() {
-        let text = app.entries[app.cursor.row].time_entry.clone();
-        
-        // Add cursor when in editing mode
-        let display_text = if matches!(app.mode, InputMode::EditingPopup) {
-            let mut chars: Vec<char> = text.chars().collect();
-            if app.text_cursor <= chars.len() {
-                chars.insert(app.text_cursor, '|');
-            }
-            chars.into_iter().collect::<String>()
+        // Add cursor and selection when in editing mode
+        let editing = matches!(app.mode, InputMode::EditingPopup);
+        let description = &app.entries[app.cursor.row].time_entry;
+        let markdown = !editing && app.config.ui.markdown;
+        let lines: Vec<Line> = if editing {
+            editor_lines(&app.editor, Style::default())
+        } else if markdown {
+            markdown::lines(description, &app.theme)
         } else {
-            text
+            description.lines().map(Line::from).collect()
         };
-        
-        let lines: Vec<Line> = display_text.lines().map(|l| Line::from(l)).collect();
 
+        // Markdown keeps leading spaces so nested bullets stay indented
         let paragraph = Paragraph::new(lines)
             .scroll((app.popup_scroll as u16, 0))
-            .wrap(Wrap { trim: true });
+            .wrap(Wrap { trim: !markdown });
 
         f.render_widget(paragraph, inner_area);
     }
 }
 
+// The text being edited split into lines, with a `|` at the cursor and the selection reversed
+fn editor_lines(editor: &FieldEditor, style: Style) -> Vec<Line<'static>> {
+    fn flush(spans: &mut Vec<Span<'static>>, run: &mut String, style: Style) {
+        if !run.is_empty() {
+            spans.push(Span::styled(std::mem::take(run), style));
+        }
+    }
+
+    let selection = editor.selection().unwrap_or(0..0);
+    let selected_style = style.add_modifier(Modifier::REVERSED);
+    let mut lines = Vec::new();
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_selected = false;
+    // The empty grapheme at the end gives the cursor somewhere to go after the last one
+    for (i, grapheme) in editor.text().graphemes(true).chain([""]).enumerate() {
+        let current_style = if run_selected { selected_style } else { style };
+        if i == editor.cursor() {
+            flush(&mut spans, &mut run, current_style);
+            spans.push(Span::styled("|", style));
+        }
+        if grapheme == "\n" || grapheme == "\r\n" {
+            flush(&mut spans, &mut run, current_style);
+            lines.push(Line::from(std::mem::take(&mut spans)));
+            continue;
+        }
+        if selection.contains(&i) != run_selected {
+            flush(&mut spans, &mut run, current_style);
+            run_selected = !run_selected;
+        }
+        run.push_str(grapheme);
+    }
+    flush(&mut spans, &mut run, style);
+    lines.push(Line::from(spans));
+    lines
+}
+
+fn draw_activity_hints(f: &mut Frame, app: &App, area: Rect) {
+    let mut title = "Activity".to_string();
+    let lines: Vec<Line> = match &app.activity_hints {
+        Some(hints) if hints.row == app.cursor.row => {
+            title = format!("Activity {}-{}", hints.range.0, hints.range.1);
+            match &hints.windows {
+                None => vec![Line::from("Loading...")],
+                Some(Ok(windows)) if windows.is_empty() => vec![Line::from("No activity recorded")],
+                Some(Ok(windows)) => windows
+                    .iter()
+                    .map(|w| {
+                        let minutes = (w.seconds / 60.0).round() as i64;
+                        Line::from(format!("{:>3}m {} - {}", minutes, w.app, w.title))
+                    })
+                    .collect(),
+                Some(Err(e)) => vec![Line::from(format!("ActivityWatch unavailable: {}", e))],
+            }
+        }
+        _ => vec![Line::from("No activity loaded")],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(bordered(app).title(title))
+        .style(app.theme.muted())
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, area);
+}
+
+// Every pane and dialog has a full border, drawn in ASCII when ui.ascii_only is set
+fn bordered(app: &App) -> Block<'static> {
+    Block::default()
+        .borders(Borders::ALL)
+        .border_set(app.theme.border_set())
+}
+
+// The description popup's area, sized and placed by ui.popup
+fn placed_rect(popup: Popup, r: Rect) -> Rect {
+    let percent =
+        |length: u16, percent: u16| (length as u32 * percent.min(100) as u32 / 100) as u16;
+    let width = percent(r.width, popup.width);
+    let height = percent(r.height, popup.height);
+    Rect {
+        x: r.x + percent(r.width - width, popup.x),
+        y: r.y + percent(r.height - height, popup.y),
+        width,
+        height,
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -319,34 +883,548 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
-fn draw_help(f: &mut Frame, _app: &App, area: Rect) {
+fn draw_report(f: &mut Frame, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(10),
+            Constraint::Percentage(35),
+            Constraint::Percentage(65),
+        ])
+        .split(area);
+    if app.report_by_hour {
+        draw_hourly_hours(f, app, chunks[0]);
+    } else {
+        draw_daily_hours(f, app, chunks[0]);
+    }
+    draw_budgets(f, app, chunks[1]);
+    draw_task_totals(f, app, chunks[2]);
+}
+
+// Bar per day of the recent days, labelled with the day of the month
+fn draw_daily_hours(f: &mut Frame, app: &App, area: Rect) {
+    let days = &app.recent_days;
+    let total: i64 = days.iter().map(|(_, minutes)| minutes).sum();
+    let worked = days.iter().filter(|(_, minutes)| *minutes > 0).count();
+    let average = if worked > 0 { total / worked as i64 } else { 0 };
+    let block = bordered(app).title(format!(
+        "Hours per day, last {} days - {} total, {} on an average working day - h by hour of day",
+        days.len(),
+        format_duration(total),
+        format_duration(average)
+    ));
+
+    let bars: Vec<Bar> = days
+        .iter()
+        .map(|(date, minutes)| {
+            Bar::default()
+                .value(*minutes as u64)
+                .text_value(if *minutes > 0 {
+                    format!("{:.1}", *minutes as f64 / 60.0)
+                } else {
+                    String::new()
+                })
+                .label(Line::from(date.format("%d").to_string()))
+        })
+        .collect();
+
+    // Spread the bars over the width, leaving a gap of one between them
+    let count = days.len().max(1) as u16;
+    let bar_width = (area.width.saturating_sub(2) / count)
+        .saturating_sub(1)
+        .max(3);
+    let chart = BarChart::default()
+        .block(block)
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_set(app.theme.bar_set())
+        .bar_style(app.theme.accent())
+        .value_style(app.theme.selected());
+    f.render_widget(chart, area);
+}
+
+// Bar per hour of the day over the report range, to see when the work gets done
+fn draw_hourly_hours(f: &mut Frame, app: &App, area: Rect) {
+    let (from, to) = app.report_range;
+    let hours = app.hourly_minutes();
+    let busiest = (0..24)
+        .max_by_key(|&hour| (hours[hour], std::cmp::Reverse(hour)))
+        .unwrap_or(0);
+    let summary = if hours[busiest] > 0 {
+        format!("busiest {:02}:00-{:02}:00", busiest, busiest + 1)
+    } else {
+        "nothing logged".to_string()
+    };
+    let block = bordered(app).title(format!(
+        "Hours by hour of day {} to {} - {} - h per day",
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d"),
+        summary
+    ));
+
+    let bars: Vec<Bar> = hours
+        .iter()
+        .enumerate()
+        .map(|(hour, minutes)| {
+            Bar::default()
+                .value(*minutes as u64)
+                // Whole hours once they no longer fit a narrow bar
+                .text_value(match *minutes {
+                    0 => String::new(),
+                    1..=599 => format!("{:.1}", *minutes as f64 / 60.0),
+                    _ => format!("{:.0}", *minutes as f64 / 60.0),
+                })
+                .label(Line::from(format!("{:02}", hour)))
+        })
+        .collect();
+
+    let bar_width = (area.width.saturating_sub(2) / 24).saturating_sub(1).max(2);
+    let chart = BarChart::default()
+        .block(block)
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_set(app.theme.bar_set())
+        .bar_style(app.theme.accent())
+        .value_style(app.theme.selected());
+    f.render_widget(chart, area);
+}
+
+fn draw_budgets(f: &mut Frame, app: &App, area: Rect) {
+    let usages = app.budget_usage();
+    let block = bordered(app).title("Report: Budgets (Esc to close)");
+
+    if usages.is_empty() {
+        let paragraph = Paragraph::new("No budgets configured. Add [budgets.<WORK CODE>] sections with weekly_hours or monthly_hours to slothtime.toml.")
+            .block(block)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let header = ["Work Code", "Period", "Logged", "Budget", "Used", ""];
+    let rows: Vec<ratatui::widgets::Row> = usages
+        .iter()
+        .map(|u| {
+            let (flag, style) = if u.is_exceeded() {
+                ("EXCEEDED", app.theme.error())
+            } else if u.is_warning() {
+                ("near limit", app.theme.warning())
+            } else {
+                ("", Style::default())
+            };
+            ratatui::widgets::Row::new(vec![
+                u.work_code.clone(),
+                u.period.label().to_string(),
+                format_duration(u.used_minutes),
+                format_duration(u.budget_minutes),
+                format!("{:.0}%", u.ratio() * 100.0),
+                flag.to_string(),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(15),
+        Constraint::Length(8),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(6),
+        Constraint::Length(10),
+    ];
+    let table = Table::new(rows)
+        .widths(&widths)
+        .header(
+            ratatui::widgets::Row::new(header)
+                .style(app.theme.heading())
+                .bottom_margin(1),
+        )
+        .block(block);
+
+    f.render_widget(table, area);
+}
+
+fn draw_task_totals(f: &mut Frame, app: &App, area: Rect) {
+    let (from, to) = app.report_range;
+    let totals = app.task_totals();
+    let block = bordered(app).title(format!(
+        "Task Totals {} to {} - f/t set dates, [/] month, w this week, e export totals, x export entries, g export timesheet",
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d")
+    ));
+
+    if totals.is_empty() {
+        let paragraph = Paragraph::new("No time logged in this range.")
+            .block(block)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let total_minutes: i64 = totals.iter().map(|t| t.minutes).sum();
+    let mut rows: Vec<ratatui::widgets::Row> = totals
+        .iter()
+        .map(|t| {
+            let task = if t.task_number.is_empty() {
+                "(no task)".to_string()
+            } else {
+                t.task_number.clone()
+            };
+            ratatui::widgets::Row::new(vec![
+                task,
+                format!("{:.2}", t.hours()),
+                format_duration(t.minutes),
+                t.days.to_string(),
+            ])
+        })
+        .collect();
+    rows.push(
+        ratatui::widgets::Row::new(vec![
+            "Total".to_string(),
+            format!("{:.2}", total_minutes as f64 / 60.0),
+            format_duration(total_minutes),
+            String::new(),
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD)),
+    );
+
+    let widths = [
+        Constraint::Length(20),
+        Constraint::Length(8),
+        Constraint::Length(10),
+        Constraint::Length(6),
+    ];
+    let table = Table::new(rows)
+        .widths(&widths)
+        .header(
+            ratatui::widgets::Row::new(["Task Number", "Hours", "Time", "Days"])
+                .style(app.theme.heading())
+                .bottom_margin(1),
+        )
+        .block(block);
+
+    f.render_widget(table, area);
+}
+
+fn draw_trash(f: &mut Frame, app: &App, area: Rect) {
+    let block = bordered(app).title("Trash - Enter/r restore, x delete forever, Esc close");
+
+    if app.trash.is_empty() {
+        let paragraph = Paragraph::new("The trash is empty.").block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let header = [
+        "Deleted",
+        "Task Number",
+        "Work Code",
+        "Time Entry",
+        "Start Time",
+        "End Time",
+    ];
+    let rows: Vec<ratatui::widgets::Row> = app
+        .trash
+        .iter()
+        .map(|t| {
+            ratatui::widgets::Row::new(vec![
+                t.deleted_at.format("%Y-%m-%d %H:%M").to_string(),
+                text::single_line(&t.entry.task_number),
+                text::single_line(&t.entry.work_code),
+                text::single_line(&t.entry.time_entry),
+                t.entry.start_time.clone(),
+                t.entry.end_time.clone(),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(17),
+        Constraint::Length(15),
+        Constraint::Length(15),
+        Constraint::Length(30),
+        Constraint::Length(12),
+        Constraint::Length(12),
+    ];
+    let table = Table::new(rows)
+        .widths(&widths)
+        .header(
+            ratatui::widgets::Row::new(header)
+                .style(app.theme.heading())
+                .bottom_margin(1),
+        )
+        .block(block)
+        .highlight_style(app.theme.selected())
+        .highlight_symbol("> ");
+
+    let mut state = TableState::default();
+    state.select(Some(app.trash_selected));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_log(f: &mut Frame, app: &App, area: Rect) {
+    let (title, lines): (String, Vec<Line>) = match &app.log_view {
+        Ok((path, lines)) => (
+            format!(
+                "Log {} - Up/Down scroll, r reload, Esc close",
+                path.display()
+            ),
+            lines
+                .iter()
+                .map(|line| {
+                    let style = if line.contains(" ERROR ") {
+                        app.theme.error()
+                    } else if line.contains(" WARN ") {
+                        app.theme.warning()
+                    } else if line.contains(" DEBUG ") {
+                        app.theme.muted()
+                    } else {
+                        Style::default()
+                    };
+                    Line::styled(line.clone(), style)
+                })
+                .collect(),
+        ),
+        Err(e) => (
+            "Log - Esc close".to_string(),
+            vec![Line::from(format!("No log to show: {}", e))],
+        ),
+    };
+
+    // Newest lines at the bottom, scrolled up from there
+    let height = area.height.saturating_sub(2) as usize;
+    let end = lines
+        .len()
+        .saturating_sub(app.log_scroll.min(lines.len().saturating_sub(height)));
+    let start = end.saturating_sub(height);
+    let paragraph = Paragraph::new(lines[start..end].to_vec()).block(bordered(app).title(title));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_debug(f: &mut Frame, app: &App, area: Rect) {
+    let width = app
+        .debug_view
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = app
+        .debug_view
+        .iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:width$}  ", label, width = width),
+                    app.theme.heading(),
+                ),
+                Span::raw(value.clone()),
+            ])
+        })
+        .collect();
+    let paragraph = Paragraph::new(lines)
+        .block(bordered(app).title("Debug - r refresh, Esc close"))
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+fn draw_whats_new(f: &mut Frame, app: &App, area: Rect) {
+    let title = format!(
+        "What's new in Slothtime {} - Up/Down scroll, any other key closes",
+        crate::changelog::VERSION
+    );
+    let paragraph = Paragraph::new(markdown::lines(&app.whats_new, &app.theme))
+        .block(bordered(app).title(title))
+        .wrap(Wrap { trim: false })
+        .scroll((app.whats_new_scroll, 0));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_history(f: &mut Frame, app: &App, area: Rect) {
+    let Some(history) = &app.history else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+
+    let results = history.results();
+    // Older days are only read once the matches so far have been scrolled through
+    let older = match history.unread_count() {
+        0 => String::new(),
+        unread => format!(", {} older read as you scroll", unread),
+    };
+    let search =
+        Paragraph::new(format!("{}|", history.query())).block(bordered(app).title(format!(
+            "Search history - {} entries in {} archived days{}",
+            results.len(),
+            history.day_count(),
+            older
+        )));
+    f.render_widget(search, chunks[0]);
+
+    let block = bordered(app)
+        .title("Enter copy into the current row, Ctrl+Y copy the description, Esc close");
+    if results.is_empty() {
+        let text = if history.day_count() + history.unread_count() == 0 {
+            "Nothing archived yet, days are archived when you export them."
+        } else {
+            "No archived entries match."
+        };
+        f.render_widget(Paragraph::new(text).block(block), chunks[1]);
+        return;
+    }
+
+    let header = ["Date", "Task Number", "Work Code", "Time", "Time Entry"];
+    let rows: Vec<ratatui::widgets::Row> = results
+        .iter()
+        .map(|(date, entry)| {
+            ratatui::widgets::Row::new(vec![
+                date.format("%Y-%m-%d").to_string(),
+                text::single_line(&entry.task_number),
+                text::single_line(&entry.work_code),
+                entry.calculate_task_time().unwrap_or_default(),
+                text::single_line(&entry.time_entry),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(12),
+        Constraint::Length(15),
+        Constraint::Length(15),
+        Constraint::Length(6),
+        // The description gets whatever is left
+        Constraint::Length(chunks[1].width.saturating_sub(56).max(20)),
+    ];
+    let table = Table::new(rows)
+        .widths(&widths)
+        .header(
+            ratatui::widgets::Row::new(header)
+                .style(app.theme.heading())
+                .bottom_margin(1),
+        )
+        .block(block)
+        .highlight_style(app.theme.selected())
+        .highlight_symbol("> ");
+
+    let mut state = TableState::default();
+    state.select(Some(history.selected));
+    f.render_stateful_widget(table, chunks[1], &mut state);
+}
+
+const SIMPLE_HELP_TEXT: &str = r#"
+Slothtime TUI - Help (simple keymap)
+
+You are always editing the selected cell, just start typing.
+  Arrow Keys - Move between cells (move the text cursor in the description popup)
+  Enter      - Move down a row (new line in the description popup)
+  Tab        - Move to next column
+  Shift+Tab  - Move to previous column
+  Home / End - Jump to start / end of the cell
+  Backspace  - Delete characters
+  F1         - Show this help
+  F2, Ctrl+S - Export (CSV, ODS or HTML, see export.format)
+  F3         - Export menu (all, new/changed or checked rows)
+  F4         - Report view (daily hours, budgets and task totals)
+  F5         - Trash
+  F6         - Search archived days and reuse an old entry
+  F7         - Edit the description in $EDITOR
+  F8         - Delete current entry (with confirmation, moved to trash)
+  F10, Ctrl+Q - Quit
+
+Press any key to return.
+"#;
+
+fn draw_help(f: &mut Frame, app: &App, area: Rect) {
+    if app.config.ui.keymap == Keymap::Simple {
+        let paragraph = Paragraph::new(SIMPLE_HELP_TEXT)
+            .block(bordered(app).title("Help"))
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
     let help_text = r#"
 Slothtime TUI - Help
 
 Navigation Mode:
   i          - Enter edit mode (vim-style)
-  dd         - Delete current entry (with confirmation)
+  Enter      - Open the description editor for the current row
+  o / O      - Insert a blank row below / above and start editing it
+  C          - Continue the finished task under the cursor in a new row starting now (:continue)
+  E          - Edit the description in $EDITOR (Ctrl+O in the description editor)
+  0-9        - Start editing a time cell (when ui.type_to_edit is on)
+  + / -      - On a time cell, type minutes to add or subtract, e.g. +15
+  Space      - Check/uncheck the row for a checked-rows export (Ctrl+E, x)
+  dd         - Delete current entry (with confirmation, moved to trash)
+  D          - Clear the current cell, u puts it back
+  b          - Open the row's ticket (links.task_url) or a URL from its description, again for the next
+  Y          - Copy the day's entries and totals as a Markdown table, for a status email
+  T          - Open the trash to restore or purge deleted entries
+  L          - Show the log of saves, exports and integration failures (:log)
+  V          - List what needs fixing before an export, without exporting (:validate)
+  :legend    - Show or hide the legend of row marks under the table
+  :relative  - Show or hide the When column, e.g. running 45m or 2h ago
+  :group     - Fold rows on the same task number into one with their total, Enter opens a group
+  :debug     - Show file sizes, save and redraw times and the latest logged problems
+  :changelog - Show what changed in each version
+  W          - Save entries to another path (when the normal save fails)
   Tab        - Move to next column
   Shift+Tab  - Move to previous column
+  gt gw gd gs ge - Jump to the Task Number, Work Code, Description, Start or End column (or g1-g5)
   Arrow Keys - Navigate up/down/left/right
+  12 Enter   - Jump to row 12
+  :12 Enter  - Jump to row 12 (use this on Start/End cells when ui.type_to_edit is on)
+  :          - Command line (row number to jump to it)
+  :coalesce  - Merge entries shorter than cleanup.min_minutes into their neighbours (previewed first)
+  :dedupe    - Find rows on the same task with overlapping times, then delete or merge them
+  :sort      - Order the rows by start time
+  :copyday a [b] - Copy day a's entries (today, yesterday or YYYY-MM-DD) to today or day b, notimes leaves out times
+  :%s/a/b/   - Replace a (a regular expression) with b in the current column, flags: a every column, i ignore case
+  :fill      - Fill the gaps between workday.start and workday.end with new rows or the selected entry
+  :lock      - Lock the day once it's submitted so its entries can't be changed, :unlock to edit again
+  :protect [columns] - Keep the checked or current row's task number and times from changing, :unprotect to lift it
+  /          - Only show rows containing some text, Esc shows all again
+  N          - Write a note for the day, saved with it and added to exports
   ?          - Show this help
   Ctrl+Y     - Copy current field to clipboard
-  Ctrl+S     - Export to CSV
+  Ctrl+S     - Export (CSV, ODS or HTML, see export.format)
+  Ctrl+E     - Export menu (all, new/changed or checked rows)
   Ctrl+X     - Clear all entries (with confirmation)
-  q          - Quit
+  I          - Import today's meetings from the configured .ics file
+  H          - Search archived days and copy an old entry into the current row
+  r          - Open the report view (hours of the last 14 days, budgets and task totals)
+  q          - Quit (asks first if an entry is still running or half filled in, see ui.quit)
+  :q         - Quit, Ctrl+C works too (outside edit mode, where it copies)
 
 Edit Mode:
   Esc        - Exit edit mode
   Tab        - Move to next column (stay in edit)
   Enter      - Move to next row (stay in edit)
   Type       - Insert characters
-  Backspace  - Delete characters
+  Backspace  - Delete characters (Delete for the one after the cursor)
+  Shift+Arrows - Select text, Ctrl+A selects the whole field
+  Ctrl+C/X/V - Copy, cut and paste the selection
+  Ctrl+Z/R   - Undo and redo changes to the field
+  Alt+U/L    - Upper- or lower-case the word at the cursor (or the selection)
+  Alt+C      - Capitalize the first letter of each sentence in the field
+  Ctrl/Alt+Arrows - Resize / move the description popup (remembered in the config)
+  Ctrl+T     - Pick a Start/End Time with the arrow keys (n now, p previous end)
+  Ctrl+L     - On a Start/End Time, also move the touching neighbour's time this edit (ui.ripple_times flips it)
+
+Description Editor:
+  ;name Tab  - Expand the [snippets] template called name
+  Alt+J      - Join the description's lines (or the selected ones) into one
+  Tab        - Jump to the snippet's next {placeholder}, or to the next column
 
 Press any key to return to navigation.
 "#;
 
     let paragraph = Paragraph::new(help_text)
-        .block(Block::default().borders(Borders::ALL).title("Help"))
+        .block(bordered(app).title("Help"))
         .wrap(Wrap { trim: true });
 
     f.render_widget(paragraph, area);
@@ -368,14 +1446,13 @@ fn draw_confirm_delete_dialog(f: &mut Frame, app: &App, area: Rect) {
     };
 
     let text = format!(
-        "Delete {}?\n\nPress 'y' to confirm, 'n' or Esc to cancel.",
+        "Delete {}?\n\nIt will be moved to the trash (T).\n\nPress 'y' to confirm, 'n' or Esc to cancel.",
         task_info
     );
 
-    let block = Block::default()
+    let block = bordered(app)
         .title("Confirm Delete")
-        .borders(Borders::ALL)
-        .style(Style::default().fg(Color::Red));
+        .style(app.theme.error());
 
     let paragraph = Paragraph::new(text)
         .block(block)
@@ -385,16 +1462,566 @@ fn draw_confirm_delete_dialog(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(paragraph, popup_area);
 }
 
-fn draw_confirm_clear_dialog(f: &mut Frame, _app: &App, area: Rect) {
+fn draw_export_menu(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(50, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let pending = app
+        .entries
+        .iter()
+        .filter(|e| crate::export::ExportScope::Changed.includes(e))
+        .count();
+    let checked = app.entries.iter().filter(|e| e.checked).count();
+    let email = if app.config.email.to.is_empty() {
+        "set email.to first".to_string()
+    } else {
+        format!("to {}", app.config.email.to.join(", "))
+    };
+    let text = format!(
+        "[a] All rows\n[c] New/changed rows only ({} pending)\n[x] Checked rows only ({} checked)\n[p] All rows, in a password-protected zip\n[m] All rows, emailed ({})\n\nEsc to cancel.",
+        pending, checked, email
+    );
+
+    let block = bordered(app).title("Export").style(app.theme.accent());
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .wrap(Wrap { trim: true })
+        .alignment(ratatui::layout::Alignment::Center);
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_time_picker(f: &mut Frame, app: &App, area: Rect) {
+    let Some(picker) = &app.time_picker else {
+        return;
+    };
+    let width = 50.min(area.width);
+    let height = 7.min(area.height);
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, popup_area);
+
+    // Brackets mark the part Up/Down changes, not only its colour
+    let part = |format: &str, selected: bool| {
+        let text = picker.time.format(format).to_string();
+        if selected {
+            Span::styled(format!("[{}]", text), app.theme.editing())
+        } else {
+            Span::raw(format!(" {} ", text))
+        }
+    };
+    let lines = vec![
+        Line::from(""),
+        Line::from(vec![
+            part("%H", !picker.minutes_selected),
+            Span::raw(":"),
+            part("%M", picker.minutes_selected),
+        ])
+        .alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(format!(
+            "Up/Down {pm}1h or {pm}{}m, Left/Right hours/minutes",
+            app.config.ui.time_step,
+            pm = app.theme.symbol("±", "+/-")
+        ))
+        .alignment(Alignment::Center),
+        Line::from("n now, p previous end, Enter set, Esc cancel").alignment(Alignment::Center),
+    ];
+    let title = if app.cursor.col == 4 {
+        "Start Time"
+    } else {
+        "End Time"
+    };
+    let paragraph = Paragraph::new(lines).block(bordered(app).title(title));
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_prompt(f: &mut Frame, app: &App, area: Rect) {
+    let Some(prompt) = &app.prompt else {
+        return;
+    };
+    let width = area.width * 6 / 10;
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(3) / 2,
+        width,
+        height: 3.min(area.height),
+    };
+    f.render_widget(Clear, popup_area);
+
+    let input = if prompt.kind.is_secret() {
+        "*".repeat(prompt.input.chars().count())
+    } else {
+        prompt.input.clone()
+    };
+    let paragraph = Paragraph::new(format!("{}|", input)).block(bordered(app).title(format!(
+        "{} - Enter to confirm, Esc to cancel",
+        prompt.kind.label()
+    )));
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_confirm_quit_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let running = app.running_entry();
+    let save_failed = app.dirty && !app.save_failures.is_empty();
+    let mut risks = Vec::new();
+    if save_failed {
+        if let Some((at, e)) = app.save_failures.last() {
+            risks.push(format!(
+                "Your entries could not be saved: {} ({})",
+                e,
+                at.format("%H:%M:%S")
+            ));
+        }
+    }
+    if let Some(row) = running {
+        risks.push(format!(
+            "Row {} is still running (started {})",
+            row + 1,
+            app.entries[row].start_time
+        ));
+    }
+    if app.current_row_incomplete() && running != Some(app.cursor.row) {
+        risks.push(format!(
+            "Row {} is only partly filled in",
+            app.cursor.row + 1
+        ));
+    }
+
+    if risks.is_empty() {
+        risks.push("Quit Slothtime?".to_string());
+    }
+
+    let mut options = Vec::new();
+    if let Some(row) = running {
+        options.push(format!("[s] Stop row {} now, save and quit", row + 1));
+    }
+    if save_failed {
+        options.push("[w] Save to another path".to_string());
+    }
+    options.push("[q] Quit anyway".to_string());
+    options.push("Esc to keep working".to_string());
+
+    let text = format!("{}\n\n{}", risks.join("\n"), options.join("\n"));
+
+    let block = bordered(app).title("Quit?").style(app.theme.error());
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .wrap(Wrap { trim: true })
+        .alignment(ratatui::layout::Alignment::Center);
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_confirm_export_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 50, area);
+    f.render_widget(Clear, popup_area);
+
+    let scope = app
+        .pending_export
+        .unwrap_or(crate::export::ExportScope::All);
+    let mut lines: Vec<Line> = app
+        .export_warnings(scope)
+        .into_iter()
+        .map(|(row, warnings)| Line::from(format!("Row {}: {}", row + 1, warnings.join(", "))))
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "Export anyway? 'y' to export, 'n' or Esc to go back and fix them.",
+    ));
+
+    let block = bordered(app)
+        .title("Check These Entries")
+        .style(app.theme.warning());
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_confirm_coalesce_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    for merge in &app.coalesce_plan {
+        let entry = &merge.entry;
+        lines.push(Line::from(format!(
+            "Rows {}-{} become {} {} {}-{} ({})",
+            merge.rows.start + 1,
+            merge.rows.end,
+            entry.task_number,
+            entry.work_code,
+            entry.start_time,
+            entry.end_time,
+            format_duration(entry.duration_minutes().unwrap_or(0))
+        )));
+        if !entry.time_entry.is_empty() {
+            lines.push(Line::from(Span::styled(
+                format!("  {}", entry.time_entry.replace('\n', " ")),
+                app.theme.muted(),
+            )));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "Merge them? 'y' to merge (the original rows go to the trash), 'n' or Esc to cancel.",
+    ));
+
+    let block = bordered(app)
+        .title("Merge Short Entries")
+        .style(app.theme.warning());
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_confirm_dedupe_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    for duplicate in &app.duplicates {
+        let rows: Vec<String> = duplicate
+            .rows
+            .iter()
+            .map(|row| (row + 1).to_string())
+            .collect();
+        lines.push(Line::from(format!(
+            "Rows {} {}",
+            rows.join(", "),
+            if duplicate.exact {
+                "are identical"
+            } else {
+                "overlap"
+            }
+        )));
+        for &row in &duplicate.rows {
+            let entry = &app.entries[row];
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "  {} {} {}-{} {}",
+                    entry.task_number,
+                    entry.work_code,
+                    entry.start_time,
+                    entry.end_time,
+                    entry.time_entry.replace('\n', " ")
+                ),
+                app.theme.muted(),
+            )));
+        }
+        if !duplicate.exact {
+            let merged = &duplicate.merged;
+            lines.push(Line::from(format!(
+                "  merged: {}-{} {}",
+                merged.start_time,
+                merged.end_time,
+                merged.time_entry.replace('\n', " ")
+            )));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "'d' keeps the first row of each and deletes the rest, 'm' merges them into one entry, 'n' or Esc cancels. Removed rows go to the trash.",
+    ));
+
+    let block = bordered(app)
+        .title("Duplicate Entries")
+        .style(app.theme.warning());
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_confirm_replace_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    for change in &app.replace_plan {
+        lines.push(Line::from(format!(
+            "Row {} {}:",
+            change.row + 1,
+            TimeEntry::field_name(change.col).unwrap_or_default()
+        )));
+        lines.push(Line::from(Span::styled(
+            format!("  - {}", change.before.replace('\n', " ")),
+            app.theme.muted(),
+        )));
+        lines.push(Line::from(format!(
+            "  + {}",
+            change.after.replace('\n', " ")
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!(
+        "Change these {} cell(s)? 'y' to replace, 'n' or Esc to cancel.",
+        app.replace_plan.len()
+    )));
+
+    let block = bordered(app)
+        .title("Find and Replace")
+        .style(app.theme.warning());
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_confirm_fill_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let workday = &app.config.workday;
+    let missing: i64 = app.fill_gaps.iter().map(|gap| gap.minutes()).sum();
+    let mut lines = vec![
+        Line::from(format!(
+            "{} of {}-{} has no entry:",
+            format_duration(missing),
+            workday.start,
+            workday.end
+        )),
+        Line::from(""),
+    ];
+    for gap in &app.fill_gaps {
+        lines.push(Line::from(format!(
+            "  {}-{} ({})",
+            gap.start.format("%H:%M"),
+            gap.end.format("%H:%M"),
+            format_duration(gap.minutes())
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!(
+        "'p' adds an {} row for each gap, 's' stretches row {} over the gaps next to it (Up/Down picks another row), 'n' or Esc cancels.",
+        fill::PLACEHOLDER,
+        app.cursor.row + 1
+    )));
+
+    let block = bordered(app).title("Fill Day").style(app.theme.accent());
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_pick_description_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let Some(pick) = &app.description_pick else {
+        return;
+    };
+    let popup_area = centered_rect(60, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut lines = vec![
+        Line::from(format!("{} was described as:", pick.task)),
+        Line::from(""),
+    ];
+    for (i, description) in pick.descriptions.iter().enumerate() {
+        let text = format!("{} {}", i + 1, description.replace('\n', " "));
+        lines.push(if i == pick.selected {
+            Line::styled(text, app.theme.selected())
+        } else {
+            Line::from(text)
+        });
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!(
+        "Enter or a number fills in row {}'s description, Up/Down picks one, Esc skips.",
+        pick.row + 1
+    )));
+
+    let block = bordered(app)
+        .title("Earlier Descriptions")
+        .style(app.theme.accent());
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_validation_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, issue) in app.issues.iter().enumerate() {
+        let text = format!("Row {}: {}", issue.row + 1, issue.message);
+        lines.push(if i == app.issue_selected {
+            Line::styled(text, app.theme.selected())
+        } else {
+            Line::from(text)
+        });
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "Enter goes to the selected problem, Up/Down picks one, Esc closes.",
+    ));
+
+    let block = bordered(app)
+        .title(format!(
+            "{} Problem(s) to Fix Before Exporting",
+            app.issues.len()
+        ))
+        .style(app.theme.warning());
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_rollover_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let today = chrono::Local::now().date_naive();
+    let mut lines = vec![
+        Line::from(format!(
+            "It's {} now, but the sheet holds the entries of {}.",
+            today.format("%a %Y-%m-%d"),
+            app.day.format("%a %Y-%m-%d")
+        )),
+        Line::from(""),
+    ];
+    match crate::time_entry::running_entry(&app.entries) {
+        Some(row) => {
+            let entry = &app.entries[row];
+            lines.push(Line::from(format!(
+                "{} {} has been running since {}.",
+                entry.task_number, entry.work_code, entry.start_time
+            )));
+            lines.push(Line::from(""));
+            lines.push(Line::from(
+                "'n' archives that day and starts today's sheet, stopping the entry at 23:59 and continuing it from 00:00.",
+            ));
+            lines.push(Line::from(
+                "'s' does the same but leaves the entry stopped.",
+            ));
+        }
+        None => lines.push(Line::from(
+            "'n' archives that day and starts today's sheet.",
+        )),
+    }
+    lines.push(Line::from(
+        "'k' or Esc keeps working on the old sheet, its exports are still dated that day.",
+    ));
+
+    let block = bordered(app).title("New Day").style(app.theme.accent());
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_clock_jump_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let (Some(jump), Some(row)) = (app.clock_jump, app.running_entry()) else {
+        return;
+    };
+    let popup_area = centered_rect(60, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let entry = &app.entries[row];
+    let before = jump.before.format("%H:%M");
+    let mut lines = vec![
+        Line::from(format!(
+            "The clock jumped from {} to {}, but only {} actually passed. The computer was probably asleep.",
+            before,
+            jump.after.format("%H:%M"),
+            format_duration((jump.after - jump.before - jump.skipped).num_minutes())
+        )),
+        Line::from(""),
+        Line::from(format!(
+            "{} {} has been running since {} and would count the {} as worked.",
+            entry.task_number,
+            entry.work_code,
+            entry.start_time,
+            format_duration(jump.skipped.num_minutes())
+        )),
+        Line::from(""),
+        Line::from(format!("'s' stops it at {}.", before)),
+    ];
+    if jump.after.date_naive() == jump.before.date_naive() {
+        lines.push(Line::from(format!(
+            "'c' stops it at {} and continues it in a new row from {}.",
+            before,
+            jump.after.format("%H:%M")
+        )));
+    }
+    lines.push(Line::from(
+        "'k' or Esc keeps it running, e.g. if you were working away from the computer.",
+    ));
+
+    let block = bordered(app).title("Clock Jump").style(app.theme.warning());
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_repair_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let Some(problem) = &app.load_problem else {
+        return;
+    };
+    let popup_area = centered_rect(70, 50, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut lines = vec![
+        Line::from("Your saved entries (entries.json) could not be read:"),
+        Line::from(Span::styled(problem.error.clone(), app.theme.error())),
+        Line::from(""),
+    ];
+    if let Some((path, entries)) = &problem.backup {
+        lines.push(Line::from(format!(
+            "b - Restore the newest backup, {} ({} entries)",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            entries.len()
+        )));
+    }
+    if !problem.salvaged.is_empty() {
+        lines.push(Line::from(format!(
+            "s - Keep the {} entries before the damage",
+            problem.salvaged.len()
+        )));
+    }
+    lines.push(Line::from("n - Start with an empty sheet"));
+    lines.push(Line::from("q - Quit and leave the file as it is"));
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "b, s and n keep the damaged file next to it as entries.corrupt-<time>.json.",
+    ));
+
+    let block = bordered(app)
+        .title("Repair Entries")
+        .style(app.theme.warning());
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_confirm_clear_dialog(f: &mut Frame, app: &App, area: Rect) {
     let popup_area = centered_rect(50, 30, area);
     f.render_widget(Clear, popup_area);
 
-    let text = "Clear all entries?\n\nThis will delete all time entries.\n\nPress 'y' to confirm, 'n' or Esc to cancel.";
+    let text = "Clear all entries?\n\nThis will move all time entries to the trash.\n\nPress 'y' to confirm, 'n' or Esc to cancel.";
 
-    let block = Block::default()
+    let block = bordered(app)
         .title("Confirm Clear All")
-        .borders(Borders::ALL)
-        .style(Style::default().fg(Color::Red));
+        .style(app.theme.error());
 
     let paragraph = Paragraph::new(text)
         .block(block)
@@ -406,7 +2033,7 @@ fn draw_confirm_clear_dialog(f: &mut Frame, _app: &App, area: Rect) {
 
 fn draw_time_bar(f: &mut Frame, app: &App, area: Rect) {
     let now = Local::now();
-    
+
     let time_text = if app.config.ui.time_bar.format_24hr {
         if app.config.ui.time_bar.show_date {
             format!("{} | {}", now.format("%H:%M:%S"), now.format("%d"))
@@ -422,8 +2049,8 @@ fn draw_time_bar(f: &mut Frame, app: &App, area: Rect) {
     };
 
     let paragraph = Paragraph::new(time_text)
-        .style(Style::default().fg(Color::Gray))
+        .style(app.theme.muted())
         .alignment(Alignment::Center);
 
     f.render_widget(paragraph, area);
-}
\ No newline at end of file
+}
@@ -0,0 +1,186 @@
+use chrono::NaiveTime;
+
+use crate::checks;
+use crate::columns::InputRules;
+use crate::config::{Checks, Mapping};
+use crate::mapping;
+use crate::time_entry::TimeEntry;
+
+// A problem with a row, and the column to fix it in when it's about one cell
+pub struct Issue {
+    pub row: usize,
+    pub col: Option<usize>,
+    pub message: String,
+}
+
+// A work code the export's [mappings] table doesn't list, `mapping` being the
+// destination and table from mapping::for_export
+pub fn unmapped(entry: &TimeEntry, mapping: Option<(&str, &Mapping)>) -> Option<String> {
+    let (destination, mapping) = mapping?;
+    mapping::unmapped(mapping, entry).then(|| {
+        format!(
+            "Work Code {} has no {} mapping",
+            entry.work_code.trim(),
+            destination
+        )
+    })
+}
+
+// Everything worth fixing before an export: empty cells, times that aren't HH:MM,
+// rows overlapping each other, the [checks] warnings, [columns] rules and [mappings].
+// Rows left entirely empty are never exported, so they're skipped.
+pub fn issues(
+    entries: &[TimeEntry],
+    checks: &Checks,
+    mapping: Option<(&str, &Mapping)>,
+    rules: &InputRules,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut spans: Vec<(usize, NaiveTime, NaiveTime)> = Vec::new();
+    for (row, entry) in entries.iter().enumerate() {
+        if entry.is_entirely_empty() {
+            continue;
+        }
+        let missing: Vec<usize> = (1..=5)
+            .filter(|&col| {
+                entry
+                    .field(col)
+                    .is_some_and(|value| value.trim().is_empty())
+            })
+            .collect();
+        if let Some(&col) = missing.first() {
+            let names: Vec<&str> = missing
+                .iter()
+                .filter_map(|&col| TimeEntry::field_name(col))
+                .collect();
+            issues.push(Issue {
+                row,
+                col: Some(col),
+                message: format!("{} missing", names.join(", ")),
+            });
+        }
+        for col in [4, 5] {
+            let value = entry.field(col).map_or("", |value| value.trim());
+            if !value.is_empty() && TimeEntry::parse_time(value).is_none() {
+                issues.push(Issue {
+                    row,
+                    col: Some(col),
+                    message: format!(
+                        "{} {} is not a time, use HH:MM",
+                        TimeEntry::field_name(col).unwrap_or_default(),
+                        value
+                    ),
+                });
+            }
+        }
+        issues.extend(
+            checks::warnings(entry, checks)
+                .into_iter()
+                .chain(rules.problems(entry))
+                .chain(unmapped(entry, mapping))
+                .map(|message| Issue {
+                    row,
+                    col: None,
+                    message,
+                }),
+        );
+
+        let start = TimeEntry::parse_time(&entry.start_time);
+        let end = TimeEntry::parse_time(&entry.end_time);
+        let (Some(start), Some(end)) = (start, end) else {
+            continue;
+        };
+        if end <= start {
+            continue;
+        }
+        // Touching is fine, 09:00-10:00 then 10:00-11:00 doesn't overlap
+        for &(other, other_start, other_end) in &spans {
+            if start < other_end && other_start < end {
+                issues.push(Issue {
+                    row,
+                    col: Some(4),
+                    message: format!(
+                        "Overlaps row {} ({}-{})",
+                        other + 1,
+                        other_start.format("%H:%M"),
+                        other_end.format("%H:%M")
+                    ),
+                });
+            }
+        }
+        spans.push((row, start, end));
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Columns;
+    use crate::time_entry::entry;
+    use std::collections::HashMap;
+
+    fn issues_of(
+        entries: &[TimeEntry],
+        mapping: Option<(&str, &Mapping)>,
+    ) -> Vec<(usize, Option<usize>, String)> {
+        let rules = InputRules::new(&Columns::default(), &std::env::temp_dir()).unwrap();
+        issues(entries, &Checks::default(), mapping, &rules)
+            .into_iter()
+            .map(|issue| (issue.row, issue.col, issue.message))
+            .collect()
+    }
+
+    #[test]
+    fn empty_cells_and_bad_times_are_listed() {
+        let entries = vec![
+            entry("T-1", "DEV", "09:00", "10:00").described("Work"),
+            TimeEntry::new(),
+            entry("T-1", "", "9am", "").described("Work"),
+        ];
+        assert_eq!(
+            issues_of(&entries, None),
+            vec![
+                (2, Some(2), "Work Code, End Time missing".to_string()),
+                (
+                    2,
+                    Some(4),
+                    "Start Time 9am is not a time, use HH:MM".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_rows_are_flagged_but_touching_ones_are_not() {
+        let entries = vec![
+            entry("T-1", "DEV", "09:00", "10:00").described("Work"),
+            entry("T-1", "DEV", "10:00", "11:00").described("Work"),
+            entry("T-1", "DEV", "09:30", "10:30").described("Work"),
+        ];
+        assert_eq!(
+            issues_of(&entries, None),
+            vec![
+                (2, Some(4), "Overlaps row 1 (09:00-10:00)".to_string()),
+                (2, Some(4), "Overlaps row 2 (10:00-11:00)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unmapped_work_codes_name_the_destination() {
+        let mapping = Mapping {
+            work_codes: HashMap::from([("DEV".to_string(), "1001".to_string())]),
+            ..Mapping::default()
+        };
+        let entries = vec![
+            entry("T-1", "DEV", "09:00", "10:00").described("Work"),
+            entry("T-1", "QA", "10:00", "11:00").described("Work"),
+        ];
+        assert_eq!(
+            issues_of(&entries, Some(("csv", &mapping))),
+            vec![(1, None, "Work Code QA has no csv mapping".to_string())]
+        );
+        assert_eq!(unmapped(&entries[1], None), None);
+    }
+}